@@ -1,3 +1,8 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
 fn get_git_hash() -> String {
   use std::process::Command;
 
@@ -21,7 +26,233 @@ fn get_git_hash() -> String {
   format!("{}-{}", branch, commit)
 }
 
+// Splits a line of data-elements.csv or uids.csv in N fields. The last field
+// is free text and may contain commas. It is followed by RET when the entry is
+// retired from the standard.
+fn split_line<const N: usize>(line: &str) -> Option<([&str; N], bool)> {
+  let fields = line.splitn(N, ',').collect::<Vec<&str>>();
+  let mut fields = <[&str; N]>::try_from(fields).ok()?;
+  let retired = match fields[N - 1].strip_suffix(",RET") {
+    Some(last) => {
+      fields[N - 1] = last;
+      true
+    }
+    None => false,
+  };
+  Some((fields, retired))
+}
+
+// An entry of data-elements.csv:
+//   tag,keyword,VR,VM,description[,RET]
+// The tag of the repeating groups contains x (e.g. 60xx0010).
+struct Entry<'a> {
+  tag: &'a str,
+  keyword: &'a str,
+  vr: &'a str,
+  vm: &'a str,
+  description: &'a str,
+  retired: bool,
+}
+
+// The bits of the tag set by the x of a repeating group tag are 0 in the mask
+// and in the value, e.g. 0xFF00FFFF and 0x60000010 for 60xx0010.
+fn mask_and_value(tag: &str) -> (u32, u32) {
+  let value = u32::from_str_radix(&tag.replace('x', "0"), 16);
+  match value {
+    Ok(value) if tag.len() == 8 => {
+      let mask = tag
+        .chars()
+        .fold(0, |mask, c| mask << 4 | if c == 'x' { 0 } else { 0xF });
+      (mask, value)
+    }
+    _ => panic!("data-elements.csv: invalid tag {}", tag),
+  }
+}
+
+// Converts a VM such as 1, 1-3, 1-n or 2-2n to a ValueMultiplicity. The
+// alternatives (e.g. "1-n or 1") are reduced to the first one, as in
+// ValueMultiplicity::try_from.
+fn value_multiplicity(vm: &str) -> String {
+  let first = vm.split(" or ").next().unwrap().trim();
+  let invalid = || -> ! { panic!("data-elements.csv: invalid VM {}", vm) };
+  let (min, max, step) = match first.split_once('-') {
+    None => (first, Some(first), "1"),
+    Some((min, "n")) => (min, None, "1"),
+    Some((min, max)) => match max.strip_suffix('n') {
+      Some(step) => (min, None, step),
+      None => (min, Some(max), "1"),
+    },
+  };
+  let min = min.parse::<u16>().unwrap_or_else(|_| invalid());
+  let max = max.map(|max| max.parse::<u16>().unwrap_or_else(|_| invalid()));
+  let step = step.parse::<u16>().unwrap_or_else(|_| invalid());
+  format!(
+    "ValueMultiplicity {{ min: {}, max: {:?}, step: {} }}",
+    min, max, step
+  )
+}
+
+// Generates the constants of the tags of data-elements.csv, along with perfect
+// hash tables to look them up by tag and keyword. dicom_tags.rs includes them.
+fn generate_dicom_tags(csv: &str, output: &Path) {
+  let entries = csv
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      let Some(([tag, keyword, vr, vm, description], retired)) = split_line(line) else {
+        panic!("data-elements.csv: invalid line {}", line);
+      };
+      Entry {
+        tag,
+        keyword,
+        vr,
+        vm,
+        description,
+        retired,
+      }
+    })
+    .collect::<Vec<Entry>>();
+
+  let mut code = String::new();
+  writeln!(code, "// @generated by build.rs from data-elements.csv").unwrap();
+  writeln!(code).unwrap();
+  for entry in &entries {
+    let (_, value) = mask_and_value(entry.tag);
+    // FIXME: In case of something like "US or SS" we only use the first VR for now
+    let vr = entry.vr.get(..2).unwrap_or("NA");
+    writeln!(code, "pub const {}: Tag = Tag {{", entry.keyword).unwrap();
+    writeln!(code, "  group: 0x{:04X},", value >> 16).unwrap();
+    writeln!(code, "  element: 0x{:04X},", value & 0xFFFF).unwrap();
+    writeln!(code, "  name: {:?},", entry.keyword).unwrap();
+    writeln!(code, "  vr: ValueRepresentation::{},", vr).unwrap();
+    writeln!(code, "  vm: {},", value_multiplicity(entry.vm)).unwrap();
+    writeln!(code, "  retired: {},", entry.retired).unwrap();
+    writeln!(code, "  description: {:?},", entry.description).unwrap();
+    writeln!(code, "}};").unwrap();
+    writeln!(code).unwrap();
+  }
+
+  let mut tags = phf_codegen::Map::new();
+  let mut keywords = phf_codegen::Map::new();
+  let mut repeating_tags = String::new();
+  for entry in &entries {
+    let (mask, value) = mask_and_value(entry.tag);
+    if mask == 0xFFFFFFFF {
+      tags.entry(value, &format!("&{}", entry.keyword));
+    } else {
+      writeln!(
+        repeating_tags,
+        "  (0x{:08X}, 0x{:08X}, &{}),",
+        mask, value, entry.keyword
+      )
+      .unwrap();
+    }
+    keywords.entry(entry.keyword.to_uppercase(), &format!("&{}", entry.keyword));
+  }
+  writeln!(code, "// The tags keyed by (group << 16) | element").unwrap();
+  writeln!(code, "static TAGS: phf::Map<u32, &Tag> = {};", tags.build()).unwrap();
+  writeln!(code).unwrap();
+  writeln!(code, "// The tags keyed by their upper case keyword").unwrap();
+  writeln!(
+    code,
+    "static KEYWORDS: phf::Map<&str, &Tag> = {};",
+    keywords.build()
+  )
+  .unwrap();
+  writeln!(code).unwrap();
+  writeln!(
+    code,
+    "// The mask, value and tag of the repeating group tags (e.g. 60xx0010)"
+  )
+  .unwrap();
+  writeln!(code, "static REPEATING_TAGS: &[(u32, u32, &Tag)] = &[").unwrap();
+  code.push_str(&repeating_tags);
+  writeln!(code, "];").unwrap();
+
+  fs::write(output, code).unwrap();
+}
+
+// Converts the type of a UID, as written in PS3.6, to a UidType
+fn uid_type(uid_type: &str) -> &'static str {
+  match uid_type {
+    "SOP Class" => "SopClass",
+    "Meta SOP Class" => "MetaSopClass",
+    "Transfer Syntax" => "TransferSyntax",
+    "Well-known SOP Instance" => "WellKnownSopInstance",
+    "Well-known Frame of Reference" => "WellKnownFrameOfReference",
+    "Synchronization Frame of Reference" => "SynchronizationFrameOfReference",
+    "Coding Scheme" | "DICOM UIDs as a Coding Scheme" => "CodingScheme",
+    "Application Context Name" => "ApplicationContextName",
+    "Service Class" => "ServiceClass",
+    "Application Hosting Model" => "ApplicationHostingModel",
+    "Mapping Resource" => "MappingResource",
+    "LDAP OID" => "LdapOid",
+    _ => panic!("uids.csv: unknown UID type {}", uid_type),
+  }
+}
+
+// Generates the constants of the UIDs of uids.csv, i.e. lines of
+//   UID,keyword,type,name[,RET]
+// along with perfect hash tables to look them up by UID and keyword. uids.rs
+// includes them.
+fn generate_uids(csv: &str, output: &Path) {
+  let mut code = String::new();
+  writeln!(code, "// @generated by build.rs from uids.csv").unwrap();
+  writeln!(code).unwrap();
+  let mut uids = phf_codegen::Map::new();
+  let mut keywords = phf_codegen::Map::new();
+  for line in csv.lines().filter(|line| !line.trim().is_empty()) {
+    let Some(([uid, keyword, kind, name], retired)) = split_line(line) else {
+      panic!("uids.csv: invalid line {}", line);
+    };
+    writeln!(code, "pub const {}: Uid = Uid {{", keyword).unwrap();
+    writeln!(code, "  uid: {:?},", uid).unwrap();
+    writeln!(code, "  keyword: {:?},", keyword).unwrap();
+    writeln!(code, "  name: {:?},", name).unwrap();
+    writeln!(code, "  uid_type: UidType::{},", uid_type(kind)).unwrap();
+    writeln!(code, "  retired: {},", retired).unwrap();
+    writeln!(code, "}};").unwrap();
+    writeln!(code).unwrap();
+    uids.entry(uid, &format!("&{}", keyword));
+    keywords.entry(keyword.to_uppercase(), &format!("&{}", keyword));
+  }
+  writeln!(code, "// The UIDs keyed by their value").unwrap();
+  writeln!(
+    code,
+    "static UIDS: phf::Map<&str, &Uid> = {};",
+    uids.build()
+  )
+  .unwrap();
+  writeln!(code).unwrap();
+  writeln!(code, "// The UIDs keyed by their upper case keyword").unwrap();
+  writeln!(
+    code,
+    "static KEYWORDS: phf::Map<&str, &Uid> = {};",
+    keywords.build()
+  )
+  .unwrap();
+
+  fs::write(output, code).unwrap();
+}
+
 fn main() {
+  // Only regenerate the dictionaries when their source changes, and the git
+  // hash when a commit is made or the branch changes
+  println!("cargo:rerun-if-changed=build.rs");
+  println!("cargo:rerun-if-changed=data-elements.csv");
+  println!("cargo:rerun-if-changed=uids.csv");
+  for git_path in [".git/HEAD", ".git/refs"] {
+    if Path::new(git_path).exists() {
+      println!("cargo:rerun-if-changed={}", git_path);
+    }
+  }
+
   let git_hash = get_git_hash();
   println!("cargo:rustc-env=GIT_HASH={}", &git_hash);
+
+  let out_dir = env::var("OUT_DIR").unwrap();
+  let csv = fs::read_to_string("data-elements.csv").unwrap();
+  generate_dicom_tags(&csv, &Path::new(&out_dir).join("dicom_tags.rs"));
+  let csv = fs::read_to_string("uids.csv").unwrap();
+  generate_uids(&csv, &Path::new(&out_dir).join("uids.rs"));
 }
@@ -0,0 +1,64 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Coverage guided fuzzing of Instance::from, seeded with the test corpus:
+//
+// cargo +nightly fuzz run instance_from fuzz/corpus/instance_from tests/data
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use rdicom::dicom_tags::{PatientName, PixelData, SOPInstanceUID, SpecificCharacterSet, StudyDate};
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+
+fuzz_target!(|buffer: &[u8]| {
+  let Ok(instance) = Instance::from(buffer) else {
+    return;
+  };
+  let _ = instance.character_set();
+  for tag in [
+    &SpecificCharacterSet,
+    &StudyDate,
+    &PatientName,
+    &SOPInstanceUID,
+    &PixelData,
+  ] {
+    let _ = instance.get_value(tag);
+    let _ = instance.get_values(tag);
+  }
+  for step in instance.walk() {
+    let Ok(step) = step else {
+      break;
+    };
+    if let WalkEvent::Attribute(attribute) = step.event {
+      let _ = instance.get_value_by_path(&step.path);
+      if let Ok(value) = DicomValue::from_dicom_attribute(&attribute, &instance) {
+        let _ = value.to_string();
+        let _ = value.to_dates();
+        let _ = value.to_times();
+        let _ = value.to_date_times();
+        let _ = value.to_ages();
+        let _ = value.to_f64s();
+        let _ = value.to_i64s();
+      }
+    }
+  }
+});
@@ -0,0 +1,331 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/chapter_6.html
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part03/sect_C.12.html#sect_C.12.1.1.2
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::from_utf8;
+
+use encoding_rs::Encoding;
+
+/**
+ * Character repertoires of the text values (SH, LO, ST, LT, PN, UC and UT) of
+ * an instance as declared by SpecificCharacterSet (0008,0005).
+ *
+ * A SpecificCharacterSet in a sequence item applies to the attributes of the
+ * item and of the items nested in it. The default, ISO_IR 6 (ASCII), is used
+ * when the dataset has no SpecificCharacterSet.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CharacterSet {
+  // Repertoire of the values before any escape sequence. None for the default
+  // repertoire (ASCII).
+  initial: Option<&'static Encoding>,
+  // The values may switch repertoire with ISO 2022 escape sequences
+  code_extensions: bool,
+}
+
+// What the bytes lower than 0x80 (G0) represent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum G0 {
+  Ascii,
+  // Japanese Kanji on 2 bytes (ISO 2022 IR 87)
+  JisX0208,
+  // Supplementary Japanese Kanji on 2 bytes (ISO 2022 IR 159)
+  JisX0212,
+}
+
+// Encoding of a defined term used without code extensions
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part03/sect_C.12.html#table_C.12-2
+fn single_byte_encoding(term: &str) -> Option<&'static Encoding> {
+  // ISO 2022 IR xxx and ISO_IR xxx share the same repertoire
+  let term = term.trim().replace("ISO 2022 IR ", "ISO_IR ");
+  match term.as_str() {
+    // windows-1252 is a superset of ISO 8859-1
+    "ISO_IR 100" => Some(encoding_rs::WINDOWS_1252),
+    "ISO_IR 101" => Some(encoding_rs::ISO_8859_2),
+    "ISO_IR 109" => Some(encoding_rs::ISO_8859_3),
+    "ISO_IR 110" => Some(encoding_rs::ISO_8859_4),
+    "ISO_IR 144" => Some(encoding_rs::ISO_8859_5),
+    "ISO_IR 127" => Some(encoding_rs::ISO_8859_6),
+    "ISO_IR 126" => Some(encoding_rs::ISO_8859_7),
+    "ISO_IR 138" => Some(encoding_rs::ISO_8859_8),
+    // windows-1254 is a superset of ISO 8859-9
+    "ISO_IR 148" => Some(encoding_rs::WINDOWS_1254),
+    "ISO_IR 203" => Some(encoding_rs::ISO_8859_15),
+    // windows-874 is a superset of TIS 620-2533
+    "ISO_IR 166" => Some(encoding_rs::WINDOWS_874),
+    // JIS X 0201 Katakana is encoded the same way in Shift_JIS
+    "ISO_IR 13" => Some(encoding_rs::SHIFT_JIS),
+    _ => None,
+  }
+}
+
+// Parse the escape sequence at the beginning of `bytes` (after ESC). Returns its
+// length and the repertoire it designates.
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part03/sect_C.12.html#table_C.12-3
+fn escape_sequence(bytes: &[u8]) -> Option<(usize, Option<G0>, Option<&'static Encoding>)> {
+  match bytes {
+    [b'(', b'B', ..] | [b'(', b'J', ..] => Some((2, Some(G0::Ascii), None)),
+    [b'$', b'B', ..] => Some((2, Some(G0::JisX0208), None)),
+    [b'$', b'(', b'D', ..] => Some((3, Some(G0::JisX0212), None)),
+    [b')', b'I', ..] => Some((2, None, Some(encoding_rs::SHIFT_JIS))),
+    // KS X 1001 and GB 2312 are designated to G1 as in EUC-KR and EUC-CN
+    [b'$', b')', b'C', ..] => Some((3, None, Some(encoding_rs::EUC_KR))),
+    [b'$', b')', b'A', ..] => Some((3, None, Some(encoding_rs::GBK))),
+    [b'-', final_byte, ..] => {
+      let encoding = match final_byte {
+        b'A' => encoding_rs::WINDOWS_1252,
+        b'B' => encoding_rs::ISO_8859_2,
+        b'C' => encoding_rs::ISO_8859_3,
+        b'D' => encoding_rs::ISO_8859_4,
+        b'L' => encoding_rs::ISO_8859_5,
+        b'G' => encoding_rs::ISO_8859_6,
+        b'F' => encoding_rs::ISO_8859_7,
+        b'H' => encoding_rs::ISO_8859_8,
+        b'M' => encoding_rs::WINDOWS_1254,
+        b'b' => encoding_rs::ISO_8859_15,
+        b'T' => encoding_rs::WINDOWS_874,
+        _ => return None,
+      };
+      Some((2, None, Some(encoding)))
+    }
+    _ => None,
+  }
+}
+
+// Decode a run of bytes of the same graphic set
+fn decode_run(
+  result: &mut String,
+  run: &[u8],
+  g0: G0,
+  g1: Option<&'static Encoding>,
+) -> Option<()> {
+  if run.is_empty() {
+    return Some(());
+  }
+  if run[0] >= 0x80 {
+    let encoding = g1?;
+    result.push_str(&encoding.decode_without_bom_handling_and_without_replacement(run)?);
+    return Some(());
+  }
+  match g0 {
+    G0::Ascii => result.push_str(from_utf8(run).ok()?),
+    // The 2 bytes characters of JIS X 0208 and JIS X 0212 are encoded in EUC-JP
+    // with the high bit set (and a 0x8F prefix for JIS X 0212).
+    G0::JisX0208 | G0::JisX0212 => {
+      let mut euc = Vec::with_capacity(run.len() * 3 / 2);
+      for pair in run.chunks(2) {
+        if g0 == G0::JisX0212 {
+          euc.push(0x8F);
+        }
+        euc.extend(pair.iter().map(|b| b | 0x80));
+      }
+      result
+        .push_str(&encoding_rs::EUC_JP.decode_without_bom_handling_and_without_replacement(&euc)?);
+    }
+  }
+  Some(())
+}
+
+impl CharacterSet {
+  /**
+   * Returns the character set described by the values of SpecificCharacterSet.
+   * Unknown terms are ignored.
+   */
+  pub fn from_terms<T: AsRef<str>>(terms: &[T]) -> CharacterSet {
+    let first = terms.first().map(|term| term.as_ref().trim()).unwrap_or("");
+    let code_extensions = terms
+      .iter()
+      .any(|term| term.as_ref().trim().starts_with("ISO 2022"));
+    let initial = match first {
+      "ISO_IR 192" => Some(encoding_rs::UTF_8),
+      "GB18030" => Some(encoding_rs::GB18030),
+      "GBK" => Some(encoding_rs::GBK),
+      _ => single_byte_encoding(first),
+    };
+    CharacterSet {
+      initial,
+      code_extensions,
+    }
+  }
+
+  /**
+   * Whether the text values are in the default repertoire.
+   */
+  pub fn is_default(&self) -> bool {
+    self.initial.is_none() && !self.code_extensions
+  }
+
+  /**
+   * Decode a text value. Person names (PN) go back to the initial repertoire
+   * on their component delimiters ('^' and '=') on top of the delimiters of all
+   * the text values (e.g. '\', CR, LF).
+   * Returns None if the value is not valid in the character set.
+   */
+  pub fn decode<'b>(&self, value: &'b [u8], person_name: bool) -> Option<Cow<'b, str>> {
+    if !self.code_extensions {
+      return match self.initial {
+        Some(encoding) => encoding.decode_without_bom_handling_and_without_replacement(value),
+        None => from_utf8(value).ok().map(Cow::Borrowed),
+      };
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let (mut g0, mut g1) = (G0::Ascii, self.initial);
+    let mut start = 0;
+    let mut i = 0;
+    while i < value.len() {
+      let byte = value[i];
+      if byte == 0x1B {
+        decode_run(&mut result, &value[start..i], g0, g1)?;
+        let (length, new_g0, new_g1) = escape_sequence(&value[i + 1..])?;
+        g0 = new_g0.unwrap_or(g0);
+        g1 = new_g1.or(g1);
+        i += 1 + length;
+        start = i;
+        continue;
+      }
+      // In a 2 bytes G0 set, the delimiters would be part of a character
+      let is_delimiter = g0 == G0::Ascii
+        && (matches!(byte, b'\\' | b'\r' | b'\n' | b'\t' | 0x0C)
+          || (person_name && matches!(byte, b'^' | b'=')));
+      // A G0/G1 switch starts a new run
+      let switches_set = i > start && (byte >= 0x80) != (value[start] >= 0x80);
+      if is_delimiter || switches_set {
+        decode_run(&mut result, &value[start..i], g0, g1)?;
+        start = i;
+      }
+      if is_delimiter {
+        result.push(byte as char);
+        g0 = G0::Ascii;
+        g1 = self.initial;
+        start = i + 1;
+      }
+      i += 1;
+    }
+    decode_run(&mut result, &value[start..], g0, g1)?;
+    Some(Cow::Owned(result))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn decode(terms: &[&str], value: &[u8]) -> Option<String> {
+    CharacterSet::from_terms(terms)
+      .decode(value, true)
+      .map(|value| value.into_owned())
+  }
+
+  #[test]
+  fn without_code_extensions() {
+    assert!(CharacterSet::from_terms(&[""]).is_default());
+    assert!(CharacterSet::from_terms(&["ISO_IR 6"]).is_default());
+    assert!(!CharacterSet::from_terms(&["ISO_IR 100"]).is_default());
+    assert_eq!(decode(&["ISO_IR 100"], b"M\xFCller").unwrap(), "Müller");
+    assert_eq!(
+      decode(&["ISO_IR 144"], b"\xBB\xEE\xDA\xE1\xD5\xDC\xD1\xE3\xE0\xD3").unwrap(),
+      "Люксембург"
+    );
+    assert_eq!(decode(&["ISO_IR 192"], "Θ^Ω".as_bytes()).unwrap(), "Θ^Ω");
+    // Not ASCII
+    assert_eq!(decode(&[""], b"M\xFCller"), None);
+  }
+
+  #[test]
+  fn japanese() {
+    // Yamada^Tarou=山田^太郎=やまだ^たろう
+    // https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_H.3.html
+    let value =
+      b"Yamada^Tarou=\x1B$B;3ED\x1B(B^\x1B$BB@O:\x1B(B=\x1B$B$d$^$@\x1B(B^\x1B$B$?$m$&\x1B(B";
+    assert_eq!(
+      decode(&["", "ISO 2022 IR 87"], value).unwrap(),
+      "Yamada^Tarou=山田^太郎=やまだ^たろう"
+    );
+    // Half width katakana in G1, then Kanji
+    let value = b"\xD4\xCF\xC0\xDE^\xC0\xDB\xB3=\x1B$B;3ED\x1B(B^\x1B$BB@O:\x1B(B";
+    assert_eq!(
+      decode(&["ISO 2022 IR 13", "ISO 2022 IR 87"], value).unwrap(),
+      "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎"
+    );
+    // Supplementary Kanji of JIS X 0212
+    assert_eq!(
+      decode(&["", "ISO 2022 IR 159"], b"\x1B$(D0!\x1B(B").unwrap(),
+      "丂"
+    );
+  }
+
+  #[test]
+  fn delimiters_reset_the_repertoire() {
+    // The component delimiter goes back to the initial G1 set (Latin 1)
+    let character_set = CharacterSet::from_terms(&["ISO 2022 IR 100", "ISO 2022 IR 144"]);
+    assert_eq!(
+      character_set.decode(b"\x1B-L\xBB^\xBB", true).unwrap(),
+      "Л^»"
+    );
+    // Only in person names
+    assert_eq!(
+      character_set.decode(b"\x1B-L\xBB^\xBB", false).unwrap(),
+      "Л^Л"
+    );
+    // In a 2 bytes G0 set, ^ is part of a character
+    assert_eq!(
+      decode(&["", "ISO 2022 IR 87"], b"\x1B$B;3ED$^\x1B(B^T").unwrap(),
+      "山田ま^T"
+    );
+  }
+
+  #[test]
+  fn korean_and_chinese() {
+    // Hong^Gildong=洪^吉洞=홍^길동
+    // https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_I.2.html
+    let value = b"Hong^Gildong=\x1B$)C\xFB\xF3^\x1B$)C\xD1\xCE\xD4\xD7=\x1B$)C\xC8\xAB^\x1B$)C\xB1\xE6\xB5\xBF";
+    assert_eq!(
+      decode(&["", "ISO 2022 IR 149"], value).unwrap(),
+      "Hong^Gildong=洪^吉洞=홍^길동"
+    );
+    // Wang^XiaoDong=王^小东=
+    // https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_K.2.html
+    let value = b"Wang^XiaoDong=\x1B$)A\xCD\xF5^\x1B$)A\xD0\xA1\xB6\xAB=";
+    assert_eq!(
+      decode(&["", "ISO 2022 IR 58"], value).unwrap(),
+      "Wang^XiaoDong=王^小东="
+    );
+  }
+
+  #[test]
+  fn single_byte_code_extensions() {
+    // Latin 1 initially, then Cyrillic
+    let value = b"M\xFCller\\\x1B-L\xBB\xEE\xDA\xE1";
+    assert_eq!(
+      decode(&["ISO 2022 IR 100", "ISO 2022 IR 144"], value).unwrap(),
+      "Müller\\Люкс"
+    );
+    // Unknown escape sequence
+    assert_eq!(decode(&["", "ISO 2022 IR 144"], b"\x1B-Zabc"), None);
+    // No G1 repertoire designated
+    assert_eq!(decode(&["", "ISO 2022 IR 144"], b"\xBB"), None);
+  }
+}
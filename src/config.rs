@@ -18,6 +18,10 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use std::path::PathBuf;
+
+use rdicom::dictionary;
+use rdicom::error::DicomError;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -38,6 +42,9 @@ pub struct Config {
   pub table_name: String,
   // Do we overwrite DICOM file on STORE
   pub store_overwrite: Option<bool>,
+  // Additional dictionaries (CSV or YAML) naming the tags absent from the
+  // built-in one, e.g. in-house private tags
+  pub dictionaries: Option<Vec<PathBuf>>,
 }
 
 impl Config {
@@ -58,4 +65,12 @@ impl Config {
       .map(|s| s.clone())
       .collect::<Vec<String>>()
   }
+
+  // Loads the additional dictionaries, so that the fields can refer to their tags
+  pub fn load_dictionaries(&self) -> Result<(), DicomError> {
+    for path in self.dictionaries.iter().flatten() {
+      dictionary::load_file(path)?;
+    }
+    Ok(())
+  }
 }
@@ -0,0 +1,664 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_6.2.html
+
+use alloc::string::ToString;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::error::{DicomError, DicomErrorKind};
+
+fn invalid_value(vr: &str, value: &str) -> DicomError {
+  DicomError::with_kind(
+    DicomErrorKind::InvalidValue,
+    &format!("Invalid {} value: {:?}", vr, value),
+  )
+}
+
+// Parse a string only made of digits
+fn parse_digits(digits: &str, vr: &str, value: &str) -> Result<u32, DicomError> {
+  if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+    return Err(invalid_value(vr, value));
+  }
+  digits.parse::<u32>().map_err(|_| invalid_value(vr, value))
+}
+
+// Parse the fraction of a second (1 to 6 digits) to microseconds
+fn parse_fraction(fraction: &str, vr: &str, value: &str) -> Result<u32, DicomError> {
+  if fraction.len() > 6 {
+    return Err(invalid_value(vr, value));
+  }
+  let microsecond = parse_digits(fraction, vr, value)?;
+  Ok(microsecond * 10_u32.pow(6 - fraction.len() as u32))
+}
+
+pub fn is_leap_year(year: u16) -> bool {
+  (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+  match month {
+    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+    4 | 6 | 9 | 11 => 30,
+    2 if is_leap_year(year) => 29,
+    2 => 28,
+    _ => 0,
+  }
+}
+
+/**
+ * A date (DA) value, e.g. "20230415".
+ * The ACR-NEMA Standard 300 format ("2023.04.15") is also accepted.
+ * Dates are ordered chronologically.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+  pub year: u16,
+  pub month: u8,
+  pub day: u8,
+}
+
+impl Date {
+  /**
+   * Returns None if the date does not exist.
+   */
+  pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+    if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+      Some(Date { year, month, day })
+    } else {
+      None
+    }
+  }
+
+  /**
+   * Number of days since 1970-01-01.
+   * http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+   */
+  pub fn days_since_epoch(&self) -> i64 {
+    let month = i64::from(self.month);
+    let year = i64::from(self.year) - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+  }
+
+  /**
+   * Inverse of days_since_epoch. Returns None if the year does not fit in a DA.
+   * http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+   */
+  pub fn from_days_since_epoch(days: i64) -> Option<Date> {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+      (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+      month_index + 3
+    } else {
+      month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    if !(0..=9999).contains(&year) {
+      return None;
+    }
+    Date::new(year as u16, month as u8, day as u8)
+  }
+}
+
+impl TryFrom<&str> for Date {
+  type Error = DicomError;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    let trimmed = value.trim();
+    // ACR-NEMA Standard 300 format
+    let digits =
+      if trimmed.len() == 10 && trimmed.as_bytes()[4] == b'.' && trimmed.as_bytes()[7] == b'.' {
+        trimmed.replace('.', "")
+      } else {
+        trimmed.to_string()
+      };
+    if digits.len() != 8 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+      return Err(invalid_value("DA", value));
+    }
+    Date::new(
+      parse_digits(&digits[0..4], "DA", value)? as u16,
+      parse_digits(&digits[4..6], "DA", value)? as u8,
+      parse_digits(&digits[6..8], "DA", value)? as u8,
+    )
+    .ok_or_else(|| invalid_value("DA", value))
+  }
+}
+
+impl fmt::Display for Date {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+  }
+}
+
+/**
+ * A time (TM) value, e.g. "142530.25". The trailing components can be
+ * omitted ("14", "1425"). The colon separated format of ACR-NEMA Standard 300
+ * ("14:25:30.25") is also accepted.
+ * Times are ordered chronologically, a partial time comes before the complete
+ * times it contains (e.g. "14" < "1400").
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+  pub hour: u8,
+  pub minute: Option<u8>,
+  pub second: Option<u8>,
+  pub microsecond: Option<u32>,
+}
+
+impl Time {
+  /**
+   * Number of microseconds since midnight, the missing components counting
+   * as 0.
+   */
+  pub fn microseconds_since_midnight(&self) -> u64 {
+    let seconds = u64::from(self.hour) * 3600
+      + u64::from(self.minute.unwrap_or(0)) * 60
+      + u64::from(self.second.unwrap_or(0));
+    seconds * 1_000_000 + u64::from(self.microsecond.unwrap_or(0))
+  }
+
+  // Parse HH[MM[SS]] and the fraction of a second of a TM or a DT
+  fn from_parts(
+    digits: &str,
+    fraction: Option<&str>,
+    vr: &str,
+    value: &str,
+  ) -> Result<Time, DicomError> {
+    if !matches!(digits.len(), 2 | 4 | 6)
+      || !digits.bytes().all(|b| b.is_ascii_digit())
+      || (fraction.is_some() && digits.len() != 6)
+    {
+      return Err(invalid_value(vr, value));
+    }
+    let component = |start: usize| -> Result<Option<u8>, DicomError> {
+      digits
+        .get(start..start + 2)
+        .map(|component| parse_digits(component, vr, value).map(|n| n as u8))
+        .transpose()
+    };
+    let time = Time {
+      hour: component(0)?.unwrap_or(0),
+      minute: component(2)?,
+      second: component(4)?,
+      microsecond: fraction
+        .map(|fraction| parse_fraction(fraction, vr, value))
+        .transpose()?,
+    };
+    // 60 is allowed for leap seconds
+    if time.hour > 23 || time.minute.unwrap_or(0) > 59 || time.second.unwrap_or(0) > 60 {
+      return Err(invalid_value(vr, value));
+    }
+    Ok(time)
+  }
+}
+
+impl TryFrom<&str> for Time {
+  type Error = DicomError;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    // ACR-NEMA Standard 300 format
+    let trimmed = value.trim().replace(':', "");
+    let mut parts = trimmed.splitn(2, '.');
+    let digits = parts.next().unwrap_or("");
+    Time::from_parts(digits, parts.next(), "TM", value)
+  }
+}
+
+impl fmt::Display for Time {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:02}", self.hour)?;
+    if let Some(minute) = self.minute {
+      write!(f, "{:02}", minute)?;
+    }
+    if let Some(second) = self.second {
+      write!(f, "{:02}", second)?;
+    }
+    if let Some(microsecond) = self.microsecond {
+      let fraction = format!("{:06}", microsecond);
+      let fraction = fraction.trim_end_matches('0');
+      write!(f, ".{}", if fraction.is_empty() { "0" } else { fraction })?;
+    }
+    Ok(())
+  }
+}
+
+/**
+ * A date time (DT) value, e.g. "20230415142530.25+0200". The trailing
+ * components can be omitted down to the year ("2023", "202304").
+ * Date times with the same UTC offset are ordered chronologically. Use to_utc
+ * to compare date times with different offsets.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+  pub year: u16,
+  pub month: Option<u8>,
+  pub day: Option<u8>,
+  pub time: Option<Time>,
+  // Offset from UTC in minutes
+  pub offset: Option<i16>,
+}
+
+impl DateTime {
+  /**
+   * The date if the date time has a month and a day.
+   */
+  pub fn date(&self) -> Option<Date> {
+    Date::new(self.year, self.month?, self.day?)
+  }
+
+  /**
+   * The same date time in UTC. Date times without offset, day or time are
+   * returned as is as they cannot be converted.
+   */
+  pub fn to_utc(&self) -> DateTime {
+    let (Some(offset), Some(date), Some(time)) = (self.offset, self.date(), self.time) else {
+      return *self;
+    };
+    let minutes = date.days_since_epoch() * 1440
+      + i64::from(time.hour) * 60
+      + i64::from(time.minute.unwrap_or(0))
+      - i64::from(offset);
+    let Some(date) = Date::from_days_since_epoch(minutes.div_euclid(1440)) else {
+      return *self;
+    };
+    let minute = minutes.rem_euclid(1440);
+    DateTime {
+      year: date.year,
+      month: Some(date.month),
+      day: Some(date.day),
+      time: Some(Time {
+        hour: (minute / 60) as u8,
+        minute: if time.minute.is_some() || offset % 60 != 0 {
+          Some((minute % 60) as u8)
+        } else {
+          None
+        },
+        ..time
+      }),
+      offset: Some(0),
+    }
+  }
+}
+
+impl TryFrom<&str> for DateTime {
+  type Error = DicomError;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    let trimmed = value.trim();
+    let (trimmed, offset) = match trimmed.find(['+', '-']) {
+      Some(position) => {
+        let (trimmed, offset) = trimmed.split_at(position);
+        let digits = &offset[1..];
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+          return Err(invalid_value("DT", value));
+        }
+        let (hours, minutes) = (
+          parse_digits(&digits[0..2], "DT", value)? as i16,
+          parse_digits(&digits[2..4], "DT", value)? as i16,
+        );
+        let offset = if offset.starts_with('-') { -1 } else { 1 } * (hours * 60 + minutes);
+        if minutes > 59 || !(-720..=840).contains(&offset) {
+          return Err(invalid_value("DT", value));
+        }
+        (trimmed, Some(offset))
+      }
+      None => (trimmed, None),
+    };
+    let mut parts = trimmed.splitn(2, '.');
+    let digits = parts.next().unwrap_or("");
+    let fraction = parts.next();
+    if !matches!(digits.len(), 4 | 6 | 8 | 10 | 12 | 14)
+      || !digits.bytes().all(|b| b.is_ascii_digit())
+    {
+      return Err(invalid_value("DT", value));
+    }
+    let component = |start: usize| -> Result<Option<u8>, DicomError> {
+      digits
+        .get(start..start + 2)
+        .map(|component| parse_digits(component, "DT", value).map(|n| n as u8))
+        .transpose()
+    };
+    let date_time = DateTime {
+      year: parse_digits(&digits[0..4], "DT", value)? as u16,
+      month: component(4)?,
+      day: component(6)?,
+      time: if digits.len() > 8 {
+        Some(Time::from_parts(&digits[8..], fraction, "DT", value)?)
+      } else if fraction.is_some() {
+        return Err(invalid_value("DT", value));
+      } else {
+        None
+      },
+      offset,
+    };
+    let valid_month = date_time
+      .month
+      .is_none_or(|month| (1..=12).contains(&month));
+    let valid_day = match (date_time.month, date_time.day) {
+      (Some(month), Some(day)) => day >= 1 && day <= days_in_month(date_time.year, month),
+      _ => true,
+    };
+    if !valid_month || !valid_day {
+      return Err(invalid_value("DT", value));
+    }
+    Ok(date_time)
+  }
+}
+
+impl fmt::Display for DateTime {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:04}", self.year)?;
+    if let Some(month) = self.month {
+      write!(f, "{:02}", month)?;
+    }
+    if let Some(day) = self.day {
+      write!(f, "{:02}", day)?;
+    }
+    if let Some(time) = self.time {
+      write!(f, "{}", time)?;
+    }
+    if let Some(offset) = self.offset {
+      let sign = if offset < 0 { '-' } else { '+' };
+      let offset = offset.unsigned_abs();
+      write!(f, "{}{:02}{:02}", sign, offset / 60, offset % 60)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AgeUnit {
+  Days,
+  Weeks,
+  Months,
+  Years,
+}
+
+/**
+ * An age string (AS) value, e.g. "042Y".
+ * Ages are ordered by their duration, e.g. "010D" < "002W" < "001M".
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Age {
+  pub value: u16,
+  pub unit: AgeUnit,
+}
+
+impl Age {
+  /**
+   * Duration of the age in days, with months and years of average length.
+   */
+  pub fn to_days(&self) -> f64 {
+    let days = match self.unit {
+      AgeUnit::Days => 1.0,
+      AgeUnit::Weeks => 7.0,
+      AgeUnit::Months => 365.25 / 12.0,
+      AgeUnit::Years => 365.25,
+    };
+    f64::from(self.value) * days
+  }
+}
+
+impl Ord for Age {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self
+      .to_days()
+      .total_cmp(&other.to_days())
+      .then(self.unit.cmp(&other.unit))
+  }
+}
+
+impl PartialOrd for Age {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl TryFrom<&str> for Age {
+  type Error = DicomError;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    let trimmed = value.trim();
+    if trimmed.len() != 4 || !trimmed.is_char_boundary(3) {
+      return Err(invalid_value("AS", value));
+    }
+    let unit = match &trimmed[3..] {
+      "D" => AgeUnit::Days,
+      "W" => AgeUnit::Weeks,
+      "M" => AgeUnit::Months,
+      "Y" => AgeUnit::Years,
+      _ => return Err(invalid_value("AS", value)),
+    };
+    Ok(Age {
+      value: parse_digits(&trimmed[0..3], "AS", value)? as u16,
+      unit,
+    })
+  }
+}
+
+impl fmt::Display for Age {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let unit = match self.unit {
+      AgeUnit::Days => 'D',
+      AgeUnit::Weeks => 'W',
+      AgeUnit::Months => 'M',
+      AgeUnit::Years => 'Y',
+    };
+    write!(f, "{:03}{}", self.value, unit)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date_time(value: &str) -> DateTime {
+    DateTime::try_from(value).unwrap()
+  }
+
+  #[test]
+  fn dates() {
+    assert_eq!(
+      Date::try_from("20230415").unwrap(),
+      Date::new(2023, 4, 15).unwrap()
+    );
+    assert_eq!(
+      Date::try_from("2023.04.15 ").unwrap(),
+      Date::new(2023, 4, 15).unwrap()
+    );
+    for invalid in [
+      "2023041",
+      "2023-04-15",
+      "20231301",
+      "20230431",
+      "2023.0415",
+      "+2023041",
+    ] {
+      assert!(Date::try_from(invalid).is_err(), "{}", invalid);
+    }
+    assert!(Date::try_from("20230415").unwrap() < Date::try_from("20231101").unwrap());
+    assert_eq!(Date::new(2023, 1, 2).unwrap().to_string(), "20230102");
+  }
+
+  #[test]
+  fn leap_years() {
+    assert!(Date::try_from("20240229").is_ok());
+    assert!(Date::try_from("20000229").is_ok());
+    assert!(Date::try_from("20230229").is_err());
+    assert!(Date::try_from("19000229").is_err());
+    assert!(DateTime::try_from("19000229").is_err());
+    assert_eq!(days_in_month(2100, 2), 28);
+  }
+
+  #[test]
+  fn days_since_epoch() {
+    for (date, days) in [
+      (Date::new(1970, 1, 1), 0),
+      (Date::new(1969, 12, 31), -1),
+      (Date::new(2000, 3, 1), 11017),
+      (Date::new(0, 1, 1), -719528),
+    ] {
+      let date = date.unwrap();
+      assert_eq!(date.days_since_epoch(), days);
+      assert_eq!(Date::from_days_since_epoch(days), Some(date));
+    }
+    assert_eq!(Date::from_days_since_epoch(-719529), None);
+    assert_eq!(Date::from_days_since_epoch(2932897), None);
+  }
+
+  #[test]
+  fn times() {
+    let time = Time::try_from("142530.25").unwrap();
+    assert_eq!(
+      time,
+      Time {
+        hour: 14,
+        minute: Some(25),
+        second: Some(30),
+        microsecond: Some(250000)
+      }
+    );
+    assert_eq!(time.to_string(), "142530.25");
+    assert_eq!(Time::try_from("14:25:30.25").unwrap(), time);
+    assert_eq!(Time::try_from("1425").unwrap().to_string(), "1425");
+    assert_eq!(
+      Time::try_from("235960")
+        .unwrap()
+        .microseconds_since_midnight(),
+      86_400_000_000
+    );
+    assert!(Time::try_from("14").unwrap() < Time::try_from("1400").unwrap());
+    for invalid in [
+      "1",
+      "240000",
+      "126000",
+      "235961",
+      "1425.5",
+      "142530.1234567",
+      "14h",
+    ] {
+      assert!(Time::try_from(invalid).is_err(), "{}", invalid);
+    }
+  }
+
+  #[test]
+  fn date_times() {
+    assert_eq!(
+      date_time("20230415142530.25+0200"),
+      DateTime {
+        year: 2023,
+        month: Some(4),
+        day: Some(15),
+        time: Some(Time::try_from("142530.25").unwrap()),
+        offset: Some(120),
+      }
+    );
+    for value in [
+      "2023",
+      "202304",
+      "20230415",
+      "2023041514-0530",
+      "20230415142530.000001",
+    ] {
+      assert_eq!(date_time(value).to_string(), value);
+    }
+    for invalid in [
+      "202",
+      "20231",
+      "2023.5",
+      "20230431",
+      "2023041514+02",
+      "2023041514+0260",
+    ] {
+      assert!(DateTime::try_from(invalid).is_err(), "{}", invalid);
+    }
+  }
+
+  #[test]
+  fn offset_range() {
+    // From -12:00 to +14:00
+    assert_eq!(date_time("2023-1200").offset, Some(-720));
+    assert_eq!(date_time("2023+1400").offset, Some(840));
+    assert!(DateTime::try_from("2023-1201").is_err());
+    assert!(DateTime::try_from("2023+1401").is_err());
+  }
+
+  #[test]
+  fn to_utc() {
+    // Back to the previous day, month and year
+    assert_eq!(
+      date_time("20230101003000+0100").to_utc(),
+      date_time("20221231233000+0000")
+    );
+    // To the next day of a leap year
+    assert_eq!(
+      date_time("20240228223015.5-0230").to_utc(),
+      date_time("20240229010015.5+0000")
+    );
+    // The minutes are added when the offset has some
+    assert_eq!(
+      date_time("2023041514+0530").to_utc(),
+      date_time("202304150830+0000")
+    );
+    assert_eq!(
+      date_time("2023041514+0200").to_utc(),
+      date_time("2023041512+0000")
+    );
+    // Cannot be converted
+    for value in ["20230415", "20230415142530", "202304+0200"] {
+      assert_eq!(date_time(value).to_utc(), date_time(value));
+    }
+    // Past the year 9999
+    assert_eq!(
+      date_time("99991231230000-0100").to_utc(),
+      date_time("99991231230000-0100")
+    );
+  }
+
+  #[test]
+  fn ages() {
+    assert_eq!(
+      Age::try_from("042Y").unwrap(),
+      Age {
+        value: 42,
+        unit: AgeUnit::Years
+      }
+    );
+    assert_eq!(Age::try_from("003W").unwrap().to_string(), "003W");
+    assert!(Age::try_from("010D").unwrap() < Age::try_from("002W").unwrap());
+    assert!(Age::try_from("002W").unwrap() < Age::try_from("001M").unwrap());
+    assert!(Age::try_from("012M").unwrap() < Age::try_from("001Y").unwrap());
+    for invalid in ["42Y", "042y", "042", "0042Y", "+42Y", "04éY"] {
+      assert!(Age::try_from(invalid).is_err(), "{}", invalid);
+    }
+  }
+}
@@ -29,7 +29,7 @@ use structopt::StructOpt;
 mod dicom_representation;
 
 use crate::dicom_representation::dcm2json;
-use rdicom::error::DicomError;
+use rdicom::error::{DicomError, DicomErrorKind};
 use rdicom::misc::is_dicom_file;
 
 // A simplified dcm2json clone
@@ -50,10 +50,10 @@ fn main() -> Result<(), Box<dyn Error>> {
   let result: Result<_, Box<dyn Error>> = if is_dicom_file(&opt.filepath) {
     dcm2json(f)
   } else {
-    Err(Box::new(DicomError::new(&format!(
-      "{} is not a dicom file",
-      opt.filepath
-    ))))
+    Err(Box::new(DicomError::with_kind(
+      DicomErrorKind::NotDicom,
+      &format!("{} is not a dicom file", opt.filepath),
+    )))
   };
 
   match result {
@@ -29,46 +29,10 @@ use std::error::Error;
 use std::fs::File;
 use std::io::BufReader;
 
-use rdicom::error::DicomError;
-use rdicom::instance::{self, DicomValue, Instance};
-
-#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
-pub enum ValueRepresentation {
-  AE,
-  AS,
-  AT,
-  CS,
-  DA,
-  DS,
-  DT,
-  FL,
-  FD,
-  IS,
-  LO,
-  LT,
-  OB,
-  OD,
-  OF,
-  OL,
-  OV,
-  OW,
-  PN,
-  SH,
-  SL,
-  SQ,
-  SS,
-  ST,
-  SV,
-  TM,
-  UC,
-  UI,
-  UL,
-  UN,
-  UR,
-  US,
-  UT,
-  UV,
-}
+use rdicom::error::{DicomError, DicomErrorKind};
+use rdicom::instance::{self, DicomValue, Instance, WalkEvent};
+use rdicom::person_name as instance_person_name;
+use rdicom::value_representation::ValueRepresentation;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Link {
@@ -109,11 +73,91 @@ pub enum NameVariant {
   NameComponents(NameComponents),
 }
 
+// A person name with its three component groups. In XML the values are
+// numbered (`number` attribute), in JSON they are not.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum PersonName {
-  Alphabetic(NameVariant),
-  Phonetic(NameVariant),
-  Ideographic(NameVariant),
+#[serde(deny_unknown_fields)]
+pub struct PersonName {
+  #[serde(rename = "@number", skip_serializing_if = "Option::is_none")]
+  number: Option<usize>,
+  #[serde(rename = "Alphabetic", skip_serializing_if = "Option::is_none")]
+  alphabetic: Option<NameVariant>,
+  #[serde(rename = "Ideographic", skip_serializing_if = "Option::is_none")]
+  ideographic: Option<NameVariant>,
+  #[serde(rename = "Phonetic", skip_serializing_if = "Option::is_none")]
+  phonetic: Option<NameVariant>,
+}
+
+impl From<&NameVariant> for instance_person_name::PersonNameComponents {
+  fn from(variant: &NameVariant) -> Self {
+    match variant {
+      NameVariant::Name(name) => instance_person_name::PersonNameComponents::parse(name),
+      NameVariant::NameComponents(components) => instance_person_name::PersonNameComponents {
+        family_name: components.family_name.clone().unwrap_or_default(),
+        given_name: components.given_name.clone().unwrap_or_default(),
+        middle_name: components.middle_name.clone().unwrap_or_default(),
+        name_prefix: components.name_prefix.clone().unwrap_or_default(),
+        name_suffix: components.name_suffix.clone().unwrap_or_default(),
+      },
+    }
+  }
+}
+
+impl From<&instance_person_name::PersonNameComponents> for NameComponents {
+  fn from(components: &instance_person_name::PersonNameComponents) -> Self {
+    let component = |value: &String| Some(value.clone()).filter(|value| !value.is_empty());
+    NameComponents {
+      family_name: component(&components.family_name),
+      given_name: component(&components.given_name),
+      middle_name: component(&components.middle_name),
+      name_prefix: component(&components.name_prefix),
+      name_suffix: component(&components.name_suffix),
+    }
+  }
+}
+
+impl PersonName {
+  // JSON representation, each component group is a string
+  fn to_json(name: &instance_person_name::PersonName) -> PersonName {
+    let group = |group: &Option<instance_person_name::PersonNameComponents>| {
+      group
+        .as_ref()
+        .map(|components| NameVariant::Name(components.to_string()))
+    };
+    PersonName {
+      number: None,
+      alphabetic: group(&name.alphabetic),
+      ideographic: group(&name.ideographic),
+      phonetic: group(&name.phonetic),
+    }
+  }
+
+  // XML representation, each component group is split in its components
+  fn to_xml(number: usize, name: &instance_person_name::PersonName) -> PersonName {
+    let group = |group: &Option<instance_person_name::PersonNameComponents>| {
+      group
+        .as_ref()
+        .map(|components| NameVariant::NameComponents(components.into()))
+    };
+    PersonName {
+      number: Some(number),
+      alphabetic: group(&name.alphabetic),
+      ideographic: group(&name.ideographic),
+      phonetic: group(&name.phonetic),
+    }
+  }
+}
+
+// Rebuild the DICOM string of the person name (e.g. "Yamada^Tarou=山田^太郎")
+impl std::fmt::Display for PersonName {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let person_name = instance_person_name::PersonName {
+      alphabetic: self.alphabetic.as_ref().map(|variant| variant.into()),
+      ideographic: self.ideographic.as_ref().map(|variant| variant.into()),
+      phonetic: self.phonetic.as_ref().map(|variant| variant.into()),
+    };
+    write!(f, "{}", person_name)
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -124,11 +168,13 @@ pub enum ValuePayload {
   // DICOM is a mess. In XML, PatientName is an attribute with a field `PersonName`
   // and as such is part of the Payload of the attribute (see Payload) but in JSON
   // it is encoded as a Value. So we need to manage that case here.
-  PersonName(PersonName),
   // Here again, sequence tag in JSON will be present in a Value object. But in
   // XML the format is different, the sequence will be below the DicomAttribute
   // tag directly enclosed in "Item" tags.
+  // Sequence comes first so that items (including empty ones) are not mistaken
+  // for person names.
   Sequence(BTreeMap<String, DicomAttributeJson>),
+  PersonName(Box<PersonName>),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -136,9 +182,9 @@ pub enum Payload {
   Value(Vec<ValuePayload>),
   BulkData(Bulkdata),
   // #[serde(deserialize_with = "items_from_xml")]
-  Item(Vec<DicomAttribute>), // Sequences will be here in XML
-  PersonName(PersonName),
-  InlineBinary(String), // base64
+  Item(Vec<DicomAttribute>),   // Sequences will be here in XML
+  PersonName(Vec<PersonName>), // Person names will be here in XML
+  InlineBinary(String),        // base64
 }
 
 // How to deal with mutually exclusive fields in serde https://stackoverflow.com/a/73604693/2603925
@@ -150,7 +196,7 @@ pub struct DicomAttribute {
   pub vr: ValueRepresentation,
   #[serde(rename = "@keyword", skip_serializing_if = "Option::is_none")]
   pub keyword: Option<String>,
-  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "@privateCreator", skip_serializing_if = "Option::is_none")]
   pub private_creator: Option<String>,
   // payload is an option because, for example, IS (Integer String) attribute
   // can be empty string which must not translate to 0 but to an empty payload
@@ -170,7 +216,7 @@ pub struct DicomAttributeJson {
   pub vr: ValueRepresentation,
   #[serde(rename = "keyword", skip_serializing_if = "Option::is_none")]
   pub keyword: Option<String>,
-  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(rename = "privateCreator", skip_serializing_if = "Option::is_none")]
   pub private_creator: Option<String>,
   // payload is an option because, for example, IS (Integer String) attribute
   // can be empty string which must not translate to 0 but to an empty payload
@@ -193,29 +239,38 @@ impl TryFrom<&Payload> for String {
       Payload::Value(value) if value.len() == 1 => match &value[0] {
         ValuePayload::String(string_value) => Ok(string_value.clone()),
         ValuePayload::Numeral(numeral_value) => Ok(numeral_value.to_string()),
-        ValuePayload::PersonName(PersonName::Alphabetic(NameVariant::Name(string_value))) => {
-          Ok(string_value.clone())
-        }
-        _ => Err(DicomError::new(&format!(
-          "Payload {:?} cannot be converted to a String",
-          payload
-        ))),
+        ValuePayload::PersonName(person_name) => Ok(person_name.to_string()),
+        _ => Err(DicomError::with_kind(
+          DicomErrorKind::InvalidValue,
+          &format!("Payload {:?} cannot be converted to a String", payload),
+        )),
       },
       Payload::Value(vec) => Ok(
         vec
           .iter()
           .map(|entry| match entry {
-            ValuePayload::String(s) => s.clone(),
-            ValuePayload::Numeral(n) => n.to_string(),
-            _ => todo!(),
+            ValuePayload::String(s) => Ok(s.clone()),
+            ValuePayload::Numeral(n) => Ok(n.to_string()),
+            ValuePayload::PersonName(person_name) => Ok(person_name.to_string()),
+            ValuePayload::Sequence(_) => Err(DicomError::with_kind(
+              DicomErrorKind::InvalidValue,
+              &format!("Payload {:?} cannot be converted to a String", payload),
+            )),
           })
+          .collect::<Result<Vec<String>, DicomError>>()?
+          .join("\\"),
+      ),
+      Payload::PersonName(person_names) => Ok(
+        person_names
+          .iter()
+          .map(|person_name| person_name.to_string())
           .collect::<Vec<String>>()
           .join("\\"),
       ),
-      _ => Err(DicomError::new(&format!(
-        "Payload {:?} cannot be converted to a String",
-        payload
-      ))),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        &format!("Payload {:?} cannot be converted to a String", payload),
+      )),
     }
   }
 }
@@ -228,58 +283,94 @@ impl TryFrom<Payload> for String {
   }
 }
 
-impl TryFrom<Payload> for u16 {
+impl TryFrom<Payload> for Vec<u16> {
   type Error = DicomError;
 
   fn try_from(payload: Payload) -> Result<Self, Self::Error> {
     match payload {
-      Payload::Value(value) => match &value[0] {
-        ValuePayload::Numeral(u16_value) => Ok(*u16_value as u16),
-        _ => Err(DicomError::new("Payload is not a u16")),
-      },
-      _ => Err(DicomError::new("Payload is not a u16")),
+      Payload::Value(value) => value
+        .iter()
+        .map(|v| match v {
+          ValuePayload::Numeral(u16_value) => Ok(*u16_value as u16),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a u16",
+          )),
+        })
+        .collect(),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a u16",
+      )),
     }
   }
 }
 
-impl TryFrom<Payload> for i16 {
+impl TryFrom<Payload> for Vec<i16> {
   type Error = DicomError;
 
   fn try_from(payload: Payload) -> Result<Self, Self::Error> {
     match payload {
-      Payload::Value(value) => match &value[0] {
-        ValuePayload::Numeral(i16_value) => Ok(*i16_value as i16),
-        _ => Err(DicomError::new("Payload is not a i16")),
-      },
-      _ => Err(DicomError::new("Payload is not a i16")),
+      Payload::Value(value) => value
+        .iter()
+        .map(|v| match v {
+          ValuePayload::Numeral(i16_value) => Ok(*i16_value as i16),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a i16",
+          )),
+        })
+        .collect(),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a i16",
+      )),
     }
   }
 }
 
-impl TryFrom<Payload> for u32 {
+impl TryFrom<Payload> for Vec<u32> {
   type Error = DicomError;
 
   fn try_from(payload: Payload) -> Result<Self, Self::Error> {
     match payload {
-      Payload::Value(value) => match &value[0] {
-        ValuePayload::Numeral(u32_value) => Ok(*u32_value as u32),
-        _ => Err(DicomError::new("Payload is not a u32")),
-      },
-      _ => Err(DicomError::new("Payload is not a u32")),
+      Payload::Value(value) => value
+        .iter()
+        .map(|v| match v {
+          ValuePayload::Numeral(u32_value) => Ok(*u32_value as u32),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a u32",
+          )),
+        })
+        .collect(),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a u32",
+      )),
     }
   }
 }
 
-impl TryFrom<Payload> for i32 {
+impl TryFrom<Payload> for Vec<i32> {
   type Error = DicomError;
 
   fn try_from(payload: Payload) -> Result<Self, Self::Error> {
     match payload {
-      Payload::Value(value) => match &value[0] {
-        ValuePayload::Numeral(i32_value) => Ok(*i32_value as i32),
-        _ => Err(DicomError::new("Payload is not a i32")),
-      },
-      _ => Err(DicomError::new("Payload is not a i32")),
+      Payload::Value(value) => value
+        .iter()
+        .map(|v| match v {
+          ValuePayload::Numeral(i32_value) => Ok(*i32_value as i32),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a i32",
+          )),
+        })
+        .collect(),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a i32",
+      )),
     }
   }
 }
@@ -293,10 +384,16 @@ impl TryFrom<Payload> for Vec<f32> {
         .iter()
         .map(|v| match v {
           ValuePayload::Numeral(f32_value) => Ok(*f32_value as f32),
-          _ => Err(DicomError::new("Payload is not a f32")),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a f32",
+          )),
         })
         .collect(),
-      _ => Err(DicomError::new("Payload is not a f32")),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a f32",
+      )),
     }
   }
 }
@@ -310,10 +407,16 @@ impl TryFrom<Payload> for Vec<f64> {
         .iter()
         .map(|v| match v {
           ValuePayload::Numeral(f64_value) => Ok(*f64_value),
-          _ => Err(DicomError::new("Payload is not a f64")),
+          _ => Err(DicomError::with_kind(
+            DicomErrorKind::InvalidValue,
+            "Payload is not a f64",
+          )),
         })
         .collect(),
-      _ => Err(DicomError::new("Payload is not a f64")),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload is not a f64",
+      )),
     }
   }
 }
@@ -324,102 +427,46 @@ impl TryFrom<Payload> for Vec<u8> {
   fn try_from(payload: Payload) -> Result<Self, Self::Error> {
     match payload {
       Payload::InlineBinary(content) => {
-        let result = general_purpose::STANDARD
-          .decode(content)
-          .map_err(|_e| DicomError::new("error while decoding base64"))?;
+        let result = general_purpose::STANDARD.decode(content).map_err(|_e| {
+          DicomError::with_kind(DicomErrorKind::InvalidValue, "error while decoding base64")
+        })?;
         Ok(result)
       }
-      _ => Err(DicomError::new("Payload cannot be converted to &[u8]")),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "Payload cannot be converted to &[u8]",
+      )),
     }
   }
 }
 
-// https://stackoverflow.com/a/75303146/2603925
-// TODO: Ugly; replace vr: &str in DicomAttribute with ValueRepresentation
-impl From<ValueRepresentation> for &'static str {
-  fn from(value_representation: ValueRepresentation) -> Self {
-    match value_representation {
-      ValueRepresentation::AE => "AE",
-      ValueRepresentation::AS => "AS",
-      ValueRepresentation::AT => "AT",
-      ValueRepresentation::CS => "CS",
-      ValueRepresentation::DA => "DA",
-      ValueRepresentation::DS => "DS",
-      ValueRepresentation::DT => "DT",
-      ValueRepresentation::FL => "FL",
-      ValueRepresentation::FD => "FD",
-      ValueRepresentation::IS => "IS",
-      ValueRepresentation::LO => "LO",
-      ValueRepresentation::LT => "LT",
-      ValueRepresentation::OB => "OB",
-      ValueRepresentation::OD => "OD",
-      ValueRepresentation::OF => "OF",
-      ValueRepresentation::OL => "OL",
-      ValueRepresentation::OV => "OV",
-      ValueRepresentation::OW => "OW",
-      ValueRepresentation::PN => "PN",
-      ValueRepresentation::SH => "SH",
-      ValueRepresentation::SL => "SL",
-      ValueRepresentation::SQ => "SQ",
-      ValueRepresentation::SS => "SS",
-      ValueRepresentation::ST => "ST",
-      ValueRepresentation::SV => "SV",
-      ValueRepresentation::TM => "TM",
-      ValueRepresentation::UC => "UC",
-      ValueRepresentation::UI => "UI",
-      ValueRepresentation::UL => "UL",
-      ValueRepresentation::UN => "UN",
-      ValueRepresentation::UR => "UR",
-      ValueRepresentation::US => "US",
-      ValueRepresentation::UT => "UT",
-      ValueRepresentation::UV => "UV",
-    }
-  }
+// Binary values (OD, OF, OL, OV, OW) are encoded in base64 in little endian
+// whatever the byte order of the file.
+fn to_base64_payload<I: Iterator<Item = u8>>(bytes: I) -> Payload {
+  Payload::Value(vec![ValuePayload::String(
+    general_purpose::STANDARD.encode(bytes.collect::<Vec<u8>>()),
+  )])
 }
 
-// TODO: Ugly; replace vr: &str in DicomAttribute with ValueRepresentation
-impl<'a> From<&'a str> for ValueRepresentation {
-  fn from(s: &'a str) -> Self {
-    match s {
-      "AE" => ValueRepresentation::AE,
-      "AS" => ValueRepresentation::AS,
-      "AT" => ValueRepresentation::AT,
-      "CS" => ValueRepresentation::CS,
-      "DA" => ValueRepresentation::DA,
-      "DS" => ValueRepresentation::DS,
-      "DT" => ValueRepresentation::DT,
-      "FL" => ValueRepresentation::FL,
-      "FD" => ValueRepresentation::FD,
-      "IS" => ValueRepresentation::IS,
-      "LO" => ValueRepresentation::LO,
-      "LT" => ValueRepresentation::LT,
-      "OB" => ValueRepresentation::OB,
-      "OD" => ValueRepresentation::OD,
-      "OF" => ValueRepresentation::OF,
-      "OL" => ValueRepresentation::OL,
-      "OV" => ValueRepresentation::OV,
-      "OW" => ValueRepresentation::OW,
-      "PN" => ValueRepresentation::PN,
-      "SH" => ValueRepresentation::SH,
-      "SL" => ValueRepresentation::SL,
-      "SQ" => ValueRepresentation::SQ,
-      "SS" => ValueRepresentation::SS,
-      "ST" => ValueRepresentation::ST,
-      "SV" => ValueRepresentation::SV,
-      "TM" => ValueRepresentation::TM,
-      "UC" => ValueRepresentation::UC,
-      "UI" => ValueRepresentation::UI,
-      "UL" => ValueRepresentation::UL,
-      "UN" => ValueRepresentation::UN,
-      "UR" => ValueRepresentation::UR,
-      "US" => ValueRepresentation::US,
-      "UT" => ValueRepresentation::UT,
-      "UV" => ValueRepresentation::UV,
-      _ => ValueRepresentation::UN,
-    }
-  }
+// IS and DS values are numbers. The malformed values are kept as strings
+// rather than dropped.
+fn to_numeral_payload<F>(values: &[String], parse: F) -> Payload
+where
+  F: Fn(&str) -> Result<f64, DicomError>,
+{
+  Payload::Value(
+    values
+      .iter()
+      .filter(|v| !v.trim().is_empty())
+      .map(|v| match parse(v) {
+        Ok(number) => ValuePayload::Numeral(number),
+        Err(_) => ValuePayload::String(v.trim().to_string()),
+      })
+      .collect::<Vec<ValuePayload>>(),
+  )
 }
 
+// https://stackoverflow.com/a/75303146/2603925
 pub fn to_xml_dicom_attribute(
   instance: &Instance,
   dicom_attribute: &instance::DicomAttribute,
@@ -429,27 +476,80 @@ pub fn to_xml_dicom_attribute(
     DicomValue::OB(content) => Some(Payload::Value(vec![ValuePayload::String(
       general_purpose::STANDARD.encode(content),
     )])),
-    DicomValue::OW(content) => {
-      let content8: &[u8] =
-        unsafe { std::slice::from_raw_parts(content.as_ptr() as *const u8, content.len() / 2) };
-      Some(Payload::Value(vec![ValuePayload::String(
-        general_purpose::STANDARD.encode(content8),
-      )]))
-    }
-    DicomValue::IS(value) => Some(Payload::Value(
+    DicomValue::OW(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::IS(value) => Some(to_numeral_payload(&value, |v| {
+      instance::parse_integer_string(v).map(|v| v as f64)
+    })),
+    DicomValue::OD(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OF(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OL(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OV(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::SV(value) => Some(Payload::Value(
       value
         .iter()
-        .filter_map(|v| v.parse::<i64>().ok())
-        .map(|v| ValuePayload::Numeral(v as f64))
-        .collect::<Vec<ValuePayload>>(),
+        .map(|&v| ValuePayload::Numeral(v as f64))
+        .collect::<_>(),
+    )),
+    DicomValue::UV(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v as f64))
+        .collect::<_>(),
+    )),
+    // https://dicom.nema.org/medical/dicom/current/output/chtml/part18/sect_F.2.7.html
+    DicomValue::AT(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|tag| ValuePayload::String(format!("{:04X}{:04X}", tag.group, tag.element)))
+        .collect::<_>(),
+    )),
+    DicomValue::UC(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|s| ValuePayload::String(s.to_string()))
+        .collect::<_>(),
+    )),
+    DicomValue::SL(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::SS(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::UL(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::US(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::PN(value) => Some(Payload::PersonName(
+      value
+        .iter()
+        .enumerate()
+        .map(|(i, name)| PersonName::to_xml(i + 1, name))
+        .collect::<_>(),
     )),
-    DicomValue::SL(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::SS(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::UL(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::US(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::PN(value) => Some(Payload::Value(vec![ValuePayload::PersonName(
-      PersonName::Alphabetic(NameVariant::Name(value[0].clone())),
-    )])),
     DicomValue::SQ(_) | DicomValue::SeqItem(_) => {
       let dicom_attributes: Result<Vec<DicomAttribute>, DicomError> = dicom_attribute
         .subattributes
@@ -468,9 +568,13 @@ pub fn to_xml_dicom_attribute(
       "{:04x}{:04x}",
       dicom_attribute.tag.group, dicom_attribute.tag.element
     ),
-    vr: dicom_attribute.vr.as_ref().into(),
+    // Items have no VR
+    vr: match dicom_attribute.vr {
+      ValueRepresentation::NA => ValueRepresentation::UN,
+      vr => vr,
+    },
     keyword: Some(dicom_attribute.tag.name.to_string()),
-    private_creator: None,
+    private_creator: dicom_attribute.private_creator.clone(),
     payload,
   })
 }
@@ -487,6 +591,7 @@ pub fn dcm2native_dicom_model(f: File) -> Result<NativeDicomModel, Box<dyn Error
   Ok(NativeDicomModel { dicom_attributes })
 }
 
+// Sequences are not handled here but by the walk in dcm2json
 pub fn to_json_dicom_attribute(
   instance: &Instance,
   dicom_attribute: &instance::DicomAttribute,
@@ -496,46 +601,80 @@ pub fn to_json_dicom_attribute(
     DicomValue::OB(content) => Some(Payload::Value(vec![ValuePayload::String(
       general_purpose::STANDARD.encode(content),
     )])),
-    DicomValue::OW(content) => {
-      let content8: &[u8] =
-        unsafe { std::slice::from_raw_parts(content.as_ptr() as *const u8, content.len() / 2) };
-      Some(Payload::Value(vec![ValuePayload::String(
-        general_purpose::STANDARD.encode(content8),
-      )]))
-    }
-    DicomValue::IS(value) => Some(Payload::Value(
+    DicomValue::OW(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::IS(value) => Some(to_numeral_payload(&value, |v| {
+      instance::parse_integer_string(v).map(|v| v as f64)
+    })),
+    DicomValue::DS(value) => Some(to_numeral_payload(&value, instance::parse_decimal_string)),
+    DicomValue::OD(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OF(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OL(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::OV(content) => Some(to_base64_payload(
+      content.iter().flat_map(|n| n.to_le_bytes()),
+    )),
+    DicomValue::SV(value) => Some(Payload::Value(
       value
         .iter()
-        .filter_map(|v| v.parse::<i64>().ok())
-        .map(|v| ValuePayload::Numeral(v as f64))
-        .collect::<Vec<ValuePayload>>(),
+        .map(|&v| ValuePayload::Numeral(v as f64))
+        .collect::<_>(),
+    )),
+    DicomValue::UV(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v as f64))
+        .collect::<_>(),
+    )),
+    // https://dicom.nema.org/medical/dicom/current/output/chtml/part18/sect_F.2.7.html
+    DicomValue::AT(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|tag| ValuePayload::String(format!("{:04X}{:04X}", tag.group, tag.element)))
+        .collect::<_>(),
+    )),
+    DicomValue::UC(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|s| ValuePayload::String(s.to_string()))
+        .collect::<_>(),
+    )),
+    DicomValue::SL(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::SS(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::UL(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::US(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|&v| ValuePayload::Numeral(v.into()))
+        .collect::<_>(),
+    )),
+    DicomValue::PN(value) => Some(Payload::Value(
+      value
+        .iter()
+        .map(|name| ValuePayload::PersonName(Box::new(PersonName::to_json(name))))
+        .collect::<_>(),
     )),
-    DicomValue::SL(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::SS(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::UL(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::US(value) => Some(Payload::Value(vec![ValuePayload::Numeral(value.into())])),
-    DicomValue::PN(value) => Some(Payload::Value(vec![ValuePayload::PersonName(
-      PersonName::Alphabetic(NameVariant::Name(value[0].clone())),
-    )])),
-    DicomValue::SQ(_) => {
-      let mut values = Vec::<ValuePayload>::new();
-      for da in &dicom_attribute.subattributes {
-        if let Some(Payload::Value(mut v)) = to_json_dicom_attribute(instance, da)?.payload.take() {
-          values.push(v.swap_remove(0));
-        }
-      }
-      Some(Payload::Value(values))
-    }
-    DicomValue::SeqItem(_) => {
-      let mut dicom_attributes = BTreeMap::<String, DicomAttributeJson>::new();
-      for da in &dicom_attribute.subattributes {
-        let tag = format!("{:04x}{:04x}", da.tag.group, da.tag.element);
-        dicom_attributes.insert(tag, to_json_dicom_attribute(instance, da)?);
-      }
-      Some(Payload::Value(vec![ValuePayload::Sequence(
-        dicom_attributes,
-      )]))
-    }
     DicomValue::CS(value) => Some(Payload::Value(
       value
         .iter()
@@ -548,9 +687,9 @@ pub fn to_json_dicom_attribute(
   };
 
   Ok(DicomAttributeJson {
-    vr: dicom_attribute.vr.as_ref().into(),
+    vr: dicom_attribute.vr,
     keyword: Some(dicom_attribute.tag.name.to_string()),
-    private_creator: None,
+    private_creator: dicom_attribute.private_creator.clone(),
     payload,
   })
 }
@@ -559,17 +698,50 @@ pub fn to_json_dicom_attribute(
 // of the difference between XML and JSON that the DICOM norm introduced.
 pub fn dcm2json(f: File) -> Result<BTreeMap<String, DicomAttributeJson>, Box<dyn Error>> {
   let instance = Instance::from_buf_reader(BufReader::new(f))?;
-  let mut dicom_attributes = BTreeMap::<String, DicomAttributeJson>::new();
-  for dicom_attribute in instance.iter() {
-    let dicom_attribute = dicom_attribute?;
-    // println!("{:?}", dicom_attribute);
-    let tag = format!(
-      "{:04x}{:04x}",
-      dicom_attribute.tag.group, dicom_attribute.tag.element
-    );
-    dicom_attributes.insert(tag, to_json_dicom_attribute(&instance, &dicom_attribute)?);
+  // The attributes of the dataset and of the items being walked through
+  let mut datasets = vec![BTreeMap::<String, DicomAttributeJson>::new()];
+  // The sequences being walked through along with their items
+  let mut sequences = Vec::<(DicomAttributeJson, Vec<ValuePayload>)>::new();
+  for step in instance.walk() {
+    match step?.event {
+      WalkEvent::EnterSequence(dicom_attribute) => sequences.push((
+        DicomAttributeJson {
+          vr: dicom_attribute.vr,
+          keyword: Some(dicom_attribute.tag.name.to_string()),
+          private_creator: dicom_attribute.private_creator.clone(),
+          payload: None,
+        },
+        vec![],
+      )),
+      WalkEvent::LeaveSequence(dicom_attribute) => {
+        if let (Some((mut sequence, items)), Some(dataset)) = (sequences.pop(), datasets.last_mut())
+        {
+          sequence.payload = Some(Payload::Value(items));
+          let tag = format!(
+            "{:04x}{:04x}",
+            dicom_attribute.tag.group, dicom_attribute.tag.element
+          );
+          dataset.insert(tag, sequence);
+        }
+      }
+      WalkEvent::EnterItem(_) => datasets.push(BTreeMap::new()),
+      WalkEvent::LeaveItem(_) => {
+        if let (Some(dataset), Some((_, items))) = (datasets.pop(), sequences.last_mut()) {
+          items.push(ValuePayload::Sequence(dataset));
+        }
+      }
+      WalkEvent::Attribute(dicom_attribute) => {
+        if let Some(dataset) = datasets.last_mut() {
+          let tag = format!(
+            "{:04x}{:04x}",
+            dicom_attribute.tag.group, dicom_attribute.tag.element
+          );
+          dataset.insert(tag, to_json_dicom_attribute(&instance, &dicom_attribute)?);
+        }
+      }
+    }
   }
-  Ok(dicom_attributes)
+  Ok(datasets.swap_remove(0))
 }
 
 pub mod json2dcm {
@@ -578,7 +750,8 @@ pub mod json2dcm {
   use std::io::BufWriter;
   use std::io::Write;
 
-  use rdicom::error::DicomError;
+  use rdicom::error::{DicomError, DicomErrorKind};
+  use rdicom::transfer_syntax::{self, TransferSyntax};
 
   use crate::dicom_representation::BTreeMap;
   use crate::dicom_representation::DicomAttribute;
@@ -587,6 +760,9 @@ pub mod json2dcm {
   use crate::dicom_representation::ValuePayload;
   use crate::dicom_representation::ValueRepresentation;
 
+  // serialize writes the attributes in explicit VR little endian
+  const TRANSFER_SYNTAX: TransferSyntax = transfer_syntax::ExplicitVRLittleEndian;
+
   fn write_even_16<W: std::io::Write>(
     writer: &mut W,
     data: &[u8],
@@ -627,8 +803,7 @@ pub mod json2dcm {
     let group_l: u8 = u8::from_str_radix(&dicom_attribute.tag[2..4], 16)?;
     let element_h: u8 = u8::from_str_radix(&dicom_attribute.tag[4..6], 16)?;
     let element_l: u8 = u8::from_str_radix(&dicom_attribute.tag[6..8], 16)?;
-    // TODO: Isn't there a better looking way?
-    let vr: &[u8] = <ValueRepresentation as Into<&str>>::into(dicom_attribute.vr).as_bytes();
+    let vr: &[u8] = dicom_attribute.vr.as_str().as_bytes();
     let mut length: usize = 6;
     writer.write_all(&[group_l, group_h, element_l, element_h, vr[0], vr[1]])?;
 
@@ -636,7 +811,13 @@ pub mod json2dcm {
     if let Some(payload) = dicom_attribute.payload {
       match dicom_attribute.vr {
         // The following VRs expect 2 bytes of padding ([0, 0]) and a 4 bytes length
-        ValueRepresentation::UN | ValueRepresentation::OW | ValueRepresentation::OB => {
+        ValueRepresentation::UN
+        | ValueRepresentation::OB
+        | ValueRepresentation::OD
+        | ValueRepresentation::OF
+        | ValueRepresentation::OL
+        | ValueRepresentation::OV
+        | ValueRepresentation::OW => {
           let data: Vec<u8> = payload.try_into()?;
           length += 6 + write_even_32(writer, data.as_slice())?;
         }
@@ -676,16 +857,54 @@ pub mod json2dcm {
           }
           length += 6 + write_even_32(writer, items_buffer.as_slice())?;
         }
-        ValueRepresentation::UT => {
+        ValueRepresentation::UC | ValueRepresentation::UR | ValueRepresentation::UT => {
           let data: String = payload.try_into()?;
           length += 6 + write_even_32(writer, data.as_bytes())?;
         }
-        ValueRepresentation::OF => {
-          unimplemented!()
+        // JSON numbers are f64, 64 bits integers above 2^53 have already lost precision
+        ValueRepresentation::SV => {
+          let value: Vec<f64> = payload.try_into()?;
+          let data: Vec<u8> = value
+            .iter()
+            .flat_map(|&v| (v as i64).to_le_bytes())
+            .collect();
+          length += 6 + write_even_32(writer, data.as_slice())?;
+        }
+        ValueRepresentation::UV => {
+          let value: Vec<f64> = payload.try_into()?;
+          let data: Vec<u8> = value
+            .iter()
+            .flat_map(|&v| (v as u64).to_le_bytes())
+            .collect();
+          length += 6 + write_even_32(writer, data.as_slice())?;
         }
         // The following VRs expect a 2 bytes length
-        ValueRepresentation::AT | ValueRepresentation::SL | ValueRepresentation::UC => {
-          todo!("{:?}", dicom_attribute.vr);
+        ValueRepresentation::AT => {
+          let value: Vec<String> = match payload {
+            Payload::Value(value) => value
+              .into_iter()
+              .map(|v| match v {
+                ValuePayload::String(tag) => Ok(tag),
+                _ => Err(DicomError::with_kind(
+                  DicomErrorKind::InvalidValue,
+                  "Payload is not an attribute tag",
+                )),
+              })
+              .collect::<Result<_, _>>()?,
+            _ => {
+              return Err(Box::new(DicomError::with_kind(
+                DicomErrorKind::InvalidValue,
+                "Payload is not an attribute tag",
+              )))
+            }
+          };
+          writer.write_all(&((4 * value.len()) as u16).to_le_bytes())?;
+          for tag in value.iter() {
+            let tag = u32::from_str_radix(tag, 16)?;
+            writer.write_all(&((tag >> 16) as u16).to_le_bytes())?;
+            writer.write_all(&((tag & 0xFFFF) as u16).to_le_bytes())?;
+          }
+          length += 2 + 4 * value.len();
         }
         ValueRepresentation::AE
         | ValueRepresentation::AS
@@ -698,40 +917,53 @@ pub mod json2dcm {
         | ValueRepresentation::SH
         | ValueRepresentation::PN
         | ValueRepresentation::ST
-        | ValueRepresentation::TM => {
-          // Strings are padded with space (0x20)
-          // https://dicom.nema.org/dicom/2013/output/chtml/part05/sect_6.2.html
-          let data: String = payload.try_into()?;
-          length += 2 + write_even_16(writer, data.as_bytes(), 0x20)?;
-        }
-        ValueRepresentation::UI => {
-          // UI is padded with 0
+        | ValueRepresentation::TM
+        | ValueRepresentation::UI => {
+          // Strings are padded with space (0x20) and UI with 0
           // https://dicom.nema.org/dicom/2013/output/chtml/part05/sect_6.2.html
           let data: String = payload.try_into()?;
-          length += 2 + write_even_16(writer, data.as_bytes(), 0x0)?;
+          length += 2 + write_even_16(writer, data.as_bytes(), dicom_attribute.vr.padding())?;
         }
         ValueRepresentation::IS => {
           let as_is: String = payload.try_into()?;
           let data: String = as_is.split('.').take(1).collect::<_>();
-          length += 2 + write_even_16(writer, data.as_bytes(), 0x20)?;
+          length += 2 + write_even_16(writer, data.as_bytes(), dicom_attribute.vr.padding())?;
+        }
+        ValueRepresentation::SL => {
+          let value: Vec<i32> = payload.try_into()?;
+          let data_length = std::mem::size_of::<i32>() * value.len();
+          writer.write_all(&(data_length as u16).to_le_bytes())?;
+          for v in value {
+            writer.write_all(&v.to_le_bytes())?;
+          }
+          length += 2 + data_length;
         }
         ValueRepresentation::UL => {
-          let value: u32 = payload.try_into()?;
-          writer.write_all(&(std::mem::size_of_val(&value) as u16).to_le_bytes())?;
-          writer.write_all(&(value.to_le_bytes()))?;
-          length += 2 + std::mem::size_of_val(&value);
+          let value: Vec<u32> = payload.try_into()?;
+          let data_length = std::mem::size_of::<u32>() * value.len();
+          writer.write_all(&(data_length as u16).to_le_bytes())?;
+          for v in value {
+            writer.write_all(&v.to_le_bytes())?;
+          }
+          length += 2 + data_length;
         }
         ValueRepresentation::SS => {
-          let value: i16 = payload.try_into()?;
-          writer.write_all(&(std::mem::size_of_val(&value) as u16).to_le_bytes())?;
-          writer.write_all(&value.to_le_bytes())?;
-          length += 2 + std::mem::size_of_val(&value);
+          let value: Vec<i16> = payload.try_into()?;
+          let data_length = std::mem::size_of::<i16>() * value.len();
+          writer.write_all(&(data_length as u16).to_le_bytes())?;
+          for v in value {
+            writer.write_all(&v.to_le_bytes())?;
+          }
+          length += 2 + data_length;
         }
         ValueRepresentation::US => {
-          let value: u16 = payload.try_into()?;
-          writer.write_all(&(std::mem::size_of_val(&value) as u16).to_le_bytes())?;
-          writer.write_all(&value.to_le_bytes())?;
-          length += 2 + std::mem::size_of_val(&value);
+          let value: Vec<u16> = payload.try_into()?;
+          let data_length = std::mem::size_of::<u16>() * value.len();
+          writer.write_all(&(data_length as u16).to_le_bytes())?;
+          for v in value {
+            writer.write_all(&v.to_le_bytes())?;
+          }
+          length += 2 + data_length;
         }
         ValueRepresentation::FL => {
           let value: Vec<f32> = payload.try_into()?;
@@ -751,31 +983,20 @@ pub mod json2dcm {
           }
           length += 2 + data_length;
         }
-        // TODO: No DicomValue equivalent for now
-        ValueRepresentation::UR
-        | ValueRepresentation::UV
-        | ValueRepresentation::OD
-        | ValueRepresentation::OL
-        | ValueRepresentation::OV
-        | ValueRepresentation::SV => {
-          unimplemented!();
-        } // No DicomValue variant in instance.rs
+        ValueRepresentation::NA => {
+          return Err(Box::new(DicomError::with_kind(
+            DicomErrorKind::InvalidFormat,
+            &format!("{} is not an attribute", dicom_attribute.tag),
+          )));
+        }
       }
     } else {
-      match dicom_attribute.vr {
-        ValueRepresentation::OB
-        | ValueRepresentation::OW
-        | ValueRepresentation::OF
-        | ValueRepresentation::SQ
-        | ValueRepresentation::UT
-        | ValueRepresentation::UN => {
-          writer.write_all(&[0, 0, 0, 0, 0, 0])?;
-          length += 6;
-        }
-        _ => {
-          writer.write_all(&[0, 0])?;
-          length += 2;
-        }
+      if dicom_attribute.vr.has_long_length() {
+        writer.write_all(&[0, 0, 0, 0, 0, 0])?;
+        length += 6;
+      } else {
+        writer.write_all(&[0, 0])?;
+        length += 2;
       }
     }
     Ok(length)
@@ -830,14 +1051,14 @@ pub mod json2dcm {
           private_creator: None,
         },
       )?;
-      // (0002,0010) UI =LittleEndianImplicit                    #  18, 1 TransferSyntaxUID
+      // (0002,0010) UI =LittleEndianExplicit                    #  20, 1 TransferSyntaxUID
       written += serialize(
         &mut meta_info_header_writer,
         DicomAttribute {
           tag: "00020010".to_string(),
           vr: ValueRepresentation::UI,
           payload: Some(Payload::Value(vec![ValuePayload::String(
-            "1.2.840.10008.1.2.1".to_string(),
+            TRANSFER_SYNTAX.uid.uid.to_string(),
           )])),
           keyword: None,
           private_creator: None,
@@ -873,12 +1094,20 @@ pub mod json2dcm {
     writer.write_all(meta_info_header.as_slice())?;
     // Write the rest of the dicom attributes
     for (tag, attribute) in json.iter() {
+      // The strings are written in UTF-8 whatever the original character set was
+      let payload = if tag == "00080005" {
+        Some(Payload::Value(vec![ValuePayload::String(
+          "ISO_IR 192".to_string(),
+        )]))
+      } else {
+        attribute.payload.clone() // TODO: get rid of clone here
+      };
       serialize(
         writer,
         DicomAttribute {
           tag: tag.to_string(),
           vr: attribute.vr,
-          payload: attribute.payload.clone(), // TODO: get rid of clone here
+          payload,
           keyword: None,
           private_creator: None,
         },
@@ -0,0 +1,381 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Tags loaded at runtime on top of the built-in dictionaries (dicom_tags and
+// private_tags), e.g. the in-house private tags of a site. The loaded tags take
+// precedence over the built-in ones, so that Tag::try_from, and therefore the
+// parsing of the instances and the tag paths, use them.
+//
+// Dictionaries are meant to be loaded once, at startup: Tag only holds static
+// strings so the names of the loaded tags are leaked.
+// Nothing can be loaded in wasm, the built-in dictionaries are the only ones.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+#[cfg(not(target_arch = "wasm32"))]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{DicomError, DicomErrorKind};
+use crate::tags::Tag;
+use crate::value_multiplicity::ValueMultiplicity;
+use crate::value_representation::ValueRepresentation;
+
+struct Registry {
+  // Keyed by (group << 16) | element
+  tags: BTreeMap<u32, Tag>,
+  // Upper case keywords, as looked up by Tag::try_from
+  keywords: BTreeMap<String, u32>,
+  // Private tags along with the value of their Private Creator. Like in
+  // private_tags, they are identified by the last byte of their element.
+  private_tags: Vec<(String, Tag)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static REGISTRY: std::sync::RwLock<Registry> = std::sync::RwLock::new(Registry {
+  tags: BTreeMap::new(),
+  keywords: BTreeMap::new(),
+  private_tags: Vec::new(),
+});
+
+// Set once a tag is registered. Tag::try_from goes through the registry first,
+// this spares it the lock when nothing was ever loaded, which is the usual case.
+#[cfg(not(target_arch = "wasm32"))]
+static LOADED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read<T>(f: impl FnOnce(&Registry) -> Option<T>) -> Option<T> {
+  if !LOADED.load(Ordering::Acquire) {
+    return None;
+  }
+  // Entries are inserted whole, a poisoned registry is still consistent
+  f(&REGISTRY.read().unwrap_or_else(|e| e.into_inner()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read<T>(f: impl FnOnce(&Registry) -> Option<T>) -> Option<T> {
+  None
+}
+
+/**
+ * Returns the loaded tag (gggg,eeee) given as 0xggggeeee, if any.
+ */
+pub fn get_by_tag(tag: u32) -> Option<Tag> {
+  read(|registry| registry.tags.get(&tag).cloned())
+}
+
+/**
+ * Returns the loaded tag with this keyword or this group and element in
+ * hexadecimal (e.g. 00091001), if any. The case is ignored.
+ */
+pub fn get_by_name(name: &str) -> Option<Tag> {
+  read(|registry| {
+    if registry.tags.is_empty() {
+      return None;
+    }
+    let name = name.to_uppercase();
+    let tag = match registry.keywords.get(&name) {
+      Some(&tag) => tag,
+      None if name.len() == 8 => u32::from_str_radix(&name, 16).ok()?,
+      None => return None,
+    };
+    registry.tags.get(&tag).cloned()
+  })
+}
+
+/**
+ * Returns the loaded private tag (gggg,xxee) of the block reserved by `creator`,
+ * if any.
+ */
+pub fn get_private(creator: &str, group: u16, element: u16) -> Option<Tag> {
+  read(|registry| {
+    registry
+      .private_tags
+      .iter()
+      .find(|(tag_creator, tag)| {
+        tag_creator == creator && tag.group == group && tag.element & 0x00FF == element & 0x00FF
+      })
+      .map(|(_, tag)| Tag {
+        element,
+        ..tag.clone()
+      })
+  })
+}
+
+/**
+ * Adds a tag to the dictionary, replacing the loaded or built-in tag with the
+ * same group and element. A private tag given with its `private_creator` is
+ * only used in the blocks reserved by this creator.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub fn register(tag: Tag, private_creator: Option<&str>) {
+  let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+  match private_creator {
+    Some(creator) => {
+      registry.private_tags.retain(|(tag_creator, registered)| {
+        !(tag_creator == creator
+          && registered.group == tag.group
+          && registered.element & 0x00FF == tag.element & 0x00FF)
+      });
+      registry.private_tags.push((creator.to_string(), tag));
+    }
+    None => {
+      let key = ((tag.group as u32) << 16) | tag.element as u32;
+      // The keyword of the replaced tag no longer names anything
+      if let Some(replaced) = registry.tags.get(&key) {
+        let keyword = replaced.name.to_uppercase();
+        if registry.keywords.get(&keyword) == Some(&key) {
+          registry.keywords.remove(&keyword);
+        }
+      }
+      registry.keywords.insert(tag.name.to_uppercase(), key);
+      registry.tags.insert(key, tag);
+    }
+  }
+  LOADED.store(true, Ordering::Release);
+}
+
+fn leak(value: &str) -> &'static str {
+  Box::leak(value.to_string().into_boxed_str())
+}
+
+// Builds a tag from the fields of an entry of data-elements.csv, interpreted
+// the same way build.rs does. The repeating groups (e.g. 60xx0010) are not
+// supported and return None.
+fn parse_entry(
+  tag: &str,
+  keyword: &str,
+  vr: &str,
+  vm: &str,
+  description: &str,
+  retired: bool,
+) -> Result<Option<Tag>, DicomError> {
+  let tag = tag.trim();
+  if tag.contains(['x', 'X']) {
+    return Ok(None);
+  }
+  let value = match u32::from_str_radix(tag, 16) {
+    Ok(value) if tag.len() == 8 => value,
+    _ => {
+      return Err(DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!(
+          "Invalid tag {}, expecting a group and element such as 00100010",
+          tag
+        ),
+      ))
+    }
+  };
+  let keyword = keyword.trim();
+  if keyword.is_empty() {
+    return Err(DicomError::with_kind(
+      DicomErrorKind::InvalidFormat,
+      &format!("Missing keyword for tag {}", tag),
+    ));
+  }
+  // In case of something like "US or SS" only the first VR is used
+  let vr = match vr.trim() {
+    // Items and delimitation items have no VR
+    "" => ValueRepresentation::NA,
+    vr => ValueRepresentation::try_from(vr.get(..2).unwrap_or(vr))?,
+  };
+  Ok(Some(Tag {
+    group: (value >> 16) as u16,
+    element: (value & 0xFFFF) as u16,
+    name: leak(keyword),
+    vr,
+    vm: ValueMultiplicity::try_from(vm)?,
+    retired,
+    description: leak(description.trim()),
+  }))
+}
+
+/**
+ * Loads tags from a CSV in the format of data-elements.csv, i.e. lines of
+ *   tag,keyword,VR,VM,description[,RET]
+ * e.g. 00091001,AcmeStudyCode,LO,1,Acme Study Code
+ * RET marks the retired tags. Empty lines and lines starting with '#' are
+ * ignored. Returns the number of tags loaded. Nothing is loaded if any line is
+ * invalid.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_csv(content: &str) -> Result<usize, DicomError> {
+  let mut tags = vec![];
+  for (number, line) in content.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let fields = line.splitn(5, ',').collect::<Vec<&str>>();
+    let tag = match fields[..] {
+      [tag, keyword, vr, vm, description] => match description.strip_suffix(",RET") {
+        Some(description) => parse_entry(tag, keyword, vr, vm, description, true),
+        None => parse_entry(tag, keyword, vr, vm, description, false),
+      },
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        "Expecting tag,keyword,VR,VM,description",
+      )),
+    }
+    .map_err(|e| DicomError {
+      details: format!("line {}: {}", number + 1, e.details),
+      ..e
+    })?;
+    tags.extend(tag);
+  }
+  let count = tags.len();
+  for tag in tags {
+    register(tag, None);
+  }
+  Ok(count)
+}
+
+#[cfg(feature = "yaml")]
+#[derive(serde::Deserialize)]
+struct YamlEntry {
+  tag: String,
+  keyword: String,
+  vr: String,
+  vm: Option<String>,
+  description: Option<String>,
+  retired: Option<bool>,
+  private_creator: Option<String>,
+}
+
+/**
+ * Loads tags from a YAML list of entries such as
+ *   - tag: "00091001"
+ *     keyword: AcmeStudyCode
+ *     vr: LO
+ *     vm: "1"
+ *     description: Acme Study Code
+ *     private_creator: ACME 1.0
+ *
+ * The VM (1 by default), the description, the retired flag (false by default)
+ * and the private creator are optional.
+ * Returns the number of tags loaded. Nothing is loaded if any entry is invalid.
+ */
+#[cfg(feature = "yaml")]
+pub fn load_yaml(content: &str) -> Result<usize, DicomError> {
+  let entries: Vec<YamlEntry> = serde_yaml::from_str(content).map_err(|e| {
+    DicomError::with_kind(DicomErrorKind::InvalidFormat, &e.to_string()).caused_by(e)
+  })?;
+  let mut tags = vec![];
+  for entry in &entries {
+    let tag = parse_entry(
+      &entry.tag,
+      &entry.keyword,
+      &entry.vr,
+      entry.vm.as_deref().unwrap_or("1"),
+      entry.description.as_deref().unwrap_or(&entry.keyword),
+      entry.retired.unwrap_or(false),
+    )?
+    .ok_or_else(|| {
+      DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!("Repeating group tags are not supported ({})", entry.tag),
+      )
+    })?;
+    tags.push((tag, entry.private_creator.as_deref()));
+  }
+  let count = tags.len();
+  for (tag, private_creator) in tags {
+    register(tag, private_creator);
+  }
+  Ok(count)
+}
+
+/**
+ * Loads a dictionary file, in YAML if its extension is .yaml or .yml and in
+ * CSV otherwise. Returns the number of tags loaded.
+ */
+#[cfg(feature = "tools")]
+pub fn load_file(path: &std::path::Path) -> Result<usize, DicomError> {
+  let extension = path.extension().and_then(|extension| extension.to_str());
+  std::fs::read_to_string(path)
+    .map_err(DicomError::from)
+    .and_then(|content| match extension {
+      Some("yaml") | Some("yml") => load_yaml(&content),
+      _ => load_csv(&content),
+    })
+    .map_err(|e| DicomError {
+      details: format!("{}: {}", path.display(), e.details),
+      ..e
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // The registry is shared by all the tests, each one uses its own tags
+
+  #[test]
+  fn replaced_keyword_is_forgotten() {
+    assert_eq!(
+      load_csv("00091001,AcmeStudyCode,LO,1,Acme Study Code\n").unwrap(),
+      1
+    );
+    let tag = get_by_name("AcmeStudyCode").unwrap();
+    assert_eq!((tag.group, tag.element), (0x0009, 0x1001));
+    assert_eq!(get_by_name("acmestudycode"), Some(tag));
+
+    load_csv("# Renamed\n00091001,AcmeStudyId,SH,1,Acme Study Id,RET\n").unwrap();
+    assert_eq!(get_by_name("AcmeStudyCode"), None);
+    let tag = get_by_tag(0x00091001).unwrap();
+    assert_eq!(tag.name, "AcmeStudyId");
+    assert_eq!(tag.vr, ValueRepresentation::SH);
+    assert!(tag.retired);
+    assert_eq!(get_by_name("00091001"), Some(tag));
+  }
+
+  #[test]
+  fn invalid_csv_loads_nothing() {
+    let error = load_csv("00091002,AcmeSeriesCode,LO,1,Acme\n0009100G,Bad,LO,1,Bad\n")
+      .err()
+      .unwrap();
+    assert_eq!(error.kind, DicomErrorKind::InvalidFormat);
+    assert!(error.details.starts_with("line 2: "), "{}", error.details);
+    assert_eq!(get_by_tag(0x00091002), None);
+    assert!(load_csv("00091003,,LO,1,No keyword\n").is_err());
+    assert!(load_csv("00091003,NoVR,XX,1,No VR\n").is_err());
+    // Repeating groups are skipped
+    assert_eq!(
+      load_csv("60xx0010,OverlayRows,US,1,Overlay Rows\n").unwrap(),
+      0
+    );
+  }
+
+  #[cfg(feature = "yaml")]
+  #[test]
+  fn private_tags_from_yaml() {
+    let yaml = "- tag: \"00111004\"\n  keyword: AcmeDose\n  vr: DS\n  private_creator: ACME 1.0\n";
+    assert_eq!(load_yaml(yaml).unwrap(), 1);
+    let tag = get_private("ACME 1.0", 0x0011, 0x1204).unwrap();
+    assert_eq!(
+      (tag.name, tag.element, tag.description),
+      ("AcmeDose", 0x1204, "AcmeDose")
+    );
+    assert_eq!(get_private("OTHER", 0x0011, 0x1004), None);
+    assert_eq!(get_by_tag(0x00111004), None);
+  }
+}
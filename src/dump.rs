@@ -26,15 +26,21 @@ use std::error::Error;
 use std::fs::File;
 use std::io::BufReader;
 use std::io::{self};
+use std::path::PathBuf;
 
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
 use rdicom::dicom_tags::{Item, ItemDelimitationItem, PixelData, SequenceDelimitationItem};
-use rdicom::error::DicomError;
+use rdicom::dictionary;
+use rdicom::error::{DicomError, DicomErrorKind};
+use rdicom::instance::ByteOrder;
 use rdicom::instance::DicomAttribute;
 use rdicom::instance::DicomValue;
 use rdicom::instance::Instance;
+use rdicom::instance::WalkEvent;
+use rdicom::tags::{Tag, TagPath};
+use rdicom::uids;
 
 /// A dcmdump clone based on rdicom
 #[derive(Debug, StructOpt)]
@@ -46,6 +52,15 @@ use rdicom::instance::Instance;
 struct Opt {
   /// DICOM input file to be dumped
   filepath: String,
+  /// Only print the occurrences of this tag (keyword or group and element in
+  /// hexadecimal, e.g. 00080100) at any depth, along with their path
+  #[structopt(short, long)]
+  search: Option<String>,
+  /// Additional dictionary naming the tags absent from the built-in one, in
+  /// the CSV format of data-elements.csv or in YAML (.yaml or .yml). Can be
+  /// repeated.
+  #[structopt(short, long, number_of_values = 1)]
+  dictionary: Vec<PathBuf>,
 }
 
 struct Data<'a> {
@@ -59,106 +74,143 @@ struct Data<'a> {
   level: usize,
 }
 
+fn sequence_data<'a>(field: &DicomAttribute, level: usize) -> Data<'a> {
+  Data {
+    group: field.group,
+    element: field.element,
+    vr: String::from("SQ"),
+    value: if field.length == 0xFFFFFFFF {
+      format!(
+        "(Sequence with undefined length #={})",
+        field.subattributes.len()
+      )
+    } else {
+      format!(
+        "(Sequence with explicit length #={})",
+        field.subattributes.len()
+      )
+    },
+    length: if field.length == 0xFFFFFFFF {
+      "u/l".to_string()
+    } else {
+      format!("{}", field.length)
+    },
+    multiplicity: 1,
+    tag_name: field.tag.name,
+    level,
+  }
+}
+
+fn sequence_delimitation_data<'a>(field: &DicomAttribute, level: usize) -> Data<'a> {
+  Data {
+    group: 0xFFFE,
+    element: 0xE0DD,
+    vr: String::from("na"),
+    value: if field.length != 0xFFFFFFFF {
+      "(SequenceDelimitationItem for re-encod.)".to_string()
+    } else {
+      "(SequenceDelimitationItem)".to_string()
+    },
+    length: format!("{}", 0),
+    multiplicity: 0,
+    tag_name: "SequenceDelimitationItem",
+    level,
+  }
+}
+
+fn item_data<'a>(field: &DicomAttribute, level: usize) -> Data<'a> {
+  Data {
+    group: field.group,
+    element: field.element,
+    vr: String::from("na"),
+    value: if field.length == 0xFFFFFFFF_usize {
+      format!(
+        "(Item with undefined length #={})",
+        field.subattributes.len()
+      )
+    } else {
+      format!(
+        "(Item with explicit length #={})",
+        field.subattributes.len()
+      )
+    },
+    length: if field.length == 0xFFFFFFFF {
+      "u/l".to_string()
+    } else {
+      format!("{}", field.length)
+    },
+    multiplicity: 1,
+    tag_name: field.tag.name,
+    level,
+  }
+}
+
+fn item_delimitation_data<'a>(field: &DicomAttribute, level: usize) -> Data<'a> {
+  Data {
+    group: 0xFFFE,
+    element: 0xE00D,
+    vr: String::from("na"),
+    value: if field.length != 0xFFFFFFFF {
+      "(ItemDelimitationItem for re-encoding)".to_string()
+    } else {
+      "(ItemDelimitationItem)".to_string()
+    },
+    length: format!("{}", 0),
+    multiplicity: 0,
+    tag_name: "ItemDelimitationItem",
+    level,
+  }
+}
+
+// Long values are cut after 66 characters
+fn shorten(display_value: &mut String) {
+  if let Some((index, _)) = display_value.char_indices().nth(66) {
+    display_value.replace_range(index.., "...");
+  }
+}
+
+fn strings_data<'a>(field: &DicomAttribute, payload: &[String], level: usize) -> Data<'a> {
+  let mut display_value = payload.join("\\");
+  shorten(&mut display_value);
+  let (display_value, multiplicity) = if display_value.is_empty() {
+    ("(no value available)".to_string(), 0)
+  } else {
+    (format!("[{}]", display_value), payload.len())
+  };
+  Data {
+    group: field.group,
+    element: field.element,
+    vr: field.vr.to_string(),
+    value: display_value,
+    length: format!("{}", field.data_length),
+    multiplicity,
+    tag_name: field.tag.name,
+    level,
+  }
+}
+
+// Sequences and items are walked through by Instance::walk. Only the pixel
+// sequences, which are not regular sequences, are handled here.
 fn get_tag_sequence<'a>(
   instance: &'a Instance,
-  field: &DicomAttribute<'a>,
+  field: &DicomAttribute,
   level: usize,
-) -> Vec<Data<'a>> {
+) -> Result<Vec<Data<'a>>, DicomError> {
   //   (group, element, vr,     value,  length, multiplicity, tag_name, level)
   let mut result: Vec<Data> = vec![];
-  match field.vr.as_ref() {
-    "SQ" => {
-      result.push(Data {
-        group: field.group,
-        element: field.element,
-        vr: String::from("SQ"),
-        value: if field.length == 0xFFFFFFFF {
-          format!(
-            "(Sequence with undefined length #={})",
-            field.subattributes.len()
-          )
-        } else {
-          format!(
-            "(Sequence with explicit length #={})",
-            field.subattributes.len()
-          )
-        },
-        length: if field.length == 0xFFFFFFFF {
-          "u/l".to_string()
-        } else {
-          format!("{}", field.length)
-        },
-        multiplicity: 1,
-        tag_name: field.tag.name,
-        level,
-      });
-      result.append(
-        &mut field
-          .subattributes
-          .iter()
-          .flat_map(|attr| get_tag_sequence(instance, attr, level + 1))
-          .collect::<_>(),
-      );
-      result.push(Data {
-        group: 0xFFFE,
-        element: 0xE0DD,
-        vr: String::from("na"),
-        value: if field.length != 0xFFFFFFFF {
-          "(SequenceDelimitationItem for re-encod.)".to_string()
-        } else {
-          "(SequenceDelimitationItem)".to_string()
-        },
-        length: format!("{}", 0),
-        multiplicity: 0,
-        tag_name: "SequenceDelimitationItem",
-        level,
-      });
-    }
+  match field.vr {
     _ if field.group == Item.group && field.element == Item.element => {
-      let mut sequence_tags: Vec<_> = field
+      let mut sequence_tags = field
         .subattributes
         .iter()
-        .flat_map(|attr| get_tag_sequence(instance, attr, level + 1))
-        .collect::<_>();
-      result.push(Data {
-        group: field.group,
-        element: field.element,
-        vr: String::from("na"),
-        value: if field.length == 0xFFFFFFFF_usize {
-          format!(
-            "(Item with undefined length #={})",
-            field.subattributes.len()
-          )
-        } else {
-          format!(
-            "(Item with explicit length #={})",
-            field.subattributes.len()
-          )
-        },
-        length: if field.length == 0xFFFFFFFF {
-          "u/l".to_string()
-        } else {
-          format!("{}", field.length)
-        },
-        multiplicity: 1,
-        tag_name: field.tag.name,
-        level,
-      });
+        .map(|attr| get_tag_sequence(instance, attr, level + 1))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+      result.push(item_data(field, level));
       result.append(&mut sequence_tags);
-      result.push(Data {
-        group: 0xFFFE,
-        element: 0xE00D,
-        vr: String::from("na"),
-        value: if field.length != 0xFFFFFFFF {
-          "(ItemDelimitationItem for re-encoding)".to_string()
-        } else {
-          "(ItemDelimitationItem)".to_string()
-        },
-        length: format!("{}", 0),
-        multiplicity: 0,
-        tag_name: "ItemDelimitationItem",
-        level,
-      });
+      result.push(item_delimitation_data(field, level));
     }
     _ if field.group == SequenceDelimitationItem.group
       && field.element == SequenceDelimitationItem.element =>
@@ -173,7 +225,7 @@ fn get_tag_sequence<'a>(
         tag_name: field.tag.name,
         level,
       });
-      return result;
+      return Ok(result);
     }
     // Special case for pixel sequence
     _ if field.group == PixelData.group
@@ -197,7 +249,10 @@ fn get_tag_sequence<'a>(
         &mut field
           .subattributes
           .iter()
-          .flat_map(|attr| get_tag_sequence(instance, attr, level + 1))
+          .map(|attr| get_tag_sequence(instance, attr, level + 1))
+          .collect::<Result<Vec<_>, _>>()?
+          .into_iter()
+          .flatten()
           // Contrary to regular SQ field, we filter out delimitation items (to match dcmdump behavior)
           .filter(|f| {
             !(f.group == ItemDelimitationItem.group && f.element == ItemDelimitationItem.element)
@@ -210,32 +265,20 @@ fn get_tag_sequence<'a>(
           .map(|(_, v)| v)
           .collect::<_>(),
       );
-      result.push(Data {
-        group: 0xFFFE,
-        element: 0xE0DD,
-        vr: String::from("na"),
-        value: if field.length != 0xFFFFFFFF {
-          "(SequenceDelimitationItem for re-encod.)".to_string()
-        } else {
-          "(SequenceDelimitationItem)".to_string()
-        },
-        length: format!("{}", 0),
-        multiplicity: 0,
-        tag_name: "SequenceDelimitationItem",
-        level,
-      });
-      return result;
+      result.push(sequence_delimitation_data(field, level));
+      return Ok(result);
     }
     _ => {
-      let value = DicomValue::from_dicom_attribute(field, instance).unwrap();
+      let value = DicomValue::from_dicom_attribute(field, instance)?;
       match value {
-        DicomValue::UI(payload) => {
+        DicomValue::UI(payload) | DicomValue::UR(payload) => {
           let mut display_value = payload;
-          if display_value.len() > 66 {
-            display_value.replace_range(66.., "...");
-          }
+          shorten(&mut display_value);
           let (display_value, multiplicity) = if display_value.is_empty() {
             ("(no value available)".to_string(), 0)
+          } else if let Some(uid) = uids::get_by_uid(&display_value) {
+            // Like dcmdump, the UIDs of the registry are shown by their keyword
+            (format!("={}", uid.keyword), 1)
           } else {
             (format!("[{}]", display_value), 1)
           };
@@ -256,33 +299,22 @@ fn get_tag_sequence<'a>(
         | DicomValue::IS(payload)
         | DicomValue::LO(payload)
         | DicomValue::LT(payload)
-        | DicomValue::PN(payload)
         | DicomValue::SH(payload)
         | DicomValue::ST(payload)
         | DicomValue::TM(payload)
         | DicomValue::DT(payload)
         | DicomValue::CS(payload)
+        | DicomValue::UC(payload)
         | DicomValue::UT(payload)
         | DicomValue::DS(payload) => {
-          let mut display_value = payload.join("\\");
-          if display_value.len() > 66 {
-            display_value.replace_range(66.., "...");
-          }
-          let (display_value, multiplicity) = if display_value.is_empty() {
-            ("(no value available)".to_string(), 0)
-          } else {
-            (format!("[{}]", display_value), payload.len())
-          };
-          result.push(Data {
-            group: field.group,
-            element: field.element,
-            vr: field.vr.to_string(),
-            value: display_value,
-            length: format!("{}", field.data_length),
-            multiplicity,
-            tag_name: field.tag.name,
-            level,
-          });
+          result.push(strings_data(field, &payload, level));
+        }
+        DicomValue::PN(payload) => {
+          let names = payload
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+          result.push(strings_data(field, &names, level));
         }
         DicomValue::SeqItemEnd => {
           result.push(Data {
@@ -295,16 +327,17 @@ fn get_tag_sequence<'a>(
             tag_name: "Item",
             level,
           });
-          return result;
+          return Ok(result);
         }
         DicomValue::SeqEnd => {
-          panic!("Unexpected SeqEnd");
+          return Err(DicomError::with_kind(
+            DicomErrorKind::InvalidFormat,
+            "Unexpected SeqEnd",
+          ));
         }
-        DicomValue::FD(payload) => {
+        DicomValue::FD(ref payload) => {
           let mut display_value = value.to_string();
-          if display_value.len() > 66 {
-            display_value.replace_range(66.., "...");
-          }
+          shorten(&mut display_value);
           let (display_value, multiplicity) = if display_value.is_empty() {
             ("(no value available)".to_string(), 0)
           } else {
@@ -321,11 +354,9 @@ fn get_tag_sequence<'a>(
             level,
           });
         }
-        DicomValue::FL(payload) => {
+        DicomValue::FL(ref payload) => {
           let mut display_value = value.to_string();
-          if display_value.len() > 66 {
-            display_value.replace_range(66.., "...");
-          }
+          shorten(&mut display_value);
           let (display_value, multiplicity) = if display_value.is_empty() {
             ("(no value available)".to_string(), 0)
           } else {
@@ -344,10 +375,24 @@ fn get_tag_sequence<'a>(
         }
         _ => {
           let display_value = value.to_string();
+          let count = match value {
+            DicomValue::AT(ref payload) => payload.len(),
+            DicomValue::OD(ref payload) => payload.len(),
+            DicomValue::OF(ref payload) => payload.len(),
+            DicomValue::OL(ref payload) => payload.len(),
+            DicomValue::OV(ref payload) => payload.len(),
+            DicomValue::SL(ref payload) => payload.len(),
+            DicomValue::SS(ref payload) => payload.len(),
+            DicomValue::SV(ref payload) => payload.len(),
+            DicomValue::UL(ref payload) => payload.len(),
+            DicomValue::US(ref payload) => payload.len(),
+            DicomValue::UV(ref payload) => payload.len(),
+            _ => 1,
+          };
           let (display_value, multiplicity) = if display_value.is_empty() {
             ("(no value available)".to_string(), 0)
           } else {
-            (display_value, 1)
+            (display_value, count)
           };
           result.push(Data {
             group: field.group,
@@ -363,39 +408,84 @@ fn get_tag_sequence<'a>(
       }
     }
   };
-  result
+  Ok(result)
+}
+
+// Formats a path with the tags keywords (e.g. ProcedureCodeSequence[0].CodeValue)
+fn path_keywords(path: &TagPath) -> String {
+  path
+    .components
+    .iter()
+    .map(|component| match component.item {
+      Some(item) => format!("{}[{}]", component.tag.name, item),
+      None => component.tag.name.to_string(),
+    })
+    .collect::<Vec<String>>()
+    .join(".")
+}
+
+fn search(instance: &Instance, tag: &Tag) -> Result<(), DicomError> {
+  for (path, value) in instance.get_values(tag)? {
+    let mut display_value = value.to_string();
+    shorten(&mut display_value);
+    println!("{} [{}] # {}", path, display_value, path_keywords(&path));
+  }
+  Ok(())
 }
 
 fn dump(opt: &Opt) -> Result<(), DicomError> {
+  for path in &opt.dictionary {
+    dictionary::load_file(path)?;
+  }
   let f = File::open(&opt.filepath)?;
 
   if rdicom::misc::is_dicom_file(&opt.filepath) {
     let instance = Instance::from_buf_reader(BufReader::new(f))?;
+    if let Some(tag) = &opt.search {
+      // Unlike Tag, TagPath accepts the private tags expressed in hexadecimal
+      return search(&instance, TagPath::try_from(tag)?.tag());
+    }
     println!();
-    println!("# Dicom-File-Format");
-    println!();
+    // Datasets without preamble (e.g. ACR-NEMA) have no file meta information
+    if instance.start_offset != 0 {
+      println!("# Dicom-File-Format");
+      println!();
 
-    println!("# Dicom-Meta-Information-Header");
-    println!("# Used TransferSyntax: Little Endian Explicit");
+      println!("# Dicom-Meta-Information-Header");
+      println!("# Used TransferSyntax: Little Endian Explicit");
+    }
 
-    let mut offset = 128 + "DICM".len();
     let mut header = true;
 
     let mut tags = vec![];
-    while offset < instance.buffer.len() {
-      let attribute = &instance.next_attribute(offset)?;
-      tags.append(&mut get_tag_sequence(&instance, attribute, 0));
-      offset = attribute.data_offset + attribute.data_length;
+    for step in instance.walk() {
+      let step = step?;
+      match &step.event {
+        WalkEvent::EnterSequence(field) => tags.push(sequence_data(field, step.depth)),
+        WalkEvent::LeaveSequence(field) => tags.push(sequence_delimitation_data(field, step.depth)),
+        WalkEvent::EnterItem(field) => tags.push(item_data(field, step.depth)),
+        WalkEvent::LeaveItem(field) => tags.push(item_delimitation_data(field, step.depth)),
+        WalkEvent::Attribute(field) => {
+          tags.append(&mut get_tag_sequence(&instance, field, step.depth)?)
+        }
+      }
     }
 
     for data in tags {
-      if header && data.group > 0x0002 {
+      if header && data.group != 0x0002 {
         header = false;
-        println!();
+        if instance.start_offset != 0 {
+          println!();
+        }
         println!("# Dicom-Data-Set");
         println!(
-          "# Used TransferSyntax: Little Endian {}",
-          if instance.implicit {
+          "# Used TransferSyntax: {} {}",
+          if instance.transfer_syntax.byte_order == ByteOrder::BigEndian {
+            "Big Endian"
+          } else {
+            "Little Endian"
+          },
+          if instance.transfer_syntax.implicit {
             "Implicit"
           } else {
             "Explicit"
@@ -421,7 +511,7 @@ fn dump(opt: &Opt) -> Result<(), DicomError> {
 fn main() {
   let opt = Opt::from_args();
   if let Err(e) = dump(&opt) {
-    eprintln!("error: {}", e.details);
+    eprintln!("error: {}", e);
     std::process::exit(1)
   }
 }
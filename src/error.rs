@@ -21,27 +21,114 @@
 use crate::alloc::string::ToString;
 use alloc::boxed::Box;
 use alloc::string::String;
+use alloc::sync::Arc;
 use core::array::TryFromSliceError;
 use core::error::Error;
 use core::fmt;
 use core::str::Utf8Error;
 
-#[derive(Debug)]
+/**
+ * The category of a DicomError, so that the callers can react differently to
+ * e.g. a truncated file and an unknown tag.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DicomErrorKind {
+  // Neither a Part 10 file nor a dataset
+  NotDicom,
+  UnsupportedTransferSyntax,
+  // The data ends in the middle of an attribute
+  Truncated,
+  // The structure of the dataset is invalid (VR, length, sequence items, ...)
+  InvalidFormat,
+  // A text value is invalid in UTF-8 or in the Specific Character Set
+  InvalidEncoding,
+  // A value is malformed (DS, DA, ...) or is not of the expected type
+  InvalidValue,
+  // The tag keyword or tag path does not exist
+  UnknownTag,
+  // A file does not exist
+  NotFound,
+  Io,
+  Other,
+}
+
+impl fmt::Display for DicomErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let kind = match self {
+      DicomErrorKind::NotDicom => "not DICOM",
+      DicomErrorKind::UnsupportedTransferSyntax => "unsupported transfer syntax",
+      DicomErrorKind::Truncated => "truncated",
+      DicomErrorKind::InvalidFormat => "invalid format",
+      DicomErrorKind::InvalidEncoding => "invalid encoding",
+      DicomErrorKind::InvalidValue => "invalid value",
+      DicomErrorKind::UnknownTag => "unknown tag",
+      DicomErrorKind::NotFound => "not found",
+      DicomErrorKind::Io => "I/O error",
+      DicomErrorKind::Other => "other",
+    };
+    write!(f, "{}", kind)
+  }
+}
+
+#[derive(Debug, Clone)]
 pub struct DicomError {
+  pub kind: DicomErrorKind,
   pub details: String,
+  // Position in the buffer where the error was detected
+  pub offset: Option<usize>,
+  // Tag path of the attribute being decoded, e.g. "00081110[0].00081150"
+  pub path: Option<String>,
+  // The underlying error, e.g. an I/O error
+  pub source: Option<Arc<dyn Error + Send + Sync>>,
 }
 
 impl DicomError {
   pub fn new(msg: &str) -> DicomError {
+    DicomError::with_kind(DicomErrorKind::Other, msg)
+  }
+
+  pub fn with_kind(kind: DicomErrorKind, msg: &str) -> DicomError {
     DicomError {
+      kind,
       details: msg.to_string(),
+      offset: None,
+      path: None,
+      source: None,
     }
   }
+
+  /**
+   * Set the position where the error was detected, unless already known.
+   */
+  pub fn at(mut self, offset: usize) -> DicomError {
+    self.offset.get_or_insert(offset);
+    self
+  }
+
+  /**
+   * Set the tag path of the attribute being decoded, unless already known.
+   */
+  pub fn in_path(mut self, path: &str) -> DicomError {
+    self.path.get_or_insert_with(|| path.to_string());
+    self
+  }
+
+  pub fn caused_by<E: Error + Send + Sync + 'static>(mut self, source: E) -> DicomError {
+    self.source = Some(Arc::new(source));
+    self
+  }
 }
 
 impl fmt::Display for DicomError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{}", self.details)
+    write!(f, "{}", self.details)?;
+    if let Some(path) = &self.path {
+      write!(f, " in {}", path)?;
+    }
+    if let Some(offset) = self.offset {
+      write!(f, " at {:#x}", offset)?;
+    }
+    Ok(())
   }
 }
 
@@ -49,38 +136,49 @@ impl Error for DicomError {
   fn description(&self) -> &str {
     &self.details
   }
+
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    self
+      .source
+      .as_ref()
+      .map(|source| source.as_ref() as &(dyn Error + 'static))
+  }
 }
 
 impl From<Box<dyn core::error::Error>> for DicomError {
   fn from(err: Box<dyn core::error::Error>) -> Self {
-    // TODO: Improve this...
-    DicomError::new(&format!("{:?}", err))
+    match err.downcast::<DicomError>() {
+      Ok(err) => *err,
+      Err(err) => DicomError::new(&err.to_string()),
+    }
   }
 }
 
 impl From<Utf8Error> for DicomError {
   fn from(err: Utf8Error) -> Self {
     match err.error_len() {
-      Some(l) => DicomError::new(&format!(
-        "UTF8 error: an unexpected byte was encountered at {}",
-        l
-      )),
-      None => DicomError::new("UTF8 error: the end of the input was reached unexpectedly"),
+      Some(l) => DicomError::with_kind(
+        DicomErrorKind::InvalidEncoding,
+        &format!("UTF8 error: an unexpected byte was encountered at {}", l),
+      ),
+      None => DicomError::with_kind(
+        DicomErrorKind::InvalidEncoding,
+        "UTF8 error: the end of the input was reached unexpectedly",
+      ),
     }
+    .caused_by(err)
   }
 }
 
 impl From<TryFromSliceError> for DicomError {
   fn from(err: TryFromSliceError) -> Self {
-    // TODO: Improve this...
-    DicomError::new(&format!("{:?}", err))
+    DicomError::with_kind(DicomErrorKind::Truncated, &err.to_string()).caused_by(err)
   }
 }
 
 impl From<core::num::ParseIntError> for DicomError {
   fn from(err: core::num::ParseIntError) -> Self {
-    // TODO: Improve this...
-    DicomError::new(&format!("{:?}", err))
+    DicomError::with_kind(DicomErrorKind::InvalidValue, &err.to_string()).caused_by(err)
   }
 }
 
@@ -93,7 +191,11 @@ impl From<&str> for DicomError {
 #[cfg(not(target_arch = "wasm32"))]
 impl From<std::io::Error> for DicomError {
   fn from(err: std::io::Error) -> Self {
-    // TODO: Improve this...
-    DicomError::new(&format!("{:?}", err))
+    let kind = match err.kind() {
+      std::io::ErrorKind::NotFound => DicomErrorKind::NotFound,
+      std::io::ErrorKind::UnexpectedEof => DicomErrorKind::Truncated,
+      _ => DicomErrorKind::Io,
+    };
+    DicomError::with_kind(kind, &err.to_string()).caused_by(err)
   }
 }
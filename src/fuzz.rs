@@ -0,0 +1,235 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Feeds corrupted versions of a corpus of DICOM files to the parser and reports
+// the inputs making it panic. Every malformed input is expected to be rejected
+// with a DicomError.
+//
+// cargo run --bin fuzz --features=tools -- --iterations 1000 /path/to/corpus
+//
+// The inputs causing a panic are written to the crash folder so that they can
+// be replayed with `fuzz <crash file>` or `dump <crash file>`.
+// Panics are caught in order to carry on with the next input, which does not
+// work with the release profiles (they abort on panic).
+
+use clap::Parser;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use walkdir::WalkDir;
+
+use rdicom::dicom_tags::{PatientName, PixelData, SOPInstanceUID, SpecificCharacterSet, StudyDate};
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+
+/// Parse corrupted and truncated versions of DICOM files and report the ones
+/// making the parser panic.
+#[derive(Debug, Parser)]
+#[command(
+  name = format!("fuzz {} ({} {})", env!("GIT_HASH"), env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+  version = "",
+)]
+struct Opt {
+  /// Number of random mutations of each file of the corpus
+  #[arg(short, long, default_value_t = 100)]
+  iterations: usize,
+  /// Seed of the mutations. Runs with the same seed are reproducible.
+  #[arg(short, long, default_value_t = 1)]
+  seed: u64,
+  /// Folder in which the inputs making the parser panic are written
+  #[arg(short, long, default_value = "crashes")]
+  crash_folder: PathBuf,
+  /// DICOM files or folders containing DICOM files (scanned recursively)
+  #[arg(required = true)]
+  corpus: Vec<PathBuf>,
+}
+
+// xorshift64*, good enough to pick mutations and reproducible across platforms
+struct Random(u64);
+
+impl Random {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 >> 12;
+    self.0 ^= self.0 << 25;
+    self.0 ^= self.0 >> 27;
+    self.0.wrapping_mul(0x2545F4914F6CDD1D)
+  }
+
+  // Returns a number in 0..bound (bound must not be 0)
+  fn below(&mut self, bound: usize) -> usize {
+    (self.next() % bound as u64) as usize
+  }
+}
+
+// Values which are likely to hit the edge cases of the parser when written
+// over a length, a tag or a VR.
+const INTERESTING: &[&[u8]] = &[
+  &[0x00, 0x00, 0x00, 0x00],
+  &[0xFF, 0xFF, 0xFF, 0xFF],
+  &[0xFE, 0xFF, 0xFF, 0xFF],
+  &[0xFF, 0xFF, 0xFF, 0x7F],
+  &[0x01, 0x00, 0x00, 0x00],
+  &[0xFE, 0xFF, 0x00, 0xE0],
+  &[0xFE, 0xFF, 0x0D, 0xE0],
+  &[0xFE, 0xFF, 0xDD, 0xE0],
+  b"SQ",
+  b"UN",
+  b"OW",
+  b"PN",
+  b"DA",
+  b"\x1B$B",
+  b"\xE9\x80",
+];
+
+// Applies a few random mutations to a valid file
+fn mutate(original: &[u8], random: &mut Random) -> Vec<u8> {
+  let mut buffer = original.to_vec();
+  for _ in 0..1 + random.below(4) {
+    if buffer.is_empty() {
+      break;
+    }
+    let offset = random.below(buffer.len());
+    match random.below(5) {
+      0 => buffer[offset] ^= 1 << random.below(8),
+      1 => buffer[offset] = random.next() as u8,
+      2 => {
+        let value = INTERESTING[random.below(INTERESTING.len())];
+        let end = (offset + value.len()).min(buffer.len());
+        buffer[offset..end].copy_from_slice(&value[..end - offset]);
+      }
+      3 => buffer.truncate(offset),
+      _ => {
+        // Duplicate a chunk, e.g. an attribute or an item header
+        let length = random.below(64).min(buffer.len() - offset);
+        let chunk = buffer[offset..offset + length].to_vec();
+        let at = random.below(buffer.len());
+        buffer.splice(at..at, chunk);
+      }
+    }
+  }
+  buffer
+}
+
+// Goes through everything the parser offers on an input. Errors are expected,
+// only panics matter.
+fn parse(buffer: &[u8]) {
+  let Ok(instance) = Instance::from(buffer) else {
+    return;
+  };
+  let _ = instance.character_set();
+  for tag in [
+    &SpecificCharacterSet,
+    &StudyDate,
+    &PatientName,
+    &SOPInstanceUID,
+    &PixelData,
+  ] {
+    let _ = instance.get_value(tag);
+    let _ = instance.get_values(tag);
+  }
+  for step in instance.walk() {
+    let Ok(step) = step else {
+      break;
+    };
+    if let WalkEvent::Attribute(attribute) = step.event {
+      let _ = instance.get_value_by_path(&step.path);
+      let Ok(value) = DicomValue::from_dicom_attribute(&attribute, &instance) else {
+        continue;
+      };
+      let _ = value.to_string();
+      let _ = value.to_dates();
+      let _ = value.to_times();
+      let _ = value.to_date_times();
+      let _ = value.to_ages();
+      let _ = value.to_f64s();
+      let _ = value.to_i64s();
+    }
+  }
+}
+
+// Parses an input and saves it in the crash folder if it makes the parser panic
+fn check(buffer: &[u8], opt: &Opt) -> Result<bool, Box<dyn Error>> {
+  if panic::catch_unwind(|| parse(buffer)).is_ok() {
+    return Ok(true);
+  }
+  let mut hasher = DefaultHasher::new();
+  buffer.hash(&mut hasher);
+  fs::create_dir_all(&opt.crash_folder)?;
+  let filepath = opt
+    .crash_folder
+    .join(format!("crash-{:016x}.dcm", hasher.finish()));
+  fs::write(&filepath, buffer)?;
+  eprintln!("panic saved to {}", filepath.display());
+  Ok(false)
+}
+
+fn fuzz(filepath: &Path, opt: &Opt, random: &mut Random) -> Result<usize, Box<dyn Error>> {
+  let original = fs::read(filepath)?;
+  let mut panics = 0;
+  let mut count = |buffer: &[u8]| -> Result<(), Box<dyn Error>> {
+    if !check(buffer, opt)? {
+      panics += 1;
+    }
+    Ok(())
+  };
+  count(&original)?;
+  // Truncated files, in at most 256 steps
+  let step = (original.len() / 256).max(1);
+  for length in (0..original.len()).step_by(step) {
+    count(&original[..length])?;
+  }
+  for _ in 0..opt.iterations {
+    count(&mutate(&original, random))?;
+  }
+  Ok(panics)
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+  let opt = Opt::parse();
+  let mut random = Random(opt.seed.max(1));
+  // The panics are reported once per input, not with their backtrace
+  panic::set_hook(Box::new(|info| eprintln!("{}", info)));
+
+  let mut files = 0;
+  let mut panics = 0;
+  for path in &opt.corpus {
+    for entry in WalkDir::new(path).sort_by_file_name() {
+      let entry = entry?;
+      if !entry.file_type().is_file() {
+        continue;
+      }
+      let file_panics = fuzz(entry.path(), &opt, &mut random)?;
+      if file_panics > 0 {
+        eprintln!("{}: {} panics", entry.path().display(), file_panics);
+      }
+      files += 1;
+      panics += file_panics;
+    }
+  }
+  println!("{} files fuzzed, {} panics", files, panics);
+  Ok(if panics == 0 {
+    ExitCode::SUCCESS
+  } else {
+    ExitCode::FAILURE
+  })
+}
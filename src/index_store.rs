@@ -88,7 +88,7 @@ fn write_data(
   let constraints = uid_fields
     .map(|f| {
       format!(
-        "{}=\"{}\"",
+        "\"{}\"=\"{}\"",
         f,
         data.get(f).unwrap_or(&"undefined".to_string())
       )
@@ -107,7 +107,7 @@ fn write_data(
       .iter()
       .map(|f| {
         format!(
-          "{}=\"{}\"",
+          "\"{}\"=\"{}\"",
           f,
           data.get(f).unwrap_or(&"undefined".to_string())
         )
@@ -123,7 +123,12 @@ fn write_data(
       .map(|x| data.get(x).unwrap_or(&"undefined".to_owned()).clone())
       .map(|x| format!("\"{}\"", x))
       .collect::<Vec<String>>();
-    let column_names = fields.join(",");
+    // Columns are quoted as fields can be tag paths (e.g. ContentSequence[0].CodeValue)
+    let column_names = fields
+      .iter()
+      .map(|f| format!("\"{}\"", f))
+      .collect::<Vec<String>>()
+      .join(",");
     let query = &format!(
       "INSERT INTO {} ({}) VALUES ({});",
       table_name,
@@ -148,7 +153,7 @@ pub fn prepare_db(
 ) -> Result<(), Box<dyn Error>> {
   let table = fields
     .iter()
-    .map(|s| s.to_string() + " TEXT NON NULL")
+    .map(|s| format!("\"{}\" TEXT NON NULL", s))
     .collect::<Vec<String>>()
     .join(",");
   connection.execute(format!(
@@ -38,89 +38,181 @@ use alloc::borrow::Cow;
 use alloc::ffi::CString;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::cell::{OnceCell, RefCell};
 use core::convert::TryInto;
 use core::error::Error;
 use core::fmt;
 use core::str::from_utf8;
 use core::str::Utf8Error;
 
+use crate::character_set::CharacterSet;
+use crate::date_time::{Age, Date, DateTime, Time};
 use crate::dicom_tags::Item;
 use crate::dicom_tags::ItemDelimitationItem;
 use crate::dicom_tags::PixelRepresentation;
 use crate::dicom_tags::SequenceDelimitationItem;
-use crate::error::DicomError;
-use crate::misc::has_dicom_header;
-use crate::tags::Tag;
+use crate::dicom_tags::SpecificCharacterSet;
+use crate::error::{DicomError, DicomErrorKind};
+use crate::misc::{guess_transfer_syntax, has_dicom_header, DICOM_PROBE_SIZE, MAX_NESTING_DEPTH};
+use crate::person_name::PersonName;
+use crate::private_tags::{get_tag, private_creator_element, private_creator_tag};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::{read_until, LazyBuffer};
+use crate::storage::{skip_attribute, Storage};
+use crate::tags::{Tag, TagPath, TagPathComponent};
+use crate::transfer_syntax::{self, TransferSyntax};
+use crate::value_multiplicity::ValueMultiplicity;
+use crate::value_representation::ValueRepresentation;
+
+/**
+ * Order in which the bytes of a binary value are stored in the dataset.
+ * The file meta information group (0002) is always little endian.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+  LittleEndian,
+  BigEndian,
+}
+
+impl ByteOrder {
+  /**
+   * Returns the byte order of the platform we are running on.
+   */
+  pub fn native() -> ByteOrder {
+    if cfg!(target_endian = "little") {
+      ByteOrder::LittleEndian
+    } else {
+      ByteOrder::BigEndian
+    }
+  }
+}
 
 #[derive(Debug)]
-pub struct Instance {
-  pub buffer: Vec<u8>,
-  pub implicit: bool,
+pub struct Instance<'a> {
+  pub buffer: Storage<'a>,
+  pub transfer_syntax: TransferSyntax,
+  // Offset of the first attribute. Past the preamble and the "DICM" prefix for
+  // Part 10 files, 0 for bare datasets.
+  pub start_offset: usize,
+  // If set, the top level attributes from this tag onward are ignored. Allows
+  // to only read the header of a file (e.g. by stopping at PixelData).
+  pub stop_at: Option<Tag>,
+  index: RefCell<AttributeIndex>,
+  // Read from SpecificCharacterSet the first time a text value is decoded
+  character_set: OnceCell<CharacterSet>,
+}
+
+// Position of a top level attribute in the buffer
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+  group: u16,
+  element: u16,
+  offset: usize,
+  sequence: bool,
+}
+
+/**
+ * Positions of the top level attributes of an instance.
+ * The index is built on demand, only as far as needed to find the requested
+ * attributes, so that the file is walked at most once.
+ */
+#[derive(Debug)]
+struct AttributeIndex {
+  // In file order
+  entries: Vec<IndexEntry>,
+  // Offset of the next attribute to index
+  next_offset: usize,
+  // The attributes are not sorted by tag (which is invalid) and can not be
+  // looked up by dichotomy
+  unsorted: bool,
+  // Error which prevented the rest of the dataset from being indexed
+  error: Option<DicomError>,
+}
+
+impl AttributeIndex {
+  fn new(start_offset: usize) -> Self {
+    AttributeIndex {
+      entries: vec![],
+      next_offset: start_offset,
+      unsorted: false,
+      error: None,
+    }
+  }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum DicomValue<'a> {
   AE(Vec<String>),
   AS(Vec<String>),
-  AT(Tag),
+  AT(Vec<Tag>),
   CS(Vec<String>),
   DA(Vec<String>),
   DS(Vec<String>),
   DT(Vec<String>),
-  FD(&'a [f64]),
-  FL(&'a [f32]),
+  FD(Cow<'a, [f64]>),
+  FL(Cow<'a, [f32]>),
   OB(&'a [u8]),
-  OW(&'a [u16]),
-  OL(&'a [u32]),
-  OV(&'a [u64]),
-  OF(&'a [f32]),
-  OD(&'a [f64]),
+  OW(Cow<'a, [u16]>),
+  OL(Cow<'a, [u32]>),
+  OV(Cow<'a, [u64]>),
+  OF(Cow<'a, [f32]>),
+  OD(Cow<'a, [f64]>),
   IS(Vec<String>),
   LO(Vec<String>),
   LT(Vec<String>),
-  // TODO: Manage different type of PersonName (Phonetic and Ideographic)
-  PN(Vec<String>),
+  PN(Vec<PersonName>),
   SeqEnd,
   SeqItem(Vec<DicomValue<'a>>),
   SeqItemEnd,
   SH(Vec<String>),
-  SL(i32),
+  SL(Cow<'a, [i32]>),
   SQ(Vec<DicomValue<'a>>),
-  SS(i16),
+  SS(Cow<'a, [i16]>),
   ST(Vec<String>),
-  SV(i64),
+  SV(Cow<'a, [i64]>),
   TM(Vec<String>),
   UC(Vec<String>),
   UI(String),
   UR(String),
-  UL(u32),
-  US(u16),
+  UL(Cow<'a, [u32]>),
+  US(Cow<'a, [u16]>),
   UT(Vec<String>),
-  UV(u64),
+  UV(Cow<'a, [u64]>),
   UN(&'a [u8]),
 }
 
 // Convert Utf8Error to DicomError with a nice error message.
 fn utf8_error_to_dicom_error(err: Utf8Error, tag: &str, offset: usize) -> DicomError {
   match err.error_len() {
-    Some(l) => DicomError::new(&format!(
-      "UTF8 error: an unexpected byte was encountered while \
-      decoding an {} tag at {:#x} + {}",
-      tag, offset, l
-    )),
-    None => DicomError::new(&format!(
-      "UTF8 error: the end of the input was reached unexpectedly \
-      while decoding an {} tag at {:#x}",
-      tag, offset
-    )),
+    Some(_) => DicomError::with_kind(
+      DicomErrorKind::InvalidEncoding,
+      &format!(
+        "UTF8 error: an unexpected byte was encountered while decoding an {} tag",
+        tag
+      ),
+    ),
+    None => DicomError::with_kind(
+      DicomErrorKind::InvalidEncoding,
+      &format!(
+        "UTF8 error: the end of the input was reached unexpectedly while decoding an {} tag",
+        tag
+      ),
+    ),
   }
+  .at(offset + err.valid_up_to())
+  .caused_by(err)
 }
 
 impl<'a> ToString for DicomValue<'a> {
   fn to_string(&self) -> String {
     match self {
-      DicomValue::AT(value) => format!("({:04x},{:04x})", value.group, value.element),
+      DicomValue::AT(value) => value
+        .iter()
+        .map(|tag| format!("({:04x},{:04x})", tag.group, tag.element))
+        .collect::<Vec<_>>()
+        .join("\\"),
       DicomValue::AE(value)
       | DicomValue::AS(value)
       | DicomValue::CS(value)
@@ -145,70 +237,257 @@ impl<'a> ToString for DicomValue<'a> {
         .collect::<Vec<_>>()
         .join("\\"),
       DicomValue::UN(value) | DicomValue::OB(value) => {
-        let mut result = String::with_capacity(40);
-        let mut it = (*value).iter().peekable();
-        while let Some(n) = it.next() {
-          result.push_str(&format!("{:02x}", n));
-          if result.len() >= 64 {
-            result.replace_range(64.., "...");
-            break;
-          }
-          if it.peek().is_some() {
-            result.push('\\');
-          }
-        }
-        result
-      }
-      DicomValue::OW(value) => {
-        let mut result = String::with_capacity(40);
-        let mut it = (*value).iter().peekable();
-        while let Some(n) = it.next() {
-          result.push_str(&format!("{:04x}", n));
-          if result.len() >= 64 {
-            result.replace_range(64.., "...");
-            break;
-          }
-          if it.peek().is_some() {
-            result.push('\\');
-          }
-        }
-        result
+        join_truncated(value.iter().map(|n| format!("{:02x}", n)))
       }
-      DicomValue::PN(value) => value.join("\\"),
-      // DicomValue::SeqEnd,
-      // DicomValue::SeqItem,
-      // DicomValue::SeqItemEnd,
-      DicomValue::SL(value) => format!("{}", value),
-      // DicomValue::SQ(value),
-      DicomValue::SS(value) => format!("{}", value),
+      DicomValue::OW(value) => join_truncated(value.iter().map(|n| format!("{:04x}", n))),
+      DicomValue::OL(value) => join_truncated(value.iter().map(|n| format!("{:08x}", n))),
+      DicomValue::OV(value) => join_truncated(value.iter().map(|n| format!("{:016x}", n))),
+      DicomValue::OF(value) => join_truncated(value.iter().map(|f| f.to_string())),
+      DicomValue::OD(value) => join_truncated(value.iter().map(|f| f.to_string())),
+      DicomValue::PN(value) => value
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::SeqEnd => "(SequenceDelimitationItem)".to_string(),
+      DicomValue::SeqItem(values) => format!("(Item with {} attributes)", values.len()),
+      DicomValue::SeqItemEnd => "(ItemDelimitationItem)".to_string(),
+      DicomValue::SL(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::SQ(values) => format!("(Sequence with {} items)", values.len()),
+      DicomValue::SS(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::SV(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::UC(value) => value.join("\\"),
       DicomValue::UI(value) => value.to_string(),
-      DicomValue::UL(value) => format!("{}", value),
-      DicomValue::US(value) => format!("{}", value),
-      _ => unimplemented!("No formatter for {:?}", self),
+      DicomValue::UL(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::UR(value) => value.to_string(),
+      DicomValue::US(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+      DicomValue::UV(value) => value
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("\\"),
+    }
+  }
+}
+
+// The offset of the value in the file is only used in error messages.
+// Join the values with '\' and truncate the result after 64 characters. Used
+// for binary values which can be arbitrarily long (e.g. PixelData).
+fn join_truncated<I: Iterator<Item = String>>(values: I) -> String {
+  let mut result = String::with_capacity(40);
+  let mut it = values.peekable();
+  while let Some(value) = it.next() {
+    result.push_str(&value);
+    if result.len() >= 64 {
+      result.replace_range(64.., "...");
+      break;
+    }
+    if it.peek().is_some() {
+      result.push('\\');
     }
   }
+  result
 }
 
-fn to_string_array(
+fn split_values(text: &str) -> Vec<String> {
+  text
+    .trim_matches(char::from(0))
+    .trim()
+    .split('\\')
+    .map(str::to_string)
+    .collect()
+}
+
+// Parse the non empty values of a string attribute (e.g. DA, TM)
+// The errors tell which of the values is malformed.
+fn parse_values<T, F>(values: &[String], parse: F) -> Result<Vec<T>, DicomError>
+where
+  F: Fn(&str) -> Result<T, DicomError>,
+{
+  values
+    .iter()
+    .enumerate()
+    .filter(|(_, value)| !value.trim().is_empty())
+    .map(|(index, value)| {
+      parse(value.as_str()).map_err(|mut err| {
+        err.details = format!("{} (value {} of {})", err.details, index + 1, values.len());
+        err
+      })
+    })
+    .collect()
+}
+
+/**
+ * Parse a decimal string (DS) value, e.g. "-1.5e3". Leading and trailing
+ * spaces are ignored.
+ */
+pub fn parse_decimal_string(value: &str) -> Result<f64, DicomError> {
+  let trimmed = value.trim();
+  // f64::from_str also accepts "inf" or "NaN" which are not valid DS
+  if trimmed.is_empty()
+    || !trimmed
+      .bytes()
+      .all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E'))
+  {
+    return Err(DicomError::with_kind(
+      DicomErrorKind::InvalidValue,
+      &format!("Invalid DS value: {:?}", value),
+    ));
+  }
+  trimmed.parse::<f64>().map_err(|_| {
+    DicomError::with_kind(
+      DicomErrorKind::InvalidValue,
+      &format!("Invalid DS value: {:?}", value),
+    )
+  })
+}
+
+/**
+ * Parse an integer string (IS) value, e.g. "-12". Leading and trailing spaces
+ * are ignored.
+ */
+pub fn parse_integer_string(value: &str) -> Result<i64, DicomError> {
+  value.trim().parse::<i64>().map_err(|_| {
+    DicomError::with_kind(
+      DicomErrorKind::InvalidValue,
+      &format!("Invalid IS value: {:?}", value),
+    )
+  })
+}
+
+// Extract the only value of a multi-valued attribute
+fn single_value<T>(tag: &Tag, values: Option<Vec<T>>) -> Result<Option<T>, DicomError> {
+  match values {
+    Some(values) if values.len() > 1 => Err(DicomError::with_kind(
+      DicomErrorKind::InvalidValue,
+      &format!("{} has {} values, expected 1", tag.name, values.len()),
+    )),
+    Some(mut values) => Ok(values.pop()),
+    None => Ok(None),
+  }
+}
+
+fn to_string_array(vr: &str, offset: usize, value: &[u8]) -> Result<Vec<String>, DicomError> {
+  Ok(split_values(
+    from_utf8(value).map_err(|err| utf8_error_to_dicom_error(err, vr, offset))?,
+  ))
+}
+
+// The VRs whose values are decoded according to the Specific Character Set
+fn is_text(vr: ValueRepresentation) -> bool {
+  matches!(
+    vr,
+    ValueRepresentation::LO
+      | ValueRepresentation::LT
+      | ValueRepresentation::PN
+      | ValueRepresentation::SH
+      | ValueRepresentation::ST
+      | ValueRepresentation::UC
+      | ValueRepresentation::UT
+  )
+}
+
+fn to_text_array(
   vr: &str,
   offset: usize,
-  length: usize,
-  buffer: &[u8],
+  character_set: &CharacterSet,
+  value: &[u8],
 ) -> Result<Vec<String>, DicomError> {
-  Ok(
-    from_utf8(&buffer[offset..offset + length])
-      .map_err(|err| utf8_error_to_dicom_error(err, vr, offset))?
-      .trim_matches(char::from(0))
-      .trim()
-      .split('\\')
-      .map(str::to_string)
-      .collect(),
-  )
+  if character_set.is_default() {
+    return to_string_array(vr, offset, value);
+  }
+  let text = character_set.decode(value, vr == "PN").ok_or_else(|| {
+    DicomError::with_kind(
+      DicomErrorKind::InvalidEncoding,
+      &format!(
+        "Invalid character for the Specific Character Set while decoding an {} tag",
+        vr
+      ),
+    )
+    .at(offset)
+  })?;
+  Ok(split_values(&text))
 }
 
-fn to_string(vr: &str, offset: usize, length: usize, buffer: &[u8]) -> Result<String, DicomError> {
+fn read_u16(buffer: &[u8], offset: usize, byte_order: ByteOrder) -> u16 {
+  let bytes = [buffer[offset], buffer[offset + 1]];
+  match byte_order {
+    ByteOrder::LittleEndian => u16::from_le_bytes(bytes),
+    ByteOrder::BigEndian => u16::from_be_bytes(bytes),
+  }
+}
+
+// Numbers stored in binary values. Every bit pattern is a valid value of these
+// types, which makes reinterpreting the bytes of a value as numbers sound.
+trait Number: Copy {
+  fn from_bytes(bytes: &[u8], byte_order: ByteOrder) -> Self;
+}
+
+macro_rules! impl_number {
+  ($($t:ty),*) => {
+    $(
+      impl Number for $t {
+        fn from_bytes(bytes: &[u8], byte_order: ByteOrder) -> Self {
+          let mut tmp = [0u8; core::mem::size_of::<$t>()];
+          tmp.copy_from_slice(bytes);
+          match byte_order {
+            ByteOrder::LittleEndian => <$t>::from_le_bytes(tmp),
+            ByteOrder::BigEndian => <$t>::from_be_bytes(tmp),
+          }
+        }
+      }
+    )*
+  };
+}
+
+impl_number!(u16, i16, u32, i32, u64, i64, f32, f64);
+
+// Decode a buffer of numbers. The values are copied and the trailing bytes not
+// making a whole number are ignored.
+fn decode_numbers<T: Number>(bytes: &[u8], byte_order: ByteOrder) -> Vec<T> {
+  bytes
+    .chunks_exact(core::mem::size_of::<T>())
+    .map(|chunk| T::from_bytes(chunk, byte_order))
+    .collect()
+}
+
+// Returns the numbers of a binary value. The buffer is borrowed when stored in
+// the byte order of the platform and suitably aligned in memory. Otherwise
+// (e.g. big endian file, value at an odd offset) the values are copied.
+fn to_numbers<T: Number>(bytes: &[u8], byte_order: ByteOrder) -> Cow<'_, [T]> {
+  if byte_order == ByteOrder::native() {
+    // Sound as any bit pattern is a valid T (see Number)
+    let (prefix, numbers, suffix) = unsafe { bytes.align_to::<T>() };
+    if prefix.is_empty() && suffix.is_empty() {
+      return Cow::Borrowed(numbers);
+    }
+  }
+  Cow::Owned(decode_numbers(bytes, byte_order))
+}
+
+fn to_string(vr: &str, offset: usize, value: &[u8]) -> Result<String, DicomError> {
   Ok(
-    from_utf8(&buffer[offset..offset + length])
+    from_utf8(value)
       .map_err(|err| utf8_error_to_dicom_error(err, vr, offset))?
       .trim_matches(char::from(0))
       .trim()
@@ -218,11 +497,11 @@ fn to_string(vr: &str, offset: usize, length: usize, buffer: &[u8]) -> Result<St
 
 impl<'a> DicomValue<'a> {
   pub fn from_dicom_attribute<'b>(
-    attribute: &DicomAttribute<'b>,
-    instance: &'b Instance,
+    attribute: &DicomAttribute,
+    instance: &'b Instance<'_>,
   ) -> Result<DicomValue<'b>, DicomError> {
-    Ok(match attribute.vr.as_ref() {
-      "SQ" => {
+    Ok(match attribute.vr {
+      ValueRepresentation::SQ => {
         let values: Result<Vec<_>, _> = attribute
           .subattributes
           .iter()
@@ -242,109 +521,213 @@ impl<'a> DicomValue<'a> {
         (0xFFFE, 0xE00D) => DicomValue::SeqItemEnd,
         (0xFFFE, 0xE0DD) => DicomValue::SeqEnd,
         _ => DicomValue::new(
-          &attribute.vr,
+          attribute.vr,
           attribute.data_offset,
-          attribute.data_length,
-          &instance.buffer,
+          // SpecificCharacterSet itself is in the default repertoire, whatever
+          // its VR claims in a corrupted file
+          &if is_text(attribute.vr)
+            && (attribute.group, attribute.element)
+              != (SpecificCharacterSet.group, SpecificCharacterSet.element)
+          {
+            match attribute.character_set {
+              Some(character_set) => character_set,
+              None => instance.character_set()?,
+            }
+          } else {
+            CharacterSet::default()
+          },
+          instance
+            .buffer
+            .bytes(attribute.data_offset, attribute.data_length)?,
+          // The file meta information group is always little endian
+          if attribute.group == 0x0002 {
+            ByteOrder::LittleEndian
+          } else {
+            instance.transfer_syntax.byte_order
+          },
         )?,
       },
     })
   }
 
+  // The offset of the value in the file is only used in error messages.
   fn new<'b>(
-    vr: &str,
+    vr: ValueRepresentation,
     offset: usize,
-    length: usize,
-    buffer: &'b [u8],
+    character_set: &CharacterSet,
+    value: &'b [u8],
+    byte_order: ByteOrder,
   ) -> Result<DicomValue<'b>, DicomError> {
     Ok(match vr {
-      "AE" => DicomValue::AE(to_string_array(vr, offset, length, buffer)?),
-      "AS" => DicomValue::AS(to_string_array(vr, offset, length, buffer)?),
-      "AT" => {
-        let tmp: u32 = u32::from_le_bytes(buffer[offset..offset + 4].try_into()?);
-        let tag = match tmp.try_into() {
-          Ok(tag) => tag,
-          Err(_) => {
-            // Tag is private, we have to create manually
-            Tag {
-              group: (tmp & 0xFFFF0000 >> 16) as u16,
-              element: (tmp & 0x0000FFFF) as u16,
-              name: "Private Tag",
-              vr: "AT",
-              vm: core::ops::Range { start: 0, end: 0 },
-              description: "Private Tag",
-            }
-          }
-        };
-        DicomValue::AT(tag)
+      ValueRepresentation::AE => DicomValue::AE(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::AS => DicomValue::AS(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::AT => DicomValue::AT(
+        value
+          .chunks_exact(4)
+          .map(|chunk| {
+            let group = read_u16(chunk, 0, byte_order);
+            let element = read_u16(chunk, 2, byte_order);
+            // The creator of a private tag is not known from here
+            get_tag(group, element, None)
+          })
+          .collect(),
+      ),
+      ValueRepresentation::CS => DicomValue::CS(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::DA => DicomValue::DA(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::DS => DicomValue::DS(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::DT => DicomValue::DT(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::FD => DicomValue::FD(to_numbers(value, byte_order)),
+      ValueRepresentation::FL => DicomValue::FL(to_numbers(value, byte_order)),
+      ValueRepresentation::IS => DicomValue::IS(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::LO => {
+        DicomValue::LO(to_text_array(vr.as_str(), offset, character_set, value)?)
       }
-      "CS" => DicomValue::CS(to_string_array(vr, offset, length, buffer)?),
-      "DA" => DicomValue::DA(to_string_array(vr, offset, length, buffer)?),
-      "DS" => DicomValue::DS(to_string_array(vr, offset, length, buffer)?),
-      "DT" => DicomValue::DT(to_string_array(vr, offset, length, buffer)?),
-      "FD" => {
-        // let tmp: [u8; 4] = buffer[offset..offset + 4].try_into()?;
-        // DicomValue::FL(f32::from_le_bytes(tmp))
-        let fdslice: &[f64] = unsafe {
-          // We create a slice of f64 from a slice of u8. Safe as long as
-          // 1. The size from the DICOM file is correct
-          // 2. We deal only with little endian
-          // This allows to avoid parsing and copying data. Speed and memory over safety here.
-          core::slice::from_raw_parts(
-            buffer[offset..offset + length].as_ptr() as *const f64,
-            length / core::mem::size_of::<f64>(),
-          )
-        };
-        DicomValue::FD(fdslice)
+      ValueRepresentation::LT => {
+        DicomValue::LT(to_text_array(vr.as_str(), offset, character_set, value)?)
       }
-      "FL" => {
-        let flslice: &[f32] = unsafe {
-          core::slice::from_raw_parts(
-            buffer[offset..offset + length].as_ptr() as *const f32,
-            length / core::mem::size_of::<f32>(),
-          )
-        };
-        DicomValue::FL(flslice)
+      ValueRepresentation::OB => DicomValue::OB(value),
+      ValueRepresentation::OW => DicomValue::OW(to_numbers(value, byte_order)),
+      ValueRepresentation::OL => DicomValue::OL(to_numbers(value, byte_order)),
+      ValueRepresentation::OV => DicomValue::OV(to_numbers(value, byte_order)),
+      ValueRepresentation::OF => DicomValue::OF(to_numbers(value, byte_order)),
+      ValueRepresentation::OD => DicomValue::OD(to_numbers(value, byte_order)),
+      ValueRepresentation::PN => DicomValue::PN(
+        to_text_array(vr.as_str(), offset, character_set, value)?
+          .iter()
+          .map(|name| PersonName::parse(name))
+          .collect(),
+      ),
+      ValueRepresentation::SH => {
+        DicomValue::SH(to_text_array(vr.as_str(), offset, character_set, value)?)
       }
-      "IS" => DicomValue::IS(to_string_array(vr, offset, length, buffer)?),
-      "LO" => DicomValue::LO(to_string_array(vr, offset, length, buffer)?),
-      "LT" => DicomValue::LT(to_string_array(vr, offset, length, buffer)?),
-      "OB" => DicomValue::OB(&buffer[offset..offset + length]),
-      "OW" => {
-        let (_, owslice, _) = unsafe { buffer[offset..offset + length].align_to::<u16>() };
-        DicomValue::OW(owslice)
+      ValueRepresentation::SL => DicomValue::SL(to_numbers(value, byte_order)),
+      ValueRepresentation::SS => DicomValue::SS(to_numbers(value, byte_order)),
+      ValueRepresentation::ST => {
+        DicomValue::ST(to_text_array(vr.as_str(), offset, character_set, value)?)
+      }
+      ValueRepresentation::SV => DicomValue::SV(to_numbers(value, byte_order)),
+      ValueRepresentation::TM => DicomValue::TM(to_string_array(vr.as_str(), offset, value)?),
+      ValueRepresentation::UC => {
+        DicomValue::UC(to_text_array(vr.as_str(), offset, character_set, value)?)
+      }
+      ValueRepresentation::UI => DicomValue::UI(to_string(vr.as_str(), offset, value)?),
+      ValueRepresentation::UL => DicomValue::UL(to_numbers(value, byte_order)),
+      ValueRepresentation::US => DicomValue::US(to_numbers(value, byte_order)),
+      ValueRepresentation::UT => {
+        DicomValue::UT(to_text_array(vr.as_str(), offset, character_set, value)?)
+      }
+      ValueRepresentation::UN => DicomValue::UN(value),
+      // UR is single valued, backslash is a valid character in an URL
+      ValueRepresentation::UR => DicomValue::UR(to_string(vr.as_str(), offset, value)?),
+      ValueRepresentation::UV => DicomValue::UV(to_numbers(value, byte_order)),
+      ValueRepresentation::SQ | ValueRepresentation::NA => {
+        return Err(
+          DicomError::with_kind(
+            DicomErrorKind::InvalidFormat,
+            &format!("Value representation {} has no value of its own", vr),
+          )
+          .at(offset),
+        )
       }
-      "PN" => DicomValue::PN(to_string_array(vr, offset, length, buffer)?),
-      "SH" => DicomValue::SH(to_string_array(vr, offset, length, buffer)?),
-      "SL" => DicomValue::SL(
-        buffer[offset] as i32
-          | (buffer[offset + 1] as i32) << 8
-          | (buffer[offset + 2] as i32) << 16
-          | (buffer[offset + 3] as i32) << 24,
-      ),
-      "SS" => DicomValue::SS(buffer[offset] as i16 | (buffer[offset + 1] as i16) << 8),
-      "ST" => DicomValue::ST(to_string_array(vr, offset, length, buffer)?),
-      "TM" => DicomValue::TM(to_string_array(vr, offset, length, buffer)?),
-      "UI" => DicomValue::UI(to_string(vr, offset, length, buffer)?),
-      "UL" => DicomValue::UL(
-        buffer[offset] as u32
-          | (buffer[offset + 1] as u32) << 8
-          | (buffer[offset + 2] as u32) << 16
-          | (buffer[offset + 3] as u32) << 24,
-      ),
-      "US" => DicomValue::US(buffer[offset] as u16 | (buffer[offset + 1] as u16) << 8),
-      "UT" => DicomValue::UT(to_string_array(vr, offset, length, buffer)?),
-      "UN" => DicomValue::UN(&buffer[offset..offset + length]),
-      _ => unimplemented!("Value representation \"{}\" not implemented", vr),
     })
   }
+
+  /**
+   * The values of a DA attribute. Empty values are skipped.
+   */
+  pub fn to_dates(&self) -> Result<Vec<Date>, DicomError> {
+    match self {
+      DicomValue::DA(values) => parse_values(values, |value| Date::try_from(value)),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not a DA",
+      )),
+    }
+  }
+
+  /**
+   * The values of a TM attribute. Empty values are skipped.
+   */
+  pub fn to_times(&self) -> Result<Vec<Time>, DicomError> {
+    match self {
+      DicomValue::TM(values) => parse_values(values, |value| Time::try_from(value)),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not a TM",
+      )),
+    }
+  }
+
+  /**
+   * The values of a DT attribute. Empty values are skipped.
+   */
+  pub fn to_date_times(&self) -> Result<Vec<DateTime>, DicomError> {
+    match self {
+      DicomValue::DT(values) => parse_values(values, |value| DateTime::try_from(value)),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not a DT",
+      )),
+    }
+  }
+
+  /**
+   * The values of an AS attribute. Empty values are skipped.
+   */
+  pub fn to_ages(&self) -> Result<Vec<Age>, DicomError> {
+    match self {
+      DicomValue::AS(values) => parse_values(values, |value| Age::try_from(value)),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not an AS",
+      )),
+    }
+  }
+
+  /**
+   * The values of a numeric attribute (DS, IS, FL or FD) as f64. Empty values
+   * are skipped.
+   */
+  pub fn to_f64s(&self) -> Result<Vec<f64>, DicomError> {
+    match self {
+      DicomValue::DS(values) => parse_values(values, parse_decimal_string),
+      DicomValue::IS(values) => parse_values(values, |value| {
+        parse_integer_string(value).map(|number| number as f64)
+      }),
+      DicomValue::FL(values) => Ok(values.iter().map(|&value| value.into()).collect()),
+      DicomValue::FD(values) => Ok(values.to_vec()),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not a DS, IS, FL or FD",
+      )),
+    }
+  }
+
+  /**
+   * The values of an integer attribute (IS, SS, US, SL, UL or SV) as i64. Empty
+   * values are skipped.
+   */
+  pub fn to_i64s(&self) -> Result<Vec<i64>, DicomError> {
+    match self {
+      DicomValue::IS(values) => parse_values(values, parse_integer_string),
+      DicomValue::SS(values) => Ok(values.iter().map(|&value| value.into()).collect()),
+      DicomValue::US(values) => Ok(values.iter().map(|&value| value.into()).collect()),
+      DicomValue::SL(values) => Ok(values.iter().map(|&value| value.into()).collect()),
+      DicomValue::UL(values) => Ok(values.iter().map(|&value| value.into()).collect()),
+      DicomValue::SV(values) => Ok(values.to_vec()),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidValue,
+        "The value is not an IS, SS, US, SL, UL or SV",
+      )),
+    }
+  }
 }
 
 #[derive(Debug, Clone)]
-pub struct DicomAttribute<'a> {
+pub struct DicomAttribute {
   pub group: u16,
   pub element: u16,
-  pub vr: Cow<'a, str>,
+  pub vr: ValueRepresentation,
   // Position of the tag content in the buffer after the VR and length
   pub data_offset: usize,
   // Length of the data field. Always set to the correct length
@@ -352,26 +735,33 @@ pub struct DicomAttribute<'a> {
   // Length as read on the file
   pub length: usize,
   pub tag: Tag,
-  pub subattributes: Vec<DicomAttribute<'a>>,
+  pub subattributes: Vec<DicomAttribute>,
+  // Value of the Private Creator attribute reserving the block of a private
+  // attribute
+  pub private_creator: Option<String>,
+  // Character set declared by the SpecificCharacterSet of the item the
+  // attribute is in (or of an enclosing item). None when the character set of
+  // the dataset applies.
+  pub character_set: Option<CharacterSet>,
 }
 
-impl<'a> DicomAttribute<'a> {
-  pub fn new<S>(
+// A Private Creator attribute met in an item: group, element and value
+type PrivateCreator = (u16, u16, String);
+
+impl DicomAttribute {
+  pub fn new(
     group: u16,
     element: u16,
-    vr: S,
+    vr: ValueRepresentation,
     data_offset: usize,
     data_length: usize,
     length: usize,
     tag: Tag,
-  ) -> DicomAttribute<'a>
-  where
-    S: Into<Cow<'a, str>>,
-  {
+  ) -> DicomAttribute {
     Self::new_with_subattributes(
       group,
       element,
-      vr.into(),
+      vr,
       data_offset,
       data_length,
       length,
@@ -380,94 +770,267 @@ impl<'a> DicomAttribute<'a> {
     )
   }
 
-  pub fn new_with_subattributes<S>(
+  pub fn new_with_subattributes(
     group: u16,
     element: u16,
-    vr: S,
+    vr: ValueRepresentation,
     data_offset: usize,
     data_length: usize,
     length: usize,
     tag: Tag,
-    subattributes: Vec<DicomAttribute<'a>>,
-  ) -> DicomAttribute<'a>
-  where
-    S: Into<Cow<'a, str>>,
-  {
+    subattributes: Vec<DicomAttribute>,
+  ) -> DicomAttribute {
     DicomAttribute {
       group,
       element,
-      vr: vr.into(),
+      vr,
       data_offset,
       data_length,
       length,
       tag,
       subattributes,
+      private_creator: None,
+      character_set: None,
     }
   }
 }
 
-impl Instance {
+impl<'a> Instance<'a> {
   /**
    * Returns an instance from a BufReader.
-   * The entire BufReader will be read before returning the instance.
+   * The entire BufReader will be read before returning the instance. Use
+   * `from_reader` on seekable inputs in order to read the file on demand.
    */
   #[cfg(not(target_arch = "wasm32"))]
   pub fn from_buf_reader<T: Read>(mut buf_reader: BufReader<T>) -> Result<Self, DicomError> {
     // Read the whole file into a buffer
     let mut buffer: Vec<u8> = vec![];
-    // TODO: Do not read the whole buffer. Use an abstraction in order to allow
-    // opening file that would not hold in memory.
     buf_reader.read_to_end(&mut buffer)?;
-    Instance::from(&buffer)
+    Instance::from_storage(Storage::Owned(buffer))
+  }
+
+  /**
+   * Returns an instance from a BufReader which is only read up to the top
+   * level attribute `stop_at` (excluded), e.g. PixelData in order to only read
+   * the header of a file. The attributes from `stop_at` onward are not
+   * available in the instance.
+   */
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn from_buf_reader_until<T: Read>(
+    mut buf_reader: BufReader<T>,
+    stop_at: Tag,
+  ) -> Result<Self, DicomError> {
+    let buffer = read_until(&mut buf_reader, &stop_at)?;
+    let mut instance = Instance::from_storage(Storage::Owned(buffer))?;
+    instance.stop_at = Some(stop_at);
+    Ok(instance)
   }
 
   /**
    * Returns an instance from a object that implements Read + Seek.
-   * The entire reader will be read before returning the instance.
+   * The reader is not read upfront. Only the parts of the file accessed while
+   * parsing the attributes are read, bulk data (e.g. PixelData) is read when
+   * its value is requested.
    */
   #[cfg(not(target_arch = "wasm32"))]
-  pub fn from_reader<T: Read + Seek>(mut reader: T) -> Result<Self, DicomError> {
-    // Read the whole file into a buffer
-    let mut buffer: Vec<u8> = vec![];
-    // TODO: Do not read the whole buffer. Use an abstraction in order to allow
-    // opening file that would not hold in memory.
-    reader.read_to_end(&mut buffer)?;
-    Instance::from(&buffer)
+  pub fn from_reader<T: Read + Seek + 'static>(reader: T) -> Result<Self, DicomError> {
+    Instance::from_storage(Storage::Lazy(LazyBuffer::new(reader)?))
   }
 
   /**
    * Returns an instance from a file path.
+   * The file is read on demand (see `from_reader`).
    */
   #[cfg(not(target_arch = "wasm32"))]
   pub fn from_filepath(filepath: &str) -> Result<Self, DicomError> {
-    let f = File::open(filepath)?;
-    Instance::from_buf_reader(BufReader::new(f))
+    Instance::from_reader(File::open(filepath)?)
+  }
+
+  /**
+   * Returns an instance from a memory mapped file.
+   *
+   * # Safety
+   *
+   * The file must not be modified (by this or another process) while the
+   * instance is alive, otherwise the content of the instance changes under
+   * its feet which is undefined behavior.
+   */
+  #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+  pub unsafe fn from_mmap(filepath: &str) -> Result<Self, DicomError> {
+    let mmap = memmap2::Mmap::map(&File::open(filepath)?)?;
+    Instance::from_storage(Storage::Mapped(mmap))
   }
 
   /**
-   * Returns an instance from a Vec<u8>.
+   * Returns an instance borrowing the provided buffer.
+   * The buffer is not copied: the instance and the values it returns point
+   * directly into it.
+   * Buffers without the Part 10 preamble are parsed as bare datasets (see
+   * `from_dataset`).
    */
-  // pub fn from(buffer: Vec<u8>) -> Result<Self, DicomError> {
-  pub fn from(buffer: &[u8]) -> Result<Self, DicomError> {
+  pub fn from(buffer: &'a [u8]) -> Result<Self, DicomError> {
+    Instance::from_storage(Storage::Borrowed(buffer))
+  }
+
+  /**
+   * Returns an instance owning the provided buffer.
+   */
+  pub fn from_vec(buffer: Vec<u8>) -> Result<Self, DicomError> {
+    Instance::from_storage(Storage::Owned(buffer))
+  }
+
+  fn from_storage(buffer: Storage<'a>) -> Result<Self, DicomError> {
     // Check it's a DICOM file
-    // TODO: Manage headerless DICOM files
-    if !has_dicom_header(&buffer) {
-      return Err(DicomError::new("Not a DICOM file"));
+    let prefix = buffer.read_bytes(0, buffer.len().min(DICOM_PROBE_SIZE))?;
+    if !has_dicom_header(&prefix) {
+      // Might be a dataset without preamble (ACR-NEMA, raw dataset, ...)
+      if guess_transfer_syntax(&prefix).is_some() {
+        return Instance::from_dataset_storage(buffer, None);
+      }
+      return Err(DicomError::with_kind(
+        DicomErrorKind::NotDicom,
+        "Not a DICOM file",
+      ));
     }
 
     let mut instance = Instance {
-      // TODO: Change API to use &[u8]
-      buffer: Vec::from(buffer),
-      implicit: false,
+      buffer,
+      // Until the actual transfer syntax is known
+      transfer_syntax: transfer_syntax::ExplicitVRLittleEndian,
+      start_offset: 128 + "DICM".len(),
+      stop_at: None,
+      index: RefCell::new(AttributeIndex::new(128 + "DICM".len())),
+      character_set: OnceCell::new(),
     };
 
-    match instance.is_supported_type() {
-      Err(e) => Err(e),
-      Ok(transfer_syntax_uid) => {
-        instance.implicit = transfer_syntax_uid == "1.2.840.10008.1.2";
-        Ok(instance)
+    let transfer_syntax = instance.is_supported_type()?;
+    instance.set_transfer_syntax(transfer_syntax)?;
+    Ok(instance)
+  }
+
+  /**
+   * Returns an instance from a dataset which is not preceded by the Part 10
+   * preamble and "DICM" prefix (raw datasets, legacy ACR-NEMA files, ...).
+   * If no transfer syntax is provided, it is read from the file meta
+   * information if present, otherwise it is guessed from the first attribute
+   * (implicit or explicit VR, little or big endian).
+   */
+  pub fn from_dataset(
+    buffer: &'a [u8],
+    transfer_syntax_uid: Option<&str>,
+  ) -> Result<Self, DicomError> {
+    Instance::from_dataset_storage(Storage::Borrowed(buffer), transfer_syntax_uid)
+  }
+
+  fn from_dataset_storage(
+    buffer: Storage<'a>,
+    transfer_syntax_uid: Option<&str>,
+  ) -> Result<Self, DicomError> {
+    let mut instance = Instance {
+      buffer,
+      // Until the actual transfer syntax is known
+      transfer_syntax: transfer_syntax::ExplicitVRLittleEndian,
+      start_offset: 0,
+      stop_at: None,
+      index: RefCell::new(AttributeIndex::new(0)),
+      character_set: OnceCell::new(),
+    };
+
+    let transfer_syntax = match transfer_syntax_uid {
+      Some(transfer_syntax_uid) => TransferSyntax::try_from(transfer_syntax_uid)?,
+      None
+        if instance.buffer.len() >= 2
+          && instance.buffer.read_u16(0, ByteOrder::LittleEndian)? == 0x0002 =>
+      {
+        instance.is_supported_type()?
       }
+      None => {
+        let prefix = instance
+          .buffer
+          .read_bytes(0, instance.buffer.len().min(DICOM_PROBE_SIZE))?;
+        guess_transfer_syntax(&prefix).ok_or_else(|| {
+          DicomError::with_kind(
+            DicomErrorKind::NotDicom,
+            "Could not determine the transfer syntax of the dataset",
+          )
+        })?
+      }
+    };
+    instance.set_transfer_syntax(transfer_syntax)?;
+    Ok(instance)
+  }
+
+  /**
+   * Returns the character set of the text values, as declared by the top level
+   * SpecificCharacterSet (0008,0005) of the dataset. The default repertoire
+   * (ISO_IR 6) is used when it is absent.
+   * The values of the sequence items declaring their own SpecificCharacterSet
+   * are decoded with it instead (see DicomAttribute::character_set).
+   */
+  pub fn character_set(&self) -> Result<CharacterSet, DicomError> {
+    if let Some(character_set) = self.character_set.get() {
+      return Ok(*character_set);
     }
+    // SpecificCharacterSet is a CS, decoding it does not require the character set
+    let character_set = match self.get_top_level_value(&SpecificCharacterSet)? {
+      Some(DicomValue::CS(terms)) => CharacterSet::from_terms(&terms),
+      _ => CharacterSet::default(),
+    };
+    Ok(*self.character_set.get_or_init(|| character_set))
+  }
+
+  fn set_transfer_syntax(&mut self, transfer_syntax: TransferSyntax) -> Result<(), DicomError> {
+    self.transfer_syntax = transfer_syntax;
+    if transfer_syntax.deflated {
+      self.inflate()?;
+    }
+    // What has been indexed so far was parsed without knowing the transfer syntax
+    self.index = RefCell::new(AttributeIndex::new(self.start_offset));
+    Ok(())
+  }
+
+  /**
+   * Returns the offset of the first attribute following the file meta
+   * information group (0002).
+   */
+  fn get_dataset_offset(&self) -> Result<usize, DicomError> {
+    let mut offset = self.start_offset;
+    // Only peek at the group so that we do not attempt to parse what follows
+    // the meta information (which might be compressed).
+    while offset + 2 <= self.buffer.len()
+      && self.buffer.read_u16(offset, ByteOrder::LittleEndian)? == 0x0002
+    {
+      let attribute = self.next_attribute(offset)?;
+      offset = attribute.data_offset + attribute.data_length;
+    }
+    Ok(offset)
+  }
+
+  /**
+   * Deflated Explicit VR Little Endian: the dataset following the file meta
+   * information is compressed with deflate (RFC 1951, without zlib header).
+   * Replace it in the buffer with its inflated version so that it can be parsed
+   * as a regular explicit little endian dataset.
+   * https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_A.5.html
+   */
+  fn inflate(&mut self) -> Result<(), DicomError> {
+    let dataset_offset = self.get_dataset_offset()?;
+    let dataset = miniz_oxide::inflate::decompress_to_vec(self.buffer.bytes(
+      dataset_offset,
+      self.buffer.len().saturating_sub(dataset_offset),
+    )?)
+    .map_err(|e| {
+      DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!("Could not inflate the dataset: {}", e),
+      )
+      .at(dataset_offset)
+    })?;
+    // The inflated dataset has to be held in memory whatever the storage
+    let mut buffer = Vec::from(self.buffer.bytes(0, dataset_offset)?);
+    buffer.extend_from_slice(&dataset);
+    self.buffer = Storage::Owned(buffer);
+    Ok(())
   }
 
   /**
@@ -477,60 +1040,450 @@ impl Instance {
    * If the tag is not present in the instance, return Ok(None).
    */
   pub fn get_value(&self, tag: &Tag) -> Result<Option<DicomValue<'_>>, DicomError> {
-    // Fast forward the DICOM prefix
-    // TODO: Deal with non-comformant DICOM files
-    // println!("get_value: {:?}", tag);
-    let mut offset = 128 + "DICM".len();
-    return loop {
-      // println!("get_value: offset: {:#06x} buffer length: {:#06x}", offset, self.buffer.len());
+    Ok(self.find_value(tag)?.map(|(_, value)| value))
+  }
+
+  // Same as get_value, along with the path to the attribute the value is from
+  fn find_value(&self, tag: &Tag) -> Result<Option<(TagPath, DicomValue<'_>)>, DicomError> {
+    // Top level attributes first. The ones past stop_at are not read but the
+    // sequences before it may contain the tag (e.g. the PixelData of an icon).
+    let offset = if self.is_after_stop(tag) {
+      None
+    } else {
+      self.find_attribute(tag)
+    };
+    if let Some(offset) = offset {
       let field = self.next_attribute(offset)?;
-      if field.group == tag.group && field.element == tag.element {
-        break Ok(Some(DicomValue::from_dicom_attribute(&field, self)?));
+      let path = TagPath::from(field.tag.clone());
+      let value =
+        DicomValue::from_dicom_attribute(&field, self).map_err(|e| e.in_path(&path.to_string()))?;
+      return Ok(Some((path, value)));
+    }
+    let mut index = self.index.borrow_mut();
+    // Then recursively parse SQ elements
+    self.update_index(&mut index, None);
+    let sequences = index
+      .entries
+      .iter()
+      .filter(|entry| entry.sequence)
+      .map(|entry| entry.offset)
+      .collect::<Vec<_>>();
+    let error = index.error.clone();
+    drop(index);
+    for offset in sequences {
+      let field = self.next_attribute(offset)?;
+      let path = [TagPathComponent {
+        tag: field.tag.clone(),
+        item: None,
+      }];
+      if let Some((path, subfield)) = Instance::get_value_sq(tag, &field, &path) {
+        let value = DicomValue::from_dicom_attribute(&subfield, self)
+          .map_err(|e| e.in_path(&path.to_string()))?;
+        return Ok(Some((path, value)));
       }
+    }
+    match error {
+      // The attribute might be in the part of the file we could not parse
+      Some(error) => Err(error),
+      None => Ok(None),
+    }
+  }
+
+  // Same as get_value but sequences are not searched. Searching them parses
+  // them, which might need the value being looked for (see get_implicit_vr).
+  fn get_top_level_value(&self, tag: &Tag) -> Result<Option<DicomValue<'_>>, DicomError> {
+    if self.is_after_stop(tag) {
+      return Ok(None);
+    }
+    match self.find_attribute(tag) {
+      Some(offset) => Ok(Some(DicomValue::from_dicom_attribute(
+        &self.next_attribute(offset)?,
+        self,
+      )?)),
+      None => Ok(None),
+    }
+  }
+
+  /**
+   * Returns the values of a numeric attribute (DS, IS, FL or FD) as f64, e.g.
+   * `instance.get_f64s(&PixelSpacing)`.
+   * If the tag is not present in the instance, return Ok(None).
+   */
+  pub fn get_f64s(&self, tag: &Tag) -> Result<Option<Vec<f64>>, DicomError> {
+    match self.find_value(tag)? {
+      Some((path, value)) => value
+        .to_f64s()
+        .map(Some)
+        .map_err(|err| err.in_path(&path.to_string())),
+      None => Ok(None),
+    }
+  }
+
+  /**
+   * Same as get_f64s for a single valued attribute, e.g. SliceThickness.
+   * Fails if the attribute has several values.
+   */
+  pub fn get_f64(&self, tag: &Tag) -> Result<Option<f64>, DicomError> {
+    single_value(tag, self.get_f64s(tag)?)
+  }
 
-      // Recursively parse SQ elements
-      if field.vr == "SQ" {
-        if let Ok(Some(subfield)) = Instance::get_value_sq(tag, &field) {
-          break Ok(Some(DicomValue::from_dicom_attribute(&subfield, self)?));
+  /**
+   * Returns the values of an integer attribute (IS, SS, US, SL, UL or SV) as
+   * i64, e.g. `instance.get_i64s(&ReferencedFrameNumber)`.
+   * If the tag is not present in the instance, return Ok(None).
+   */
+  pub fn get_i64s(&self, tag: &Tag) -> Result<Option<Vec<i64>>, DicomError> {
+    match self.find_value(tag)? {
+      Some((path, value)) => value
+        .to_i64s()
+        .map(Some)
+        .map_err(|err| err.in_path(&path.to_string())),
+      None => Ok(None),
+    }
+  }
+
+  /**
+   * Same as get_i64s for a single valued attribute, e.g. InstanceNumber.
+   * Fails if the attribute has several values.
+   */
+  pub fn get_i64(&self, tag: &Tag) -> Result<Option<i64>, DicomError> {
+    single_value(tag, self.get_i64s(tag)?)
+  }
+
+  /**
+   * Returns the value of the attribute pointed by a tag path, e.g.
+   * `ContentSequence[2].ConceptCodeSequence[0].CodeValue`.
+   * A path made of a single tag behaves like get_value.
+   * A path ending with an index returns the item (SeqItem) of the sequence,
+   * e.g. `ContentSequence[2]`.
+   * If the attribute or the item is not present in the instance, return Ok(None).
+   */
+  pub fn get_value_by_path(&self, path: &TagPath) -> Result<Option<DicomValue<'_>>, DicomError> {
+    let (first, rest) = path
+      .components
+      .split_first()
+      .ok_or_else(|| DicomError::with_kind(DicomErrorKind::UnknownTag, "Empty tag path"))?;
+    if rest.is_empty() && first.item.is_none() {
+      return self.get_value(&first.tag);
+    }
+    if self.is_after_stop(&first.tag) {
+      return Ok(None);
+    }
+    match self.find_attribute(&first.tag) {
+      Some(offset) => {
+        let field = self.next_attribute(offset)?;
+        let attribute = if rest.is_empty() {
+          Instance::get_item(field, first.item)
+        } else {
+          Instance::get_attribute_by_path(&field, first.item, rest)
+        };
+        match attribute {
+          Some(attribute) => Ok(Some(
+            DicomValue::from_dicom_attribute(&attribute, self)
+              .map_err(|e| e.in_path(&path.to_string()))?,
+          )),
+          None => Ok(None),
         }
       }
+      // The attribute might be in the part of the file we could not parse
+      None => match &self.index.borrow().error {
+        Some(error) => Err(error.clone()),
+        None => Ok(None),
+      },
+    }
+  }
 
-      offset = field.data_offset
-        + if field.data_length == 0xFFFFFFFF {
-          0
+  // Looks for the attribute pointed by `path` in the items of the sequence
+  // `attribute`. If `item` is None, all the items are searched.
+  fn get_attribute_by_path(
+    attribute: &DicomAttribute,
+    item: Option<usize>,
+    path: &[TagPathComponent],
+  ) -> Option<DicomAttribute> {
+    let (first, rest) = path.split_first()?;
+    attribute
+      .subattributes
+      .iter()
+      .filter(|subattribute| {
+        subattribute.group == Item.group && subattribute.element == Item.element
+      })
+      .enumerate()
+      .filter(|(index, _)| item.is_none_or(|item| *index == item))
+      .flat_map(|(_, subattribute)| subattribute.subattributes.iter())
+      .filter(|subattribute| {
+        subattribute.group == first.tag.group && subattribute.element == first.tag.element
+      })
+      .find_map(|subattribute| {
+        if rest.is_empty() {
+          // TODO: I don't like that clone but not sure how to get rid of it for now
+          Instance::get_item(subattribute.clone(), first.item)
         } else {
-          field.data_length
-        };
-      if offset >= self.buffer.len() {
-        break Ok(None);
+          Instance::get_attribute_by_path(subattribute, first.item, rest)
+        }
+      })
+  }
+
+  // The attribute pointed by the last component of a path: the sequence
+  // itself, or one of its items when the path ends with an index.
+  fn get_item(attribute: DicomAttribute, item: Option<usize>) -> Option<DicomAttribute> {
+    match item {
+      None => Some(attribute),
+      Some(item) => attribute
+        .subattributes
+        .into_iter()
+        .filter(|subattribute| {
+          subattribute.group == Item.group && subattribute.element == Item.element
+        })
+        .nth(item),
+    }
+  }
+
+  /**
+   * Returns all the values of a particular DICOM tag, at any depth, along with
+   * the path to each of them. The values are returned in the order they appear
+   * in the instance.
+   */
+  pub fn get_values(&self, tag: &Tag) -> Result<Vec<(TagPath, DicomValue<'_>)>, DicomError> {
+    let mut index = self.index.borrow_mut();
+    self.update_index(&mut index, None);
+    // Only the attributes to return and the sequences need to be parsed
+    let offsets = index
+      .entries
+      .iter()
+      .filter(|entry| entry.sequence || (entry.group, entry.element) == (tag.group, tag.element))
+      .map(|entry| entry.offset)
+      .collect::<Vec<_>>();
+    if let Some(error) = &index.error {
+      return Err(error.clone());
+    }
+    drop(index);
+    let mut attributes = vec![];
+    for offset in offsets {
+      let field = self.next_attribute(offset)?;
+      let path = vec![TagPathComponent {
+        tag: field.tag.clone(),
+        item: None,
+      }];
+      if field.group == tag.group && field.element == tag.element {
+        attributes.push((
+          TagPath {
+            components: path.clone(),
+          },
+          field.clone(),
+        ));
       }
-    };
+      if field.vr == ValueRepresentation::SQ {
+        Instance::collect_attributes(tag, &field, &path, &mut attributes);
+      }
+    }
+    attributes
+      .into_iter()
+      .map(|(path, attribute)| {
+        let value = DicomValue::from_dicom_attribute(&attribute, self)
+          .map_err(|e| e.in_path(&path.to_string()))?;
+        Ok((path, value))
+      })
+      .collect()
   }
 
-  fn get_value_sq<'a>(
-    // &'a self,
+  // Collects the attributes matching `tag` in the items of the sequence
+  // `attribute` located at `path`.
+  fn collect_attributes(
     tag: &Tag,
-    attr: &DicomAttribute<'a>,
-  ) -> Result<Option<DicomAttribute<'a>>, DicomError> {
-    match attr.vr.as_ref() {
-      "SQ" => attr
-        .subattributes
-        .iter()
-        .map(|subattr| Instance::get_value_sq(tag, subattr))
-        .find(|result| matches!(result, Ok(Some(value))))
-        .unwrap_or(Ok(None)),
-      _ if attr.group == Item.group && attr.element == Item.element => attr
-        .subattributes
+    attribute: &DicomAttribute,
+    path: &[TagPathComponent],
+    attributes: &mut Vec<(TagPath, DicomAttribute)>,
+  ) {
+    let items = attribute.subattributes.iter().filter(|subattribute| {
+      subattribute.group == Item.group && subattribute.element == Item.element
+    });
+    for (index, item) in items.enumerate() {
+      for subattribute in &item.subattributes {
+        let mut subpath = path.to_vec();
+        if let Some(sequence) = subpath.last_mut() {
+          sequence.item = Some(index);
+        }
+        subpath.push(TagPathComponent {
+          tag: subattribute.tag.clone(),
+          item: None,
+        });
+        if subattribute.group == tag.group && subattribute.element == tag.element {
+          attributes.push((
+            TagPath {
+              components: subpath.clone(),
+            },
+            subattribute.clone(),
+          ));
+        }
+        if subattribute.vr == ValueRepresentation::SQ {
+          Instance::collect_attributes(tag, subattribute, &subpath, attributes);
+        }
+      }
+    }
+  }
+
+  // Whether the top level attribute with this tag is located after the tag
+  // parsing stops at. Nested attributes are not concerned.
+  fn is_after_stop(&self, tag: &Tag) -> bool {
+    self
+      .stop_at
+      .as_ref()
+      .is_some_and(|stop_at| (tag.group, tag.element) >= (stop_at.group, stop_at.element))
+  }
+
+  // Returns the offset of a top level attribute, indexing the dataset as far
+  // as needed.
+  fn find_attribute(&self, tag: &Tag) -> Option<usize> {
+    let key = (tag.group, tag.element);
+    let mut index = self.index.borrow_mut();
+    self.update_index(&mut index, Some(key));
+    if index.unsorted {
+      index
+        .entries
         .iter()
-        .map(|subattr| Instance::get_value_sq(tag, subattr))
-        .find(|result| matches!(result, Ok(Some(value))))
-        .unwrap_or(Ok(None)),
-      // TODO: I don't like that clone but not sure how to get rid of it for now
-      _ if attr.group == tag.group && attr.element == tag.element => Ok(Some(attr.clone())),
-      _ => Ok(None),
+        .find(|entry| (entry.group, entry.element) == key)
+        .map(|entry| entry.offset)
+    } else {
+      index
+        .entries
+        .binary_search_by_key(&key, |entry| (entry.group, entry.element))
+        .ok()
+        .map(|i| index.entries[i].offset)
+    }
+  }
+
+  // Indexes the top level attributes up to `until` (included) or up to the end
+  // of the dataset if None.
+  fn update_index(&self, index: &mut AttributeIndex, until: Option<(u16, u16)>) {
+    while index.error.is_none()
+      && match (until, index.entries.last()) {
+        (Some(until), Some(last)) => index.unsorted || (last.group, last.element) < until,
+        _ => true,
+      }
+    {
+      match self.index_next_attribute(index) {
+        Ok(true) => (),
+        Ok(false) => break,
+        Err(e) => index.error = Some(e),
+      }
+    }
+  }
+
+  // Indexes the attribute at `index.next_offset`. Only its header is read.
+  // Returns false once the end of the dataset has been reached.
+  fn index_next_attribute(&self, index: &mut AttributeIndex) -> Result<bool, DicomError> {
+    let offset = index.next_offset;
+    if self.is_end_of_dataset(offset)? {
+      return Ok(false);
+    }
+    let byte_order = self.get_byte_order(offset)?;
+    let implicit = self.transfer_syntax.implicit
+      && self.buffer.read_u16(offset, ByteOrder::LittleEndian)? != 0x0002;
+    let (group, element, next_offset) =
+      skip_attribute(&mut &self.buffer, offset, implicit, byte_order)?;
+    // Same VR as the one next_attribute will find
+    let sequence = if implicit {
+      // The index is being built, the Private Creator can not be found with
+      // find_attribute
+      let creator = private_creator_element(group, element)
+        .and_then(|creator_element| {
+          index
+            .entries
+            .iter()
+            .find(|entry| (entry.group, entry.element) == (group, creator_element))
+        })
+        .and_then(|entry| self.next_attribute(entry.offset).ok())
+        .and_then(|attribute| self.read_private_creator(&attribute));
+      get_tag(group, element, creator.as_deref()).vr == ValueRepresentation::SQ
+    } else {
+      &self.buffer.read_array(offset + 4)? == b"SQ"
+    };
+    if let Some(last) = index.entries.last() {
+      if (group, element) < (last.group, last.element) {
+        index.unsorted = true;
+      }
+    }
+    index.entries.push(IndexEntry {
+      group,
+      element,
+      offset,
+      sequence,
+    });
+    index.next_offset = next_offset;
+    Ok(true)
+  }
+
+  /**
+   * Returns true if there is no top level attribute left to parse at offset,
+   * either because the end of the file or the `stop_at` tag has been reached.
+   */
+  pub fn is_end_of_dataset(&self, offset: usize) -> Result<bool, DicomError> {
+    if offset >= self.buffer.len() {
+      return Ok(true);
+    }
+    match &self.stop_at {
+      Some(stop_at) => {
+        let byte_order = self.get_byte_order(offset)?;
+        let group = self.buffer.read_u16(offset, byte_order)?;
+        let element = self.buffer.read_u16(offset + 2, byte_order)?;
+        Ok((group, element) >= (stop_at.group, stop_at.element))
+      }
+      None => Ok(false),
     }
   }
 
+  // The file meta information group is always encoded in little endian
+  fn get_byte_order(&self, offset: usize) -> Result<ByteOrder, DicomError> {
+    Ok(
+      if self.buffer.read_u16(offset, ByteOrder::LittleEndian)? == 0x0002 {
+        ByteOrder::LittleEndian
+      } else {
+        self.transfer_syntax.byte_order
+      },
+    )
+  }
+
+  // Looks for the first attribute matching `tag` in the items of the sequence
+  // `attribute` located at `path`, and returns it along with its path.
+  fn get_value_sq(
+    tag: &Tag,
+    attribute: &DicomAttribute,
+    path: &[TagPathComponent],
+  ) -> Option<(TagPath, DicomAttribute)> {
+    attribute
+      .subattributes
+      .iter()
+      .filter(|subattribute| {
+        subattribute.group == Item.group && subattribute.element == Item.element
+      })
+      .enumerate()
+      .find_map(|(index, item)| {
+        item.subattributes.iter().find_map(|subattribute| {
+          let mut subpath = path.to_vec();
+          if let Some(sequence) = subpath.last_mut() {
+            sequence.item = Some(index);
+          }
+          subpath.push(TagPathComponent {
+            tag: subattribute.tag.clone(),
+            item: None,
+          });
+          if subattribute.vr == ValueRepresentation::SQ {
+            Instance::get_value_sq(tag, subattribute, &subpath)
+          } else if subattribute.group == tag.group && subattribute.element == tag.element {
+            // TODO: I don't like that clone but not sure how to get rid of it for now
+            Some((
+              TagPath {
+                components: subpath,
+              },
+              subattribute.clone(),
+            ))
+          } else {
+            None
+          }
+        })
+      })
+  }
+
   /**
    * Iterates over the DicomAttribute within the Instance.
    */
@@ -538,6 +1491,14 @@ impl Instance {
     InstanceIter::new(self)
   }
 
+  /**
+   * Walks depth-first through the DicomAttribute within the Instance, entering
+   * sequences and items.
+   */
+  pub fn walk(&self) -> InstanceWalker<'_> {
+    InstanceWalker::new(self)
+  }
+
   // https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_A.4.html
   fn retrieve_next_data_element(
     &self,
@@ -548,33 +1509,35 @@ impl Instance {
     let original_offset = offset;
     let mut offset = offset;
     if offset >= self.buffer.len() {
-      return Err(DicomError::new(&format!(
-        "Trying to read out of file bound (offset: {}, file size: {})",
-        offset,
-        self.buffer.len()
-      )));
+      return Err(
+        DicomError::with_kind(
+          DicomErrorKind::Truncated,
+          &format!(
+            "Trying to read out of file bound (file size: {})",
+            self.buffer.len()
+          ),
+        )
+        .at(offset),
+      );
     }
 
+    let byte_order = self.transfer_syntax.byte_order;
     let mut group;
     let mut element;
     loop {
-      group = self.buffer[offset] as u16 | (self.buffer[offset + 1] as u16) << 8;
-      element = self.buffer[offset + 2] as u16 | (self.buffer[offset + 3] as u16) << 8;
+      group = self.buffer.read_u16(offset, byte_order)?;
+      element = self.buffer.read_u16(offset + 2, byte_order)?;
       offset += 4;
       // println!("retrieve_next_data_element: {:#04x?} {:#06x?}:{:#06x?}", offset, group, element);
       if group == 0xFFFE && element == 0xE000 {
         let length = {
-          let tmp: [u8; 4] = self.buffer[offset..offset + 4].try_into().unwrap();
+          let length = self.buffer.read_u32(offset, byte_order)?;
           offset += 4;
-          u32::from_le_bytes(tmp) as usize
-        };
-        let data: &[u32] = if length != 0 {
-          let tmp: &[u8] = self.buffer[offset..offset + length].try_into().unwrap();
-          offset += length;
-          unsafe { core::mem::transmute(tmp) } // Basic Offset Table data is 32bits unsigned
-        } else {
-          &[]
+          length as usize
         };
+        // Skip the fragment without reading it, the data is only accessed on demand
+        let data_offset = offset;
+        offset = end_of_value(offset, length)?;
 
         let tag = (((group as u32) << 16) | element as u32)
           .try_into()
@@ -582,12 +1545,19 @@ impl Instance {
             group,
             element,
             name: "Unknown Tag & Data",
-            vr: "",
-            vm: core::ops::Range { start: 0, end: 0 },
+            vr: ValueRepresentation::NA,
+            vm: ValueMultiplicity::at_least(1),
+            retired: false,
             description: "Unknown Tag & Data",
           });
         items.push(DicomAttribute::new(
-          group, element, "OB", offset, length, length, tag,
+          group,
+          element,
+          ValueRepresentation::OB,
+          data_offset,
+          length,
+          length,
+          tag,
         ));
       } else {
         break;
@@ -599,16 +1569,20 @@ impl Instance {
       items.push(DicomAttribute::new(
         group,
         element,
-        "",
+        ValueRepresentation::NA,
         offset,
         4,
         4,
         SequenceDelimitationItem,
       ));
     } else {
-      return Err(DicomError::new(
-        "Expecting sequence items in an ecapsulated pixel data field",
-      ));
+      return Err(
+        DicomError::with_kind(
+          DicomErrorKind::InvalidFormat,
+          "Expecting sequence items in an ecapsulated pixel data field",
+        )
+        .at(offset - 4),
+      );
     }
 
     *item_length += offset - original_offset;
@@ -624,35 +1598,41 @@ impl Instance {
     // https://dicom.nema.org/medical/dicom/2017a/output/chtml/part05/chapter_A.html
     if tag.group == 0x7FE0 && tag.element == 0x0010 {
       // PixelData
-      tag.vr = "OW";
+      tag.vr = ValueRepresentation::OW;
       tag.name = "PixelData";
     }
     if tag.group == 0x0028 && tag.element == 0x0106 {
       // SmallestImagePixelValue
       // DICOM makes some fields' value representation depend on the value of other field AND
       // make these value respresentation implicit. What an awful mess...
-      let unsigned = self.get_value(&PixelRepresentation)? == Some(DicomValue::US(0));
+      let unsigned = matches!(
+        self.get_top_level_value(&PixelRepresentation)?,
+        Some(DicomValue::US(value)) if value.first() == Some(&0)
+      );
       if unsigned {
-        tag.vr = "US"
+        tag.vr = ValueRepresentation::US
       } else {
-        tag.vr = "SS"
+        tag.vr = ValueRepresentation::SS
       };
       tag.name = "SmallestImagePixelValue";
     }
     if tag.group == 0x0028 && tag.element == 0x0107 {
       // LargestImagePixelValue
-      let unsigned = self.get_value(&PixelRepresentation)? == Some(DicomValue::US(0));
+      let unsigned = matches!(
+        self.get_top_level_value(&PixelRepresentation)?,
+        Some(DicomValue::US(value)) if value.first() == Some(&0)
+      );
       if unsigned {
-        tag.vr = "US"
+        tag.vr = ValueRepresentation::US
       } else {
-        tag.vr = "SS"
+        tag.vr = ValueRepresentation::SS
       };
       tag.name = "LargestImagePixelValue";
     }
     if tag.element == 0x0000 {
       // GenericGroupLength
       // So apparently, all tag with element = 0 are GenericGroupLength.
-      tag.vr = "UL";
+      tag.vr = ValueRepresentation::UL;
       tag.name = "GenericGroupLength";
     }
 
@@ -662,49 +1642,102 @@ impl Instance {
   /**
    * Returns the next attribute.
    */
-  pub fn next_attribute(&self, offset: usize) -> Result<DicomAttribute<'_>, DicomError> {
+  pub fn next_attribute(&self, offset: usize) -> Result<DicomAttribute, DicomError> {
+    self.next_nested_attribute(offset, 0, None, None)
+  }
+
+  // `depth` is the number of sequences and items the attribute is nested in.
+  // `creators` are the Private Creator attributes of the item the attribute is
+  // in, None for the top level attributes.
+  // `character_set` is the one declared by the item the attribute is in or by
+  // an enclosing item, if any.
+  fn next_nested_attribute(
+    &self,
+    offset: usize,
+    depth: usize,
+    creators: Option<&[PrivateCreator]>,
+    character_set: Option<CharacterSet>,
+  ) -> Result<DicomAttribute, DicomError> {
     // group(u16),element(u16),vr(str[2]),length(u16)
     // println!("next_attribute: {:#04x?}", offset);
     let mut offset = offset;
+    if depth > MAX_NESTING_DEPTH {
+      return Err(
+        DicomError::with_kind(
+          DicomErrorKind::InvalidFormat,
+          &format!(
+            "Sequences nested more than {} levels deep",
+            MAX_NESTING_DEPTH
+          ),
+        )
+        .at(offset),
+      );
+    }
     if offset >= self.buffer.len() {
-      return Err(DicomError::new(&format!(
-        "Trying to read out of file bound (offset: {}, file size: {})",
-        offset,
-        self.buffer.len()
-      )));
+      return Err(
+        DicomError::with_kind(
+          DicomErrorKind::Truncated,
+          &format!(
+            "Trying to read out of file bound (file size: {})",
+            self.buffer.len()
+          ),
+        )
+        .at(offset),
+      );
     }
-    let group = self.buffer[offset] as u16 | (self.buffer[offset + 1] as u16) << 8;
-    let element = self.buffer[offset + 2] as u16 | (self.buffer[offset + 3] as u16) << 8;
+    let byte_order = self.get_byte_order(offset)?;
+    let group = self.buffer.read_u16(offset, byte_order)?;
+    let element = self.buffer.read_u16(offset + 2, byte_order)?;
     // println!("next_attribute: {:#04x?} {:#06x?}:{:#06x?}", offset, group, element);
     offset += 4; // Skip group and element
                  // Check if we have a sequence related data element
     if group == 0xFFFE {
       // Sequence delimiter items can have a length or 0xFFFFFFFF like sequence themselves
-      let tmp: [u8; 4] = self.buffer[offset..offset + 4].try_into().unwrap();
-      let length = u32::from_le_bytes(tmp) as usize; // Can sometimes be equal to 0xFFFFFFFF
+      // Can sometimes be equal to 0xFFFFFFFF
+      let length = self.buffer.read_u32(offset, byte_order)? as usize;
       offset += 4;
       return match element {
         0xE000 => {
           let mut subattributes: Vec<DicomAttribute> = vec![];
+          let mut creators: Vec<PrivateCreator> = vec![];
+          let mut item_character_set = character_set;
           let mut subattribute;
           let mut suboffset = offset;
           let mut item_length = 0;
           // If size is 0xFFFFFFFF, then the sequence will have a delimiter, and
           // then to not perform offset + length which will overflow on 32bits
           // architecture (wasm)
-          while length == 0xFFFFFFFF || suboffset < (offset + length) {
-            subattribute = self.next_attribute(suboffset)?;
+          let end = match length {
+            0xFFFFFFFF => None,
+            _ => Some(end_of_value(offset, length)?),
+          };
+          while end.is_none_or(|end| suboffset < end) {
+            subattribute = self.next_nested_attribute(
+              suboffset,
+              depth + 1,
+              Some(&creators),
+              item_character_set,
+            )?;
             suboffset = subattribute.data_offset + subattribute.data_length;
             item_length = (subattribute.data_offset + subattribute.data_length) - offset;
             if subattribute.tag == ItemDelimitationItem {
               break;
             }
+            if subattribute.tag == private_creator_tag(subattribute.group, subattribute.element) {
+              if let Some(creator) = self.read_private_creator(&subattribute) {
+                creators.push((subattribute.group, subattribute.element, creator));
+              }
+            }
+            // Applies to the attributes following it in the item
+            if subattribute.tag == SpecificCharacterSet {
+              item_character_set = Some(self.read_character_set(&subattribute));
+            }
             subattributes.push(subattribute);
           }
           Ok(DicomAttribute::new_with_subattributes(
             group,
             element,
-            "",
+            ValueRepresentation::NA,
             offset,
             item_length,
             length,
@@ -712,62 +1745,63 @@ impl Instance {
             subattributes,
           ))
         }
-        0xE00D => Ok(DicomAttribute::new(
-          group,
-          element,
-          "",
-          offset,
-          length,
-          length,
-          ItemDelimitationItem,
-        )),
-        0xE0DD => Ok(DicomAttribute::new(
-          group,
-          element,
-          "",
-          offset,
-          length,
-          length,
-          SequenceDelimitationItem,
-        )),
-        _ => Err(DicomError::new(&format!(
-          "unknown sequence related data element: {}",
-          element
-        ))),
+        // The length of the delimiters is skipped over
+        0xE00D => {
+          end_of_value(offset, length)?;
+          Ok(DicomAttribute::new(
+            group,
+            element,
+            ValueRepresentation::NA,
+            offset,
+            length,
+            length,
+            ItemDelimitationItem,
+          ))
+        }
+        0xE0DD => {
+          end_of_value(offset, length)?;
+          Ok(DicomAttribute::new(
+            group,
+            element,
+            ValueRepresentation::NA,
+            offset,
+            length,
+            length,
+            SequenceDelimitationItem,
+          ))
+        }
+        _ => Err(
+          DicomError::with_kind(
+            DicomErrorKind::InvalidFormat,
+            &format!("unknown sequence related data element: {}", element),
+          )
+          .at(offset - 8),
+        ),
       };
     }
     // Create tag based on group and element or generate a synthetic "unknown" tag
-    let mut tag = (((group as u32) << 16) | element as u32)
-      .try_into()
-      .unwrap_or(Tag {
-        group,
-        element,
-        name: "Unknown Tag & Data",
-        vr: "UN",
-        vm: core::ops::Range { start: 0, end: 0 },
-        description: "Unknown Tag & Data",
-      });
-    let vr = if group == 0x0002 || !self.implicit {
+    let private_creator = self.get_private_creator(group, element, creators);
+    let mut tag = get_tag(group, element, private_creator.as_deref());
+    let vr = if group == 0x0002 || !self.transfer_syntax.implicit {
       offset += 2; // Skip VR
-      from_utf8(&self.buffer[offset - 2..offset])
-        .map_err(|err| utf8_error_to_dicom_error(err, "tag", offset - 2))?
+      ValueRepresentation::try_from(&self.buffer.read_array::<2>(offset - 2)?[..])
+        .map_err(|err| err.at(offset - 2))?
     } else {
       self.get_implicit_vr(&mut tag)?;
       tag.vr
     };
 
     let length: usize;
-    if ["OB", "OD", "OF", "OL", "OW", "SQ", "UC", "UR", "UT", "UN"].contains(&vr) {
+    if vr.has_long_length() {
       // These VR types handles themselves differently. They have 2 reserved bytes
       // that need to be skipped and their data length is on 4 bytes.
       // https://dicom.nema.org/dicom/2013/output/chtml/part05/chapter_7.html#sect_7.1.2
-      if group == 0x0002 || !self.implicit {
+      if group == 0x0002 || !self.transfer_syntax.implicit {
         offset += 2; // Skip reserved byte
       }
-      let tmp: [u8; 4] = self.buffer[offset..offset + 4].try_into().unwrap();
-      length = u32::from_le_bytes(tmp) as usize; // Can sometimes be equal to 0xFFFFFFFF
+      length = self.buffer.read_u32(offset, byte_order)? as usize; // Can sometimes be equal to 0xFFFFFFFF
       offset += 4;
-      if vr == "SQ" || // Sequence are special types within those special types... yikes.
+      if vr == ValueRepresentation::SQ || // Sequence are special types within those special types... yikes.
          length == 0xFFFFFFFF
       {
         // https://github.com/pydicom/pydicom/issues/1140
@@ -783,9 +1817,13 @@ impl Instance {
           // on what a sequence look like in a DICOM file.
           let mut item;
           let mut suboffset = offset;
+          let end = match length {
+            0xFFFFFFFF => None,
+            _ => Some(end_of_value(offset, length)?),
+          };
           // Go through the items in the sequence and fetch them recursively
-          while length == 0xFFFFFFFF || suboffset < (offset + length) {
-            item = self.next_attribute(suboffset)?;
+          while end.is_none_or(|end| suboffset < end) {
+            item = self.next_nested_attribute(suboffset, depth + 1, Some(&[]), character_set)?;
             suboffset = item.data_offset + item.data_length;
             item_length = (item.data_offset + item.data_length) - offset;
             if item.tag == SequenceDelimitationItem {
@@ -794,7 +1832,7 @@ impl Instance {
             items.push(item);
           }
         }
-        return Ok(DicomAttribute::new_with_subattributes(
+        let mut attribute = DicomAttribute::new_with_subattributes(
           group,
           element,
           vr,
@@ -803,132 +1841,417 @@ impl Instance {
           length,
           tag,
           items,
-        ));
+        );
+        attribute.private_creator = private_creator;
+        attribute.character_set = character_set;
+        return Ok(attribute);
       }
     } else {
-      length = if group == 0x0002 || !self.implicit {
+      length = if group == 0x0002 || !self.transfer_syntax.implicit {
         offset += 2;
-        self.buffer[offset - 2] as usize | (self.buffer[offset - 1] as usize) << 8
+        self.buffer.read_u16(offset - 2, byte_order)? as usize
       } else {
         offset += 4;
-        self.buffer[offset - 4] as usize
-          | (self.buffer[offset - 3] as usize) << 8
-          | (self.buffer[offset - 2] as usize) << 16
-          | (self.buffer[offset - 1] as usize) << 24
+        self.buffer.read_u32(offset - 4, byte_order)? as usize
       }
     }
-    Ok(DicomAttribute::new(
-      group, element, vr, offset, length, length, tag,
-    ))
+    end_of_value(offset, length)?;
+    let mut attribute = DicomAttribute::new(group, element, vr, offset, length, length, tag);
+    attribute.private_creator = private_creator;
+    attribute.character_set = character_set;
+    Ok(attribute)
   }
 
-  fn is_supported_type(&self) -> Result<String, DicomError> {
-    // Only supporting little-endian explicit VR for now.
+  // Returns the value of the Private Creator attribute reserving the block of a
+  // private attribute. The top level attributes (`creators` is None) look it up
+  // in the dataset.
+  fn get_private_creator(
+    &self,
+    group: u16,
+    element: u16,
+    creators: Option<&[PrivateCreator]>,
+  ) -> Option<String> {
+    let creator_element = private_creator_element(group, element)?;
+    match creators {
+      Some(creators) => creators
+        .iter()
+        .find(|creator| (creator.0, creator.1) == (group, creator_element))
+        .map(|creator| creator.2.clone()),
+      None => {
+        let offset = self.find_attribute(&private_creator_tag(group, creator_element))?;
+        self.read_private_creator(&self.next_attribute(offset).ok()?)
+      }
+    }
+  }
+
+  // The value of a Private Creator attribute is read as is, without going
+  // through DicomValue which needs the character set (and the index).
+  fn read_private_creator(&self, attribute: &DicomAttribute) -> Option<String> {
+    let value = self
+      .buffer
+      .read_bytes(attribute.data_offset, attribute.data_length)
+      .ok()?;
+    Some(
+      from_utf8(&value)
+        .ok()?
+        .trim_matches([' ', '\0'])
+        .to_string(),
+    )
+  }
+
+  // The character set declared by a SpecificCharacterSet attribute. Like the
+  // Private Creators, it is read as is as it is needed to decode the values.
+  fn read_character_set(&self, attribute: &DicomAttribute) -> CharacterSet {
+    let value = self
+      .buffer
+      .read_bytes(attribute.data_offset, attribute.data_length)
+      .ok();
+    let terms = value
+      .as_deref()
+      .and_then(|value| from_utf8(value).ok())
+      .unwrap_or_default()
+      .split('\\')
+      .collect::<Vec<&str>>();
+    CharacterSet::from_terms(&terms)
+  }
+
+  fn is_supported_type(&self) -> Result<TransferSyntax, DicomError> {
     if let Some(transfer_syntax_uid_field) = self.get_value(&0x00020010.try_into().unwrap())? {
       match transfer_syntax_uid_field {
         DicomValue::UI(transfer_syntax_uid) => {
-          if !vec![
-            // "1.2.840.10008.1.2",      // Implicit VR Little Endian: Default Transfer Syntax for DICOM
-            "1.2.840.10008.1.2.1.99", // Deflated Explicit VR Little Endian
-            "1.2.840.10008.1.2.2",    // Explicit VR Big Endian
-          ]
-          .contains(&transfer_syntax_uid.as_str())
-          {
-            Ok(transfer_syntax_uid)
-          } else {
-            Err(DicomError::new(&format!(
-              "Unsupported Transfer Syntax UID: {} ({})",
-              transfer_syntax_uid,
-              get_transfer_syntax_uid_label(&transfer_syntax_uid)
-                .unwrap_or("Unknown transfer syntax uid")
-            )))
-          }
+          TransferSyntax::try_from(transfer_syntax_uid.as_str())
         }
-        _ => Err(DicomError::new("Unexpected type")),
+        _ => Err(DicomError::with_kind(
+          DicomErrorKind::InvalidValue,
+          "Unexpected type",
+        )),
       }
     } else {
-      Err(DicomError::new("Transfer Syntax UID not found"))
-    }
-  }
-}
-
-fn get_transfer_syntax_uid_label(transfer_syntax_uid: &str) -> Result<&str, DicomError> {
-  match transfer_syntax_uid {
-    "1.2.840.10008.1.2" => Ok("Implicit VR Little Endian: Default Transfer Syntax for DICOM"),
-    "1.2.840.10008.1.2.1" => Ok("Explicit VR Little Endian"),
-    "1.2.840.10008.1.2.1.99" => Ok("Deflated Explicit VR Little Endian"),
-    "1.2.840.10008.1.2.2" => Ok("Explicit VR Big Endian"),
-    "1.2.840.10008.1.2.4.50" => Ok("JPEG Baseline (Process 1)"),
-    "1.2.840.10008.1.2.4.51" => Ok("JPEG Baseline (Processes 2 & 4)"),
-    "1.2.840.10008.1.2.4.52" => Ok("JPEG Extended (Processes 3 & 5)"),
-    "1.2.840.10008.1.2.4.53" => Ok("JPEG Spectral Selection, Nonhierarchical (Processes 6 & 8)"),
-    "1.2.840.10008.1.2.4.54" => Ok("JPEG Spectral Selection, Nonhierarchical (Processes 7 & 9)"),
-    "1.2.840.10008.1.2.4.55" => Ok("JPEG Full Progression, Nonhierarchical (Processes 10 & 12)"),
-    "1.2.840.10008.1.2.4.56" => Ok("JPEG Full Progression, Nonhierarchical (Processes 11 & 13)"),
-    "1.2.840.10008.1.2.4.57" => Ok("JPEG Lossless, Nonhierarchical (Processes 14)"),
-    "1.2.840.10008.1.2.4.58" => Ok("JPEG Lossless, Nonhierarchical (Processes 15)"),
-    "1.2.840.10008.1.2.4.59" => Ok("JPEG Extended, Hierarchical (Processes 16 & 18)"),
-    "1.2.840.10008.1.2.4.60" => Ok("JPEG Extended, Hierarchical (Processes 17 & 19)"),
-    "1.2.840.10008.1.2.4.61" => Ok("JPEG Spectral Selection, Hierarchical (Processes 20 & 22)"),
-    "1.2.840.10008.1.2.4.62" => Ok("JPEG Spectral Selection, Hierarchical (Processes 21 & 23)"),
-    "1.2.840.10008.1.2.4.63" => Ok("JPEG Full Progression, Hierarchical (Processes 24 & 26)"),
-    "1.2.840.10008.1.2.4.64" => Ok("JPEG Full Progression, Hierarchical (Processes 25 & 27)"),
-    "1.2.840.10008.1.2.4.65" => Ok("JPEG Lossless, Nonhierarchical (Process 28)"),
-    "1.2.840.10008.1.2.4.66" => Ok("JPEG Lossless, Nonhierarchical (Process 29)"),
-    "1.2.840.10008.1.2.4.70" => Ok(
-      "JPEG Lossless, Nonhierarchical, First- Order Prediction (Processes 14 [Selection Value 1])",
-    ),
-    "1.2.840.10008.1.2.4.80" => Ok("JPEG-LS Lossless Image Compression"),
-    "1.2.840.10008.1.2.4.81" => Ok("JPEG-LS Lossy (Near- Lossless) Image Compression"),
-    "1.2.840.10008.1.2.4.90" => Ok("JPEG 2000 Image Compression (Lossless Only)"),
-    "1.2.840.10008.1.2.4.91" => Ok("JPEG 2000 Image Compression"),
-    "1.2.840.10008.1.2.4.92" => {
-      Ok("JPEG 2000 Part 2 Multicomponent Image Compression (Lossless Only)")
-    }
-    "1.2.840.10008.1.2.4.93" => Ok("JPEG 2000 Part 2 Multicomponent Image Compression"),
-    "1.2.840.10008.1.2.4.94" => Ok("JPIP Referenced"),
-    "1.2.840.10008.1.2.4.95" => Ok("JPIP Referenced Deflate"),
-    "1.2.840.10008.1.2.5" => Ok("RLE Lossless"),
-    "1.2.840.10008.1.2.6.1" => Ok("RFC 2557 MIME Encapsulation"),
-    "1.2.840.10008.1.2.4.100" => Ok("MPEG2 Main Profile Main Level"),
-    "1.2.840.10008.1.2.4.102" => Ok("MPEG-4 AVC/H.264 High Profile / Level 4.1"),
-    "1.2.840.10008.1.2.4.103" => Ok("MPEG-4 AVC/H.264 BD-compatible High Profile / Level 4.1"),
-    _ => Err(DicomError::new(&format!(
-      "Unknown transfer_syntax_uid: {}",
-      transfer_syntax_uid
-    ))),
+      Err(DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        "Transfer Syntax UID not found",
+      ))
+    }
   }
 }
 
+// Offset of the end of a value. The length is read from the file, it might not
+// fit in the address space on 32 bits architectures (wasm).
+fn end_of_value(offset: usize, length: usize) -> Result<usize, DicomError> {
+  offset.checked_add(length).ok_or_else(|| {
+    DicomError::with_kind(
+      DicomErrorKind::Truncated,
+      &format!("Attribute length {} goes past the end of the file", length),
+    )
+    .at(offset)
+  })
+}
+
 pub struct InstanceIter<'a> {
-  instance: &'a Instance,
+  instance: &'a Instance<'a>,
   offset: usize,
 }
 
 impl<'a> InstanceIter<'a> {
-  fn new(instance: &'a Instance) -> Self {
-    // TODO: Deal with DICOM with broken headers
+  fn new(instance: &'a Instance<'a>) -> Self {
     InstanceIter {
       instance,
-      offset: 128 + "DICM".len(),
+      offset: instance.start_offset,
     }
   }
 }
 
 impl<'a> Iterator for InstanceIter<'a> {
-  type Item = Result<DicomAttribute<'a>, DicomError>;
+  type Item = Result<DicomAttribute, DicomError>;
 
   fn next(&mut self) -> core::option::Option<<Self as Iterator>::Item> {
-    if self.offset < self.instance.buffer.len() {
-      match self.instance.next_attribute(self.offset) {
+    match self.instance.is_end_of_dataset(self.offset) {
+      Ok(true) => None,
+      Ok(false) => match self.instance.next_attribute(self.offset) {
         Ok(attribute) => {
           self.offset = attribute.data_offset + attribute.data_length;
           Some(Ok(attribute))
         }
         Err(e) => Some(Err(e)),
+      },
+      Err(e) => Some(Err(e)),
+    }
+  }
+}
+
+/**
+ * Event produced by the depth-first walk of an instance (see Instance::walk).
+ * Sequences and items are given with all their subattributes, which are
+ * also yielded one by one by the following events.
+ */
+#[derive(Debug, Clone)]
+pub enum WalkEvent {
+  EnterSequence(DicomAttribute),
+  // The sequence without its subattributes
+  LeaveSequence(DicomAttribute),
+  EnterItem(DicomAttribute),
+  // The item without its subattributes
+  LeaveItem(DicomAttribute),
+  Attribute(DicomAttribute),
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkStep {
+  pub event: WalkEvent,
+  // 0 for the top level attributes. Items are one level deeper than their
+  // sequence and the attributes of an item one level deeper than the item.
+  pub depth: usize,
+  // Path to the attribute. For items, the last component is the sequence with
+  // the index of the item.
+  pub path: TagPath,
+}
+
+// Sequence or item being walked through
+struct WalkFrame {
+  // The sequence or item without its subattributes
+  attribute: DicomAttribute,
+  subattributes: vec::IntoIter<DicomAttribute>,
+  path: Vec<TagPathComponent>,
+  depth: usize,
+  // Number of items already walked through in a sequence
+  items: usize,
+}
+
+pub struct InstanceWalker<'a> {
+  iter: InstanceIter<'a>,
+  stack: Vec<WalkFrame>,
+  done: bool,
+}
+
+impl<'a> InstanceWalker<'a> {
+  fn new(instance: &'a Instance<'a>) -> Self {
+    InstanceWalker {
+      iter: InstanceIter::new(instance),
+      stack: vec![],
+      done: false,
+    }
+  }
+
+  // Produces the event of an attribute and starts walking through it if it
+  // is a sequence or an item.
+  fn enter(
+    &mut self,
+    attribute: DicomAttribute,
+    path: Vec<TagPathComponent>,
+    depth: usize,
+  ) -> WalkStep {
+    let is_item = attribute.group == Item.group && attribute.element == Item.element;
+    if !is_item && attribute.vr != ValueRepresentation::SQ {
+      return WalkStep {
+        event: WalkEvent::Attribute(attribute),
+        depth,
+        path: TagPath { components: path },
+      };
+    }
+    let mut header = attribute.clone();
+    let subattributes = core::mem::take(&mut header.subattributes);
+    self.stack.push(WalkFrame {
+      attribute: header,
+      subattributes: subattributes.into_iter(),
+      path: path.clone(),
+      depth,
+      items: 0,
+    });
+    WalkStep {
+      event: if is_item {
+        WalkEvent::EnterItem(attribute)
+      } else {
+        WalkEvent::EnterSequence(attribute)
+      },
+      depth,
+      path: TagPath { components: path },
+    }
+  }
+}
+
+impl<'a> Iterator for InstanceWalker<'a> {
+  type Item = Result<WalkStep, DicomError>;
+
+  fn next(&mut self) -> core::option::Option<<Self as Iterator>::Item> {
+    if self.done {
+      return None;
+    }
+    let Some(frame) = self.stack.last_mut() else {
+      // Top level
+      return match self.iter.next()? {
+        Ok(attribute) => {
+          let path = vec![TagPathComponent {
+            tag: attribute.tag.clone(),
+            item: None,
+          }];
+          Some(Ok(self.enter(attribute, path, 0)))
+        }
+        Err(e) => {
+          self.done = true;
+          Some(Err(e))
+        }
+      };
+    };
+    match frame.subattributes.next() {
+      Some(subattribute) => {
+        let mut path = frame.path.clone();
+        let depth = frame.depth + 1;
+        if subattribute.group == Item.group && subattribute.element == Item.element {
+          // An item of a sequence
+          if let Some(sequence) = path.last_mut() {
+            sequence.item = Some(frame.items);
+          }
+          frame.items += 1;
+        } else {
+          // An attribute of an item
+          path.push(TagPathComponent {
+            tag: subattribute.tag.clone(),
+            item: None,
+          });
+        }
+        Some(Ok(self.enter(subattribute, path, depth)))
+      }
+      None => {
+        let frame = self.stack.pop()?;
+        let is_item =
+          frame.attribute.group == Item.group && frame.attribute.element == Item.element;
+        Some(Ok(WalkStep {
+          event: if is_item {
+            WalkEvent::LeaveItem(frame.attribute)
+          } else {
+            WalkEvent::LeaveSequence(frame.attribute)
+          },
+          depth: frame.depth,
+          path: TagPath {
+            components: frame.path,
+          },
+        }))
       }
-    } else {
-      None
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dicom_tags::{Modality, PixelSpacing, Rows};
+
+  #[test]
+  fn big_endian_numbers() {
+    assert_eq!(read_u16(&[0x12, 0x34], 0, ByteOrder::BigEndian), 0x1234);
+    assert_eq!(read_u16(&[0x12, 0x34], 0, ByteOrder::LittleEndian), 0x3412);
+    assert_eq!(
+      decode_numbers::<u32>(
+        &[0, 0, 1, 0, 0xFF, 0xFF, 0xFF, 0xFE, 0xAA],
+        ByteOrder::BigEndian
+      ),
+      vec![256, 0xFFFFFFFE]
+    );
+    assert_eq!(
+      decode_numbers::<i16>(&[0xFF, 0xF4], ByteOrder::BigEndian),
+      vec![-12]
+    );
+    assert_eq!(
+      decode_numbers::<f32>(&[0x3F, 0x00, 0x00, 0x00], ByteOrder::BigEndian),
+      vec![0.5]
+    );
+    assert_eq!(
+      decode_numbers::<f64>(&[0xC0, 0x02, 0, 0, 0, 0, 0, 0], ByteOrder::BigEndian),
+      vec![-2.25]
+    );
+  }
+
+  #[test]
+  fn foreign_byte_order_is_copied() {
+    let foreign = match ByteOrder::native() {
+      ByteOrder::LittleEndian => ByteOrder::BigEndian,
+      ByteOrder::BigEndian => ByteOrder::LittleEndian,
+    };
+    // On the heap, so that the bytes are aligned for u16
+    let bytes = 0x0102u16.to_ne_bytes().to_vec();
+    assert!(matches!(
+      to_numbers::<u16>(&bytes, ByteOrder::native()),
+      Cow::Borrowed([0x0102])
+    ));
+    assert!(matches!(
+      to_numbers::<u16>(&bytes, foreign),
+      Cow::Owned(numbers) if numbers == [0x0201]
+    ));
+  }
+
+  #[test]
+  fn explicit_big_endian_dataset() {
+    // (0008,1032) ProcedureCodeSequence with an item holding (0008,0100) CodeValue,
+    // then (0028,0010) Rows and (0028,0030) PixelSpacing
+    let buffer = b"\x00\x08\x10\x32SQ\x00\x00\xFF\xFF\xFF\xFF\
+      \xFF\xFE\xE0\x00\x00\x00\x00\x0A\x00\x08\x01\x00SH\x00\x02C1\
+      \xFF\xFE\xE0\xDD\x00\x00\x00\x00\
+      \x00\x28\x00\x10US\x00\x02\x01\x00\
+      \x00\x28\x00\x30DS\x00\x080.5\\0.25";
+    let instance = Instance::from_dataset(buffer, Some("1.2.840.10008.1.2.2")).unwrap();
+    assert_eq!(
+      instance.get_value(&Rows).unwrap(),
+      Some(DicomValue::US(Cow::Owned(vec![256])))
+    );
+    assert_eq!(
+      instance.get_f64s(&PixelSpacing).unwrap(),
+      Some(vec![0.5, 0.25])
+    );
+    assert_eq!(
+      instance
+        .get_value_by_path(&TagPath::try_from("ProcedureCodeSequence[0].CodeValue").unwrap())
+        .unwrap(),
+      Some(DicomValue::SH(vec!["C1".to_string()]))
+    );
+    // The byte order is found without the transfer syntax
+    let instance = Instance::from_dataset(buffer, None).unwrap();
+    assert_eq!(
+      instance.transfer_syntax,
+      transfer_syntax::ExplicitVRBigEndian
+    );
+    assert_eq!(instance.get_i64(&Rows).unwrap(), Some(256));
+  }
+
+  // A Part 10 file whose file meta information only holds the transfer syntax
+  fn part10(transfer_syntax_uid: &[u8], dataset: &[u8]) -> Vec<u8> {
+    let mut buffer = vec![0; 128];
+    buffer.extend_from_slice(b"DICM\x02\x00\x10\x00UI");
+    buffer.extend_from_slice(&(transfer_syntax_uid.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(transfer_syntax_uid);
+    buffer.extend_from_slice(dataset);
+    buffer
+  }
+
+  #[test]
+  fn deflated_dataset() {
+    // (0008,0060) Modality then (0028,0010) Rows
+    let dataset = b"\x08\x00\x60\x00CS\x02\x00CT\x28\x00\x10\x00US\x02\x00\x00\x01";
+    let compressed = miniz_oxide::deflate::compress_to_vec(dataset, 6);
+    let buffer = part10(b"1.2.840.10008.1.2.1.99", &compressed);
+    let instance = Instance::from(&buffer[..]).unwrap();
+    assert_eq!(
+      instance.transfer_syntax,
+      transfer_syntax::DeflatedExplicitVRLittleEndian
+    );
+    assert_eq!(instance.get_i64(&Rows).unwrap(), Some(256));
+    assert_eq!(
+      instance.get_value(&Modality).unwrap(),
+      Some(DicomValue::CS(vec!["CT".to_string()]))
+    );
+    // The file meta information is left as is: the dataset starts right after
+    // the group 0002, before the compressed bytes
+    assert_eq!(instance.get_dataset_offset().unwrap(), 128 + 4 + 8 + 22);
+    assert_eq!(
+      instance.get_dataset_offset().unwrap(),
+      buffer.len() - compressed.len()
+    );
+  }
+
+  #[test]
+  fn invalid_deflated_dataset() {
+    let buffer = part10(b"1.2.840.10008.1.2.1.99", b"\xFF\xFF\xFF\xFF");
+    let error = Instance::from(&buffer[..]).unwrap_err();
+    assert_eq!(error.kind, DicomErrorKind::InvalidFormat);
+    assert_eq!(error.offset, Some(128 + 4 + 8 + 22));
+  }
+}
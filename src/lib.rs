@@ -26,12 +26,22 @@
 extern crate alloc; // We need this in order to use alloc modules
 
 pub mod allocator;
+pub mod character_set;
 pub mod config_file;
+pub mod date_time;
 pub mod dicom_tags;
+pub mod dictionary;
 pub mod error;
 pub mod instance;
 pub mod misc;
+pub mod person_name;
+pub mod private_tags;
+pub mod storage;
 pub mod tags;
+pub mod transfer_syntax;
+pub mod uids;
+pub mod value_multiplicity;
+pub mod value_representation;
 // Only include the wasm module when compiling to wasm
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
@@ -21,22 +21,219 @@
 use alloc::vec::Vec;
 use core::str::from_utf8;
 
+use crate::instance::ByteOrder;
+use crate::tags::Tag;
+use crate::transfer_syntax::{self, TransferSyntax};
+use crate::value_representation::ValueRepresentation;
+
 pub fn has_dicom_header(buffer: &[u8]) -> bool {
   buffer.len() > 0x84 && from_utf8(&buffer[0x80..0x80 + 4]) == Ok("DICM")
 }
 
+// Number of bytes at the beginning of a file used to tell whether it is a
+// DICOM file and how its dataset is encoded.
+pub const DICOM_PROBE_SIZE: usize = 4096;
+
+// Maximum depth of the sequences and items nested in one another. Parsing them
+// is recursive, deeper datasets are rejected instead of overflowing the stack.
+pub const MAX_NESTING_DEPTH: usize = 128;
+
 /**
  * Check if a file is a DICOM file.
+ * Only the beginning of the file is read.
  * Imperfect heuristic for now.
  */
 #[cfg(not(target_arch = "wasm32"))]
 pub fn is_dicom_file(file_path: &str) -> bool {
-  match std::fs::read(file_path) {
-    Ok(buf) => is_dicom(&buf),
+  use std::io::Read;
+  let mut buffer = vec![];
+  match std::fs::File::open(file_path)
+    .and_then(|f| f.take(DICOM_PROBE_SIZE as u64).read_to_end(&mut buffer))
+  {
+    Ok(_) => is_dicom(&buffer),
     Err(_) => false,
   }
 }
 
 pub fn is_dicom(buffer: &Vec<u8>) -> bool {
-  has_dicom_header(buffer)
+  has_dicom_header(buffer) || guess_transfer_syntax(buffer).is_some()
+}
+
+/**
+ * Guess the transfer syntax of a dataset not preceded by the Part 10 preamble
+ * (e.g. ACR-NEMA files) by looking at its first attributes.
+ * Returns None if the buffer does not look like a dataset: it must start with
+ * two well formed attributes (valid VR if explicit, lengths within the buffer,
+ * ascending tags), the first one being in a non-zero group or in the
+ * dictionary.
+ * Imperfect heuristic, as there is nothing to rely on in these files.
+ */
+pub fn guess_transfer_syntax(buffer: &[u8]) -> Option<TransferSyntax> {
+  if buffer.len() < 8 {
+    return None;
+  }
+  // A dataset starts with one of the lowest groups (command, file meta
+  // information, identifying), which tells us the byte order.
+  let group_le = u16::from_le_bytes([buffer[0], buffer[1]]);
+  let group_be = u16::from_be_bytes([buffer[0], buffer[1]]);
+  let little_endian = group_le <= group_be;
+  if group_le.min(group_be) > 0x0008 {
+    return None;
+  }
+  let explicit = ValueRepresentation::try_from(&buffer[4..6]).is_ok();
+  let transfer_syntax = match (explicit, little_endian) {
+    (true, true) => transfer_syntax::ExplicitVRLittleEndian,
+    (true, false) => transfer_syntax::ExplicitVRBigEndian,
+    (false, true) => transfer_syntax::ImplicitVRLittleEndian,
+    // There is no such thing as Implicit VR Big Endian
+    (false, false) => return None,
+  };
+  starts_with_attributes(buffer, &transfer_syntax).then_some(transfer_syntax)
+}
+
+// Whether the buffer starts with two consecutive well formed attributes
+fn starts_with_attributes(buffer: &[u8], transfer_syntax: &TransferSyntax) -> bool {
+  let Some((tag, value_offset, next_offset)) = read_attribute(buffer, 0, transfer_syntax) else {
+    return false;
+  };
+  // A buffer of zeros starts with (0000,0000) and a null length
+  if tag >> 16 == 0 && Tag::try_from(tag).is_err() {
+    return false;
+  }
+  match next_offset {
+    // Undefined length: the first item of the sequence or its end follows
+    None => matches!(
+      buffer
+        .get(value_offset..value_offset + 4)
+        .map(|header| read_tag(header, transfer_syntax.byte_order)),
+      Some(0xFFFEE000 | 0xFFFEE0DD)
+    ),
+    Some(next_offset) => match read_attribute(buffer, next_offset, transfer_syntax) {
+      Some((next_tag, _, _)) => next_tag > tag,
+      None => false,
+    },
+  }
+}
+
+// The tag of the attribute at this offset, the offset of its value and the
+// offset of the attribute following it (None for an undefined length). None if
+// the attribute is not well formed.
+fn read_attribute(
+  buffer: &[u8],
+  offset: usize,
+  transfer_syntax: &TransferSyntax,
+) -> Option<(u32, usize, Option<usize>)> {
+  let header = buffer.get(offset..offset + 8)?;
+  let byte_order = transfer_syntax.byte_order;
+  let (header_length, length) = if transfer_syntax.implicit {
+    (8, read_u32(&header[4..8], byte_order))
+  } else {
+    let vr = ValueRepresentation::try_from(&header[4..6]).ok()?;
+    if vr.has_long_length() {
+      (
+        12,
+        read_u32(buffer.get(offset + 8..offset + 12)?, byte_order),
+      )
+    } else {
+      let length = match byte_order {
+        ByteOrder::LittleEndian => u16::from_le_bytes([header[6], header[7]]),
+        ByteOrder::BigEndian => u16::from_be_bytes([header[6], header[7]]),
+      };
+      (8, length as u32)
+    }
+  };
+  let tag = read_tag(header, byte_order);
+  if length == 0xFFFFFFFF {
+    return Some((tag, offset + header_length, None));
+  }
+  let next_offset = offset + header_length + length as usize;
+  // The buffer may only be the beginning of the file, cutting the value of
+  // the second attribute
+  if next_offset <= buffer.len() || (offset > 0 && buffer.len() >= DICOM_PROBE_SIZE) {
+    Some((tag, offset + header_length, Some(next_offset)))
+  } else {
+    None
+  }
+}
+
+fn read_tag(header: &[u8], byte_order: ByteOrder) -> u32 {
+  let (group, element) = match byte_order {
+    ByteOrder::LittleEndian => (
+      u16::from_le_bytes([header[0], header[1]]),
+      u16::from_le_bytes([header[2], header[3]]),
+    ),
+    ByteOrder::BigEndian => (
+      u16::from_be_bytes([header[0], header[1]]),
+      u16::from_be_bytes([header[2], header[3]]),
+    ),
+  };
+  (group as u32) << 16 | element as u32
+}
+
+fn read_u32(bytes: &[u8], byte_order: ByteOrder) -> u32 {
+  let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+  match byte_order {
+    ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
+    ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn zeros_are_not_a_dataset() {
+    assert_eq!(guess_transfer_syntax(&[0; 8]), None);
+    assert_eq!(guess_transfer_syntax(&[0; 16]), None);
+    assert_eq!(guess_transfer_syntax(&[0; 256]), None);
+    assert!(!is_dicom(&vec![0; 256]));
+  }
+
+  #[test]
+  fn implicit_little_endian() {
+    // (0008,0020) StudyDate then (0008,0060) Modality
+    let buffer = b"\x08\x00\x20\x00\x08\x00\x00\x0020230102\x08\x00\x60\x00\x02\x00\x00\x00CT";
+    assert_eq!(
+      guess_transfer_syntax(buffer),
+      Some(transfer_syntax::ImplicitVRLittleEndian)
+    );
+    // A single attribute is not enough
+    assert_eq!(guess_transfer_syntax(&buffer[..16]), None);
+    // Nor are descending tags
+    let buffer = b"\x08\x00\x60\x00\x02\x00\x00\x00CT\x08\x00\x20\x00\x08\x00\x00\x0020230102";
+    assert_eq!(guess_transfer_syntax(buffer), None);
+    // The length of the first attribute goes past the buffer
+    let buffer = b"\x08\x00\x20\x00\x08\x01\x00\x0020230102\x08\x00\x60\x00\x02\x00\x00\x00CT";
+    assert_eq!(guess_transfer_syntax(buffer), None);
+  }
+
+  #[test]
+  fn explicit_vr() {
+    let buffer = b"\x08\x00\x20\x00DA\x08\x0020230102\x08\x00\x60\x00CS\x02\x00CT";
+    assert_eq!(
+      guess_transfer_syntax(buffer),
+      Some(transfer_syntax::ExplicitVRLittleEndian)
+    );
+    let buffer = b"\x00\x08\x00\x20DA\x00\x0820230102\x00\x08\x00\x60CS\x00\x02CT";
+    assert_eq!(
+      guess_transfer_syntax(buffer),
+      Some(transfer_syntax::ExplicitVRBigEndian)
+    );
+    // The second attribute has no valid VR
+    let buffer = b"\x08\x00\x20\x00DA\x08\x0020230102\x08\x00\x60\x00cs\x02\x00CT";
+    assert_eq!(guess_transfer_syntax(buffer), None);
+  }
+
+  #[test]
+  fn undefined_length_sequence() {
+    // (0008,1110) ReferencedStudySequence followed by its first item
+    let buffer = b"\x08\x00\x10\x11SQ\x00\x00\xFF\xFF\xFF\xFF\xFE\xFF\x00\xE0\x00\x00\x00\x00";
+    assert_eq!(
+      guess_transfer_syntax(buffer),
+      Some(transfer_syntax::ExplicitVRLittleEndian)
+    );
+    let buffer = b"\x08\x00\x10\x11SQ\x00\x00\xFF\xFF\xFF\xFF\x08\x00\x20\x00\x00\x00\x00\x00";
+    assert_eq!(guess_transfer_syntax(buffer), None);
+  }
 }
@@ -0,0 +1,216 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_6.2.html#sect_6.2.1
+
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+/**
+ * One component group of a person name, e.g. "Adams^John Robert Quincy^^Rev.^B.A. M.Div."
+ * Missing components are empty.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PersonNameComponents {
+  pub family_name: String,
+  pub given_name: String,
+  pub middle_name: String,
+  pub name_prefix: String,
+  pub name_suffix: String,
+}
+
+/**
+ * A person name (PN) value made of up to three component groups: the
+ * alphabetic, ideographic and phonetic representations of the name,
+ * e.g. "Yamada^Tarou=山田^太郎=やまだ^たろう".
+ * Missing or empty component groups are None.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PersonName {
+  pub alphabetic: Option<PersonNameComponents>,
+  pub ideographic: Option<PersonNameComponents>,
+  pub phonetic: Option<PersonNameComponents>,
+}
+
+impl PersonNameComponents {
+  /**
+   * Split a component group on '^'. Components past the fifth one are
+   * appended to the suffix.
+   */
+  pub fn parse(group: &str) -> PersonNameComponents {
+    let mut components = group
+      .splitn(5, '^')
+      .map(|component| component.trim().to_string());
+    PersonNameComponents {
+      family_name: components.next().unwrap_or_default(),
+      given_name: components.next().unwrap_or_default(),
+      middle_name: components.next().unwrap_or_default(),
+      name_prefix: components.next().unwrap_or_default(),
+      name_suffix: components.next().unwrap_or_default(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.family_name.is_empty()
+      && self.given_name.is_empty()
+      && self.middle_name.is_empty()
+      && self.name_prefix.is_empty()
+      && self.name_suffix.is_empty()
+  }
+}
+
+// Formats the component group as in DICOM, without the trailing empty components
+impl fmt::Display for PersonNameComponents {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let components = [
+      self.family_name.as_str(),
+      self.given_name.as_str(),
+      self.middle_name.as_str(),
+      self.name_prefix.as_str(),
+      self.name_suffix.as_str(),
+    ];
+    let length = components
+      .iter()
+      .rposition(|component| !component.is_empty())
+      .map_or(0, |position| position + 1);
+    write!(f, "{}", components[..length].join("^"))
+  }
+}
+
+impl PersonName {
+  /**
+   * Split a person name on '=' into its component groups, then each group on
+   * '^' into its components.
+   */
+  pub fn parse(value: &str) -> PersonName {
+    let mut groups = value.splitn(3, '=').map(|group| {
+      let components = PersonNameComponents::parse(group);
+      if components.is_empty() {
+        None
+      } else {
+        Some(components)
+      }
+    });
+    PersonName {
+      alphabetic: groups.next().flatten(),
+      ideographic: groups.next().flatten(),
+      phonetic: groups.next().flatten(),
+    }
+  }
+}
+
+// Formats the person name as in DICOM, without the trailing empty component groups
+impl fmt::Display for PersonName {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let groups = [&self.alphabetic, &self.ideographic, &self.phonetic]
+      .iter()
+      .map(|group| group.as_ref().map(|g| g.to_string()).unwrap_or_default())
+      .collect::<Vec<String>>();
+    let length = groups
+      .iter()
+      .rposition(|group| !group.is_empty())
+      .map_or(0, |position| position + 1);
+    write!(f, "{}", groups[..length].join("="))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn components(components: [&str; 5]) -> PersonNameComponents {
+    PersonNameComponents {
+      family_name: components[0].to_string(),
+      given_name: components[1].to_string(),
+      middle_name: components[2].to_string(),
+      name_prefix: components[3].to_string(),
+      name_suffix: components[4].to_string(),
+    }
+  }
+
+  #[test]
+  fn components_of_a_group() {
+    assert_eq!(
+      PersonNameComponents::parse("Adams^John Robert Quincy^^Rev.^B.A. M.Div."),
+      components(["Adams", "John Robert Quincy", "", "Rev.", "B.A. M.Div."])
+    );
+    assert_eq!(
+      PersonNameComponents::parse("Morrison-Jones^Susan^^^Ph.D., Chief Executive Officer"),
+      components([
+        "Morrison-Jones",
+        "Susan",
+        "",
+        "",
+        "Ph.D., Chief Executive Officer"
+      ])
+    );
+    // Padding is trimmed, extra components end up in the suffix
+    assert_eq!(
+      PersonNameComponents::parse(" Doe ^John ^A^Dr^Jr^III"),
+      components(["Doe", "John", "A", "Dr", "Jr^III"])
+    );
+    assert!(PersonNameComponents::parse("^^ ^").is_empty());
+  }
+
+  #[test]
+  fn component_groups() {
+    let name = PersonName::parse("Yamada^Tarou=山田^太郎=やまだ^たろう");
+    assert_eq!(
+      name.alphabetic,
+      Some(components(["Yamada", "Tarou", "", "", ""]))
+    );
+    assert_eq!(
+      name.ideographic,
+      Some(components(["山田", "太郎", "", "", ""]))
+    );
+    assert_eq!(
+      name.phonetic,
+      Some(components(["やまだ", "たろう", "", "", ""]))
+    );
+    // Only the ideographic and phonetic representations
+    let name = PersonName::parse("=Wang^XiaoDong=");
+    assert_eq!(name.alphabetic, None);
+    assert_eq!(
+      name.ideographic,
+      Some(components(["Wang", "XiaoDong", "", "", ""]))
+    );
+    assert_eq!(name.phonetic, None);
+    assert_eq!(PersonName::parse(""), PersonName::default());
+  }
+
+  #[test]
+  fn trailing_delimiters_are_not_formatted() {
+    for (value, formatted) in [
+      ("Doe^John", "Doe^John"),
+      ("Doe^John^^^", "Doe^John"),
+      ("Doe^John==", "Doe^John"),
+      ("=Wang^XiaoDong=", "=Wang^XiaoDong"),
+      ("^^^^", ""),
+      (
+        "Yamada^Tarou=山田^太郎=やまだ^たろう",
+        "Yamada^Tarou=山田^太郎=やまだ^たろう",
+      ),
+    ] {
+      assert_eq!(PersonName::parse(value).to_string(), formatted, "{}", value);
+    }
+  }
+}
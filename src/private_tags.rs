@@ -0,0 +1,481 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_7.8.html
+//
+// Private attributes live in the odd groups. A Private Creator attribute
+// (gggg,00xx) reserves the block of elements (gggg,xx00-xxFF) to the vendor
+// named by its value. The meaning of (gggg,xxee) therefore depends on the
+// creator of block xx and not only on its group and element.
+
+use core::convert::TryFrom;
+
+use crate::dictionary;
+use crate::tags::Tag;
+use crate::value_multiplicity::ValueMultiplicity;
+use crate::value_representation::ValueRepresentation as VR;
+
+/**
+ * The attributes a vendor defines in the blocks it reserves in a group.
+ */
+#[derive(Debug)]
+pub struct PrivateDictionary {
+  pub creator: &'static str,
+  pub group: u16,
+  pub tags: &'static [PrivateTag],
+}
+
+/**
+ * A private attribute, identified by the last byte of its element.
+ */
+#[derive(Debug)]
+pub struct PrivateTag {
+  pub element: u8,
+  pub name: &'static str,
+  pub vr: VR,
+  pub vm: ValueMultiplicity,
+  pub description: &'static str,
+}
+
+// A VM of 0 stands for 1-n
+const fn tag(
+  element: u8,
+  name: &'static str,
+  vr: VR,
+  vm: u16,
+  description: &'static str,
+) -> PrivateTag {
+  PrivateTag {
+    element,
+    name,
+    vr,
+    vm: if vm == 0 {
+      ValueMultiplicity::at_least(1)
+    } else {
+      ValueMultiplicity::exactly(vm)
+    },
+    description,
+  }
+}
+
+// Sources: the conformance statements of the vendors, and the private
+// dictionaries of DCMTK and GDCM.
+#[rustfmt::skip]
+pub const PRIVATE_DICTIONARIES: &[PrivateDictionary] = &[
+  PrivateDictionary {
+    creator: "SIEMENS CSA HEADER",
+    group: 0x0029,
+    tags: &[
+      tag(0x08, "CSAImageHeaderType", VR::CS, 1, "CSA Image Header Type"),
+      tag(0x09, "CSAImageHeaderVersion", VR::LO, 1, "CSA Image Header Version"),
+      tag(0x10, "CSAImageHeaderInfo", VR::OB, 1, "CSA Image Header Info"),
+      tag(0x18, "CSASeriesHeaderType", VR::CS, 1, "CSA Series Header Type"),
+      tag(0x19, "CSASeriesHeaderVersion", VR::LO, 1, "CSA Series Header Version"),
+      tag(0x20, "CSASeriesHeaderInfo", VR::OB, 1, "CSA Series Header Info"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "SIEMENS CSA NON-IMAGE",
+    group: 0x0029,
+    tags: &[
+      tag(0x08, "CSADataType", VR::CS, 1, "CSA Data Type"),
+      tag(0x09, "CSADataVersion", VR::LO, 1, "CSA Data Version"),
+      tag(0x10, "CSADataInfo", VR::OB, 1, "CSA Data Info"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "SIEMENS MEDCOM HEADER",
+    group: 0x0029,
+    tags: &[
+      tag(0x08, "MedComHeaderType", VR::CS, 1, "MedCom Header Type"),
+      tag(0x09, "MedComHeaderVersion", VR::LO, 1, "MedCom Header Version"),
+      tag(0x10, "MedComHeaderInfo", VR::OB, 1, "MedCom Header Info"),
+      tag(0x20, "MedComHistoryInformation", VR::OB, 1, "MedCom History Information"),
+      tag(0x31, "PMTFInformation1", VR::LO, 1, "PMTF Information 1"),
+      tag(0x32, "PMTFInformation2", VR::UL, 1, "PMTF Information 2"),
+      tag(0x33, "PMTFInformation3", VR::UL, 1, "PMTF Information 3"),
+      tag(0x34, "PMTFInformation4", VR::CS, 1, "PMTF Information 4"),
+      tag(0x35, "PMTFInformation5", VR::UL, 1, "PMTF Information 5"),
+      tag(0x40, "ApplicationHeaderSequence", VR::SQ, 1, "Application Header Sequence"),
+      tag(0x41, "ApplicationHeaderType", VR::CS, 1, "Application Header Type"),
+      tag(0x42, "ApplicationHeaderID", VR::LO, 1, "Application Header ID"),
+      tag(0x43, "ApplicationHeaderVersion", VR::LO, 1, "Application Header Version"),
+      tag(0x44, "ApplicationHeaderInfo", VR::OB, 1, "Application Header Info"),
+      tag(0x50, "WorkflowControlFlags", VR::LO, 8, "Workflow Control Flags"),
+      tag(0x51, "ArchiveManagementFlagKeepOnline", VR::CS, 1, "Archive Management Flag Keep Online"),
+      tag(0x52, "ArchiveManagementFlagDoNotArchive", VR::CS, 1, "Archive Management Flag Do Not Archive"),
+      tag(0x53, "ImageLocationStatus", VR::CS, 1, "Image Location Status"),
+      tag(0x54, "EstimatedRetrieveTime", VR::DS, 1, "Estimated Retrieve Time"),
+      tag(0x55, "DataSizeOfRetrievedImages", VR::DS, 1, "Data Size of Retrieved Images"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "SIEMENS MR HEADER",
+    group: 0x0019,
+    tags: &[
+      tag(0x08, "CSAImageHeaderType", VR::CS, 1, "CSA Image Header Type"),
+      tag(0x09, "CSAImageHeaderVersion", VR::LO, 1, "CSA Image Header Version"),
+      tag(0x0A, "NumberOfImagesInMosaic", VR::US, 1, "Number of Images in Mosaic"),
+      tag(0x0B, "SliceMeasurementDuration", VR::DS, 1, "Slice Measurement Duration"),
+      tag(0x0C, "BValue", VR::IS, 1, "B Value"),
+      tag(0x0D, "DiffusionDirectionality", VR::CS, 1, "Diffusion Directionality"),
+      tag(0x0E, "DiffusionGradientDirection", VR::FD, 3, "Diffusion Gradient Direction"),
+      tag(0x0F, "GradientMode", VR::SH, 1, "Gradient Mode"),
+      tag(0x11, "FlowCompensation", VR::SH, 1, "Flow Compensation"),
+      tag(0x12, "TablePositionOrigin", VR::SL, 3, "Table Position Origin"),
+      tag(0x13, "ImaAbsTablePosition", VR::SL, 3, "Ima Abs Table Position"),
+      tag(0x14, "ImaRelTablePosition", VR::IS, 3, "Ima Rel Table Position"),
+      tag(0x15, "SlicePositionPCS", VR::FD, 3, "Slice Position PCS"),
+      tag(0x16, "TimeAfterStart", VR::DS, 1, "Time After Start"),
+      tag(0x17, "SliceResolution", VR::DS, 1, "Slice Resolution"),
+      tag(0x18, "RealDwellTime", VR::IS, 1, "Real Dwell Time"),
+      tag(0x27, "BMatrix", VR::FD, 6, "B Matrix"),
+      tag(0x28, "BandwidthPerPixelPhaseEncode", VR::FD, 1, "Bandwidth per Pixel Phase Encode"),
+      tag(0x29, "MosaicRefAcqTimes", VR::FD, 0, "Mosaic Ref Acq Times"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "SIEMENS MR HEADER",
+    group: 0x0051,
+    tags: &[
+      tag(0x08, "CSAImageHeaderType", VR::CS, 1, "CSA Image Header Type"),
+      tag(0x09, "CSAImageHeaderVersion", VR::LO, 1, "CSA Image Header Version"),
+      tag(0x0A, "AcquisitionDuration", VR::LO, 1, "Acquisition Duration"),
+      tag(0x0B, "AcquisitionMatrixText", VR::SH, 1, "Acquisition Matrix Text"),
+      tag(0x0C, "FieldOfView", VR::LO, 1, "Field of View"),
+      tag(0x0D, "SlicePositionText", VR::SH, 1, "Slice Position Text"),
+      tag(0x0E, "SliceOrientation", VR::LO, 1, "Slice Orientation"),
+      tag(0x0F, "CoilString", VR::LO, 1, "Coil String"),
+      tag(0x11, "PATModeText", VR::LO, 1, "PAT Mode Text"),
+      tag(0x12, "TablePositionText", VR::SH, 1, "Table Position Text"),
+      tag(0x13, "PositivePCSDirections", VR::SH, 1, "Positive PCS Directions"),
+      tag(0x15, "ImageFilter", VR::SH, 1, "Image Filter"),
+      tag(0x16, "ImageTypeText", VR::LO, 1, "Image Type Text"),
+      tag(0x17, "SliceThicknessText", VR::SH, 1, "Slice Thickness Text"),
+      tag(0x19, "ScanOptionsAbbreviation", VR::LO, 1, "Scan Options Abbreviation"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "GEMS_IDEN_01",
+    group: 0x0009,
+    tags: &[
+      tag(0x01, "FullFidelity", VR::LO, 1, "Full Fidelity"),
+      tag(0x02, "SuiteID", VR::SH, 1, "Suite ID"),
+      tag(0x04, "ProductID", VR::SH, 1, "Product ID"),
+      tag(0x27, "ImageActualDate", VR::SL, 1, "Image Actual Date"),
+      tag(0x30, "ServiceID", VR::SH, 1, "Service ID"),
+      tag(0x31, "MobileLocationNumber", VR::SH, 1, "Mobile Location Number"),
+      tag(0xE3, "EquipmentUID", VR::UI, 1, "Equipment UID"),
+      tag(0xE6, "GenesisVersionNow", VR::SH, 1, "Genesis Version Now"),
+      tag(0xE7, "ExamRecordChecksum", VR::UL, 1, "Exam Record Checksum"),
+      tag(0xE9, "ActualSeriesDataTimeStamp", VR::SL, 1, "Actual Series Data Time Stamp"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "GEMS_ACQU_01",
+    group: 0x0019,
+    tags: &[
+      tag(0x0F, "HorizontalFrameOfReference", VR::DS, 1, "Horizontal Frame of Reference"),
+      tag(0x11, "SeriesContrast", VR::SS, 1, "Series Contrast"),
+      tag(0x12, "LastPseq", VR::SS, 1, "Last Pseq"),
+      tag(0x17, "SeriesPlane", VR::SS, 1, "Series Plane"),
+      tag(0x18, "FirstScanRAS", VR::LO, 1, "First Scan RAS"),
+      tag(0x19, "FirstScanLocation", VR::DS, 1, "First Scan Location"),
+      tag(0x1A, "LastScanRAS", VR::LO, 1, "Last Scan RAS"),
+      tag(0x1B, "LastScanLocation", VR::DS, 1, "Last Scan Location"),
+      tag(0x23, "TableSpeed", VR::DS, 1, "Table Speed"),
+      tag(0x24, "MidScanTime", VR::DS, 1, "Mid Scan Time"),
+      tag(0x25, "MidScanFlag", VR::SS, 1, "Mid Scan Flag"),
+      tag(0x27, "RotationSpeed", VR::DS, 1, "Rotation Speed"),
+      tag(0x9C, "PulseSequenceName", VR::LO, 1, "Pulse Sequence Name"),
+      tag(0x9E, "InternalPulseSequenceName", VR::LO, 1, "Internal Pulse Sequence Name"),
+      tag(0xA7, "UserData0", VR::DS, 1, "User Data 0"),
+      tag(0xA8, "UserData1", VR::DS, 1, "User Data 1"),
+      tag(0xA9, "UserData2", VR::DS, 1, "User Data 2"),
+      tag(0xAA, "UserData3", VR::DS, 1, "User Data 3"),
+      tag(0xAB, "UserData4", VR::DS, 1, "User Data 4"),
+      tag(0xBB, "UserData20", VR::DS, 1, "User Data 20"),
+      tag(0xBC, "UserData21", VR::DS, 1, "User Data 21"),
+      tag(0xBD, "UserData22", VR::DS, 1, "User Data 22"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "GEMS_RELA_01",
+    group: 0x0021,
+    tags: &[
+      tag(0x03, "SeriesFromWhichPrescribed", VR::SS, 1, "Series from which Prescribed"),
+      tag(0x05, "GenesisVersionNow", VR::SH, 1, "Genesis Version Now"),
+      tag(0x07, "SeriesRecordChecksum", VR::UL, 1, "Series Record Checksum"),
+      tag(0x18, "GenesisVersionNow", VR::SH, 1, "Genesis Version Now"),
+      tag(0x19, "AcqReconRecordChecksum", VR::UL, 1, "Acq Recon Record Checksum"),
+      tag(0x20, "TableStartLocation", VR::DS, 1, "Table Start Location"),
+      tag(0x35, "SeriesFromWhichPrescribed", VR::SS, 1, "Series from which Prescribed"),
+      tag(0x36, "ImageFromWhichPrescribed", VR::SS, 1, "Image from which Prescribed"),
+      tag(0x37, "ScreenFormat", VR::SS, 1, "Screen Format"),
+      tag(0x4A, "AnatomicalReferenceForScout", VR::LO, 1, "Anatomical Reference for Scout"),
+      tag(0x4E, "TubeFocalSpotPosition", VR::US, 1, "Tube Focal Spot Position"),
+      tag(0x4F, "LocationsInAcquisition", VR::SS, 1, "Locations in Acquisition"),
+      tag(0x50, "GraphicallyPrescribed", VR::SS, 1, "Graphically Prescribed"),
+      tag(0x51, "RotationFromSourceXRot", VR::DS, 1, "Rotation from Source x rot"),
+      tag(0x52, "RotationFromSourceYRot", VR::DS, 1, "Rotation from Source y rot"),
+      tag(0x53, "RotationFromSourceZRot", VR::DS, 1, "Rotation from Source z rot"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "GEMS_SERS_01",
+    group: 0x0025,
+    tags: &[
+      tag(0x06, "LastPulseSequenceUsed", VR::SS, 1, "Last Pulse Sequence Used"),
+      tag(0x07, "ImagesInSeries", VR::SL, 1, "Images in Series"),
+      tag(0x10, "LandmarkCounter", VR::SL, 1, "Landmark Counter"),
+      tag(0x11, "NumberOfAcquisitions", VR::SS, 1, "Number of Acquisitions"),
+      tag(0x14, "IndicatesNumberOfUpdatesToHeader", VR::SL, 1, "Indicates Number of Updates to Header"),
+      tag(0x17, "SeriesCompleteFlag", VR::SL, 1, "Series Complete Flag"),
+      tag(0x18, "NumberOfImagesArchived", VR::SL, 1, "Number of Images Archived"),
+      tag(0x19, "LastImageNumberUsed", VR::SL, 1, "Last Image Number Used"),
+      tag(0x1A, "PrimaryReceiverSuiteAndHost", VR::SH, 1, "Primary Receiver Suite and Host"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "GEMS_PARM_01",
+    group: 0x0043,
+    tags: &[
+      tag(0x01, "BitmapOfPrescanOptions", VR::SS, 1, "Bitmap of Prescan Options"),
+      tag(0x02, "GradientOffsetInX", VR::SS, 1, "Gradient Offset in X"),
+      tag(0x03, "GradientOffsetInY", VR::SS, 1, "Gradient Offset in Y"),
+      tag(0x04, "GradientOffsetInZ", VR::SS, 1, "Gradient Offset in Z"),
+      tag(0x06, "NumberOfEPIShots", VR::SS, 1, "Number of EPI Shots"),
+      tag(0x07, "ViewsPerSegment", VR::SS, 1, "Views per Segment"),
+      tag(0x08, "RespiratoryRateBpm", VR::SS, 1, "Respiratory Rate, bpm"),
+      tag(0x27, "ScanPitchRatio", VR::SH, 1, "Scan Pitch Ratio"),
+      tag(0x39, "SlopInteger6To9", VR::IS, 4, "Slop Integer 6 to 9"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "Philips Imaging DD 001",
+    group: 0x2001,
+    tags: &[
+      tag(0x01, "ChemicalShift", VR::FL, 1, "Chemical Shift"),
+      tag(0x02, "ChemicalShiftNumberMR", VR::IS, 1, "Chemical Shift Number MR"),
+      tag(0x03, "DiffusionBFactor", VR::FL, 1, "Diffusion B-Factor"),
+      tag(0x04, "DiffusionDirection", VR::CS, 1, "Diffusion Direction"),
+      tag(0x06, "ImageEnhanced", VR::CS, 1, "Image Enhanced"),
+      tag(0x07, "ImageTypeEDES", VR::CS, 1, "Image Type ED ES"),
+      tag(0x08, "PhaseNumber", VR::IS, 1, "Phase Number"),
+      tag(0x09, "ImagePrepulseDelay", VR::FL, 1, "Image Prepulse Delay"),
+      tag(0x0A, "SliceNumberMR", VR::IS, 1, "Slice Number MR"),
+      tag(0x0B, "SliceOrientation", VR::CS, 1, "Slice Orientation"),
+      tag(0x0C, "ArrhythmiaRejection", VR::CS, 1, "Arrhythmia Rejection"),
+      tag(0x0E, "CardiacCycled", VR::CS, 1, "Cardiac Cycled"),
+      tag(0x0F, "CardiacGateWidth", VR::SS, 1, "Cardiac Gate Width"),
+      tag(0x10, "CardiacSync", VR::CS, 1, "Cardiac Sync"),
+      tag(0x11, "DiffusionEchoTime", VR::FL, 1, "Diffusion Echo Time"),
+      tag(0x12, "DynamicSeries", VR::CS, 1, "Dynamic Series"),
+      tag(0x13, "EPIFactor", VR::SL, 1, "EPI Factor"),
+      tag(0x14, "NumberOfEchoes", VR::SL, 1, "Number of Echoes"),
+      tag(0x15, "NumberOfLocations", VR::SS, 1, "Number of Locations"),
+      tag(0x16, "NumberOfPCDirections", VR::SS, 1, "Number of PC Directions"),
+      tag(0x17, "NumberOfPhasesMR", VR::SL, 1, "Number of Phases MR"),
+      tag(0x18, "NumberOfSlicesMR", VR::SL, 1, "Number of Slices MR"),
+      tag(0x19, "PartialMatrixScanned", VR::CS, 1, "Partial Matrix Scanned"),
+      tag(0x1A, "PCVelocity", VR::FL, 0, "PC Velocity"),
+      tag(0x1B, "PrepulseDelay", VR::FL, 1, "Prepulse Delay"),
+      tag(0x1C, "PrepulseType", VR::CS, 1, "Prepulse Type"),
+      tag(0x1D, "ReconstructionNumberMR", VR::IS, 1, "Reconstruction Number MR"),
+      tag(0x1F, "RespirationSync", VR::CS, 1, "Respiration Sync"),
+      tag(0x20, "ScanningTechnique", VR::LO, 1, "Scanning Technique"),
+      tag(0x21, "SPIR", VR::CS, 1, "SPIR"),
+      tag(0x22, "WaterFatShift", VR::FL, 1, "Water Fat Shift"),
+      tag(0x23, "FlipAnglePhilips", VR::DS, 1, "Flip Angle Philips"),
+      tag(0x24, "Interactive", VR::CS, 1, "Interactive"),
+      tag(0x25, "EchoTimeDisplayMR", VR::SH, 1, "Echo Time Display MR"),
+      tag(0x2D, "NumberOfStackSlices", VR::SS, 1, "Number of Stack Slices"),
+      tag(0x32, "StackRadialAngle", VR::FL, 1, "Stack Radial Angle"),
+      tag(0x33, "StackRadialAxis", VR::CS, 1, "Stack Radial Axis"),
+      tag(0x35, "StackSliceNumber", VR::SS, 1, "Stack Slice Number"),
+      tag(0x36, "StackType", VR::CS, 1, "Stack Type"),
+      tag(0x5F, "StackSequence", VR::SQ, 1, "Stack Sequence"),
+      tag(0x60, "NumberOfStacks", VR::SL, 1, "Number of Stacks"),
+      tag(0x63, "ExaminationSource", VR::CS, 1, "Examination Source"),
+      tag(0x81, "NumberOfDynamicScans", VR::IS, 1, "Number of Dynamic Scans"),
+      tag(0x82, "EchoTrainLength", VR::IS, 1, "Echo Train Length"),
+      tag(0x83, "ImagingFrequency", VR::DS, 1, "Imaging Frequency"),
+      tag(0x85, "MagneticFieldStrength", VR::DS, 1, "Magnetic Field Strength"),
+      tag(0x86, "NumberOfPhaseEncodingSteps", VR::IS, 1, "Number of Phase Encoding Steps"),
+    ],
+  },
+  PrivateDictionary {
+    creator: "Philips MR Imaging DD 001",
+    group: 0x2005,
+    tags: &[
+      tag(0x0D, "ScaleIntercept", VR::FL, 1, "Scale Intercept"),
+      tag(0x0E, "ScaleSlope", VR::FL, 1, "Scale Slope"),
+      tag(0x20, "NumberOfChemicalShift", VR::SL, 1, "Number of Chemical Shift"),
+      tag(0xB0, "DiffusionDirectionRL", VR::FL, 1, "Diffusion Direction RL"),
+      tag(0xB1, "DiffusionDirectionAP", VR::FL, 1, "Diffusion Direction AP"),
+      tag(0xB2, "DiffusionDirectionFH", VR::FL, 1, "Diffusion Direction FH"),
+    ],
+  },
+];
+
+/**
+ * Whether the attributes of a group are private, i.e. the group is odd and not
+ * one of the groups reserved by the standard (0001, 0003, 0005, 0007, FFFF).
+ */
+pub fn is_private_group(group: u16) -> bool {
+  group % 2 == 1 && group > 0x0008 && group != 0xFFFF
+}
+
+/**
+ * Returns the element of the Private Creator attribute reserving the block of a
+ * private attribute, e.g. 0x0011 for (0029,1108).
+ * None for the attributes which are not part of a block, including the Private
+ * Creator attributes themselves.
+ */
+pub fn private_creator_element(group: u16, element: u16) -> Option<u16> {
+  if is_private_group(group) && element >= 0x1000 {
+    Some(element >> 8)
+  } else {
+    None
+  }
+}
+
+/**
+ * Returns the tag of a Private Creator attribute (gggg,0010-00FF).
+ */
+pub fn private_creator_tag(group: u16, element: u16) -> Tag {
+  Tag {
+    group,
+    element,
+    name: "PrivateCreator",
+    vr: VR::LO,
+    vm: ValueMultiplicity::exactly(1),
+    retired: false,
+    description: "Private Creator",
+  }
+}
+
+/**
+ * Looks a private attribute up in the private dictionaries, given the value of
+ * the Private Creator attribute reserving its block. The private tags loaded
+ * in `dictionary` are looked up first.
+ */
+pub fn lookup(creator: &str, group: u16, element: u16) -> Option<Tag> {
+  // Trailing spaces are not significant
+  let creator = creator.trim_end_matches([' ', '\0']);
+  // The tags loaded at runtime take precedence
+  if let Some(tag) = dictionary::get_private(creator, group, element) {
+    return Some(tag);
+  }
+  PRIVATE_DICTIONARIES
+    .iter()
+    .filter(|dictionary| dictionary.group == group && dictionary.creator == creator)
+    .flat_map(|dictionary| dictionary.tags.iter())
+    .find(|tag| tag.element as u16 == element & 0x00FF)
+    .map(|tag| Tag {
+      group,
+      element,
+      name: tag.name,
+      vr: tag.vr,
+      vm: tag.vm,
+      retired: false,
+      description: tag.description,
+    })
+}
+
+/**
+ * Returns the tag of an attribute, be it standard or private. `creator` is the
+ * value of the Private Creator attribute reserving the block of a private
+ * attribute, if any.
+ * Unknown attributes get a tag with a UN value representation.
+ */
+pub fn get_tag(group: u16, element: u16, creator: Option<&str>) -> Tag {
+  if let Ok(tag) = Tag::try_from(((group as u32) << 16) | element as u32) {
+    return tag;
+  }
+  if is_private_group(group) && (0x0010..=0x00FF).contains(&element) {
+    return private_creator_tag(group, element);
+  }
+  creator
+    .and_then(|creator| lookup(creator, group, element))
+    .unwrap_or_else(|| Tag::unknown(group, element))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn private_blocks() {
+    assert!(is_private_group(0x0029));
+    assert!(!is_private_group(0x0028));
+    assert!(!is_private_group(0x0007));
+    assert!(!is_private_group(0xFFFF));
+    assert_eq!(private_creator_element(0x0029, 0x1108), Some(0x0011));
+    assert_eq!(private_creator_element(0x0029, 0x0011), None);
+    assert_eq!(private_creator_element(0x0028, 0x1108), None);
+  }
+
+  #[test]
+  fn tags_of_a_creator() {
+    // Trailing spaces of the creator are padding
+    let tag = get_tag(0x0019, 0x100C, Some("SIEMENS MR HEADER "));
+    assert_eq!((tag.element, tag.name, tag.vr), (0x100C, "BValue", VR::IS));
+    // The same tag in another block
+    let tag = get_tag(0x0019, 0x120C, Some("SIEMENS MR HEADER"));
+    assert_eq!((tag.element, tag.name), (0x120C, "BValue"));
+    // The same creator in another group
+    assert_eq!(lookup("SIEMENS MR HEADER", 0x0021, 0x100C), None);
+    assert_eq!(get_tag(0x0019, 0x0012, None).name, "PrivateCreator");
+    assert_eq!(
+      get_tag(0x0043, 0x1039, Some("UNKNOWN VENDOR")),
+      Tag::unknown(0x0043, 0x1039)
+    );
+    assert_eq!(get_tag(0x0019, 0x100C, None), Tag::unknown(0x0019, 0x100C));
+  }
+
+  #[test]
+  fn registered_tags_take_precedence() {
+    // The registry is shared with the tests of dictionary, which use other tags
+    dictionary::register(
+      Tag {
+        group: 0x0051,
+        element: 0x100C,
+        name: "FieldOfViewText",
+        vr: VR::LT,
+        vm: ValueMultiplicity::exactly(1),
+        retired: false,
+        description: "Field of View Text",
+      },
+      Some("SIEMENS MR HEADER"),
+    );
+    let tag = get_tag(0x0051, 0x130C, Some("SIEMENS MR HEADER"));
+    assert_eq!(
+      (tag.element, tag.name, tag.vr),
+      (0x130C, "FieldOfViewText", VR::LT)
+    );
+    // The other tags of the built-in dictionary are still there
+    assert_eq!(
+      get_tag(0x0051, 0x100D, Some("SIEMENS MR HEADER")).name,
+      "SlicePositionText"
+    );
+    assert_eq!(lookup("SIEMENS CSA HEADER", 0x0051, 0x100C), None);
+  }
+}
@@ -28,7 +28,7 @@
 use atty::Stream;
 use clap::Parser;
 use sqlite::Connection;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::fs::metadata;
 use std::fs::File;
@@ -41,8 +41,11 @@ use walkdir::WalkDir;
 
 use rdicom::config_file::{self, ConfigProvenance};
 use rdicom::dicom_tags;
-use rdicom::dicom_tags::{MediaStorageSOPClassUID, Modality};
-use rdicom::instance::Instance;
+use rdicom::dicom_tags::{MediaStorageSOPClassUID, Modality, PixelData};
+use rdicom::error::DicomErrorKind;
+use rdicom::instance::{DicomValue, Instance};
+use rdicom::tags::{Tag, TagPath};
+use rdicom::uids::MediaStorageDirectoryStorage;
 
 mod config;
 mod db;
@@ -51,7 +54,6 @@ mod index_store;
 use index_store::{CsvIndexStore, IndexStore, SqlIndexStore};
 
 const ESC: char = 27u8 as char;
-const MEDIA_STORAGE_DIRECTORY_STORAGE: &str = "1.2.840.10008.1.3.10";
 
 /// Scan a folder for DICOM assets and create an index file in CSV or SQL format.
 #[derive(Debug, Parser)]
@@ -110,6 +112,27 @@ fn file_exists(path: &str) -> Result<PathBuf, Box<dyn Error>> {
 
 pub const DEFAULT_CONFIG: &str = include_str!("../config.yaml");
 
+fn join<T: ToString>(values: Vec<T>) -> String {
+  values
+    .iter()
+    .map(|value| value.to_string())
+    .collect::<Vec<String>>()
+    .join("\\")
+}
+
+// Dates and times are stored normalized (e.g. the ACR-NEMA date "2023.04.15" is
+// stored as "20230415") so that they can be compared as strings by range
+// queries. The values which cannot be parsed are stored as is.
+fn index_value(value: &DicomValue) -> String {
+  let normalized = match value {
+    DicomValue::DA(_) => value.to_dates().map(join),
+    DicomValue::TM(_) => value.to_times().map(join),
+    DicomValue::DT(_) => value.to_date_times().map(join),
+    _ => return value.to_string(),
+  };
+  normalized.unwrap_or_else(|_| value.to_string())
+}
+
 fn configure_log(log_file: &PathBuf) -> Result<(), Box<dyn Error>> {
   use log4rs::append::file::FileAppender;
   use log4rs::config::{Appender, Config, Root};
@@ -154,6 +177,7 @@ fn main() -> Result<(), Box<dyn Error>> {
   // Are we on a terminal
   let on_a_tty = atty::is(Stream::Stdout);
   let config: config::Config = serde_yaml::from_str(&config_access.content)?;
+  config.load_dictionaries()?;
   // Create an vector of fields to write in the index
   let indexable_fields = config
     .indexing
@@ -169,6 +193,21 @@ fn main() -> Result<(), Box<dyn Error>> {
         .chain(config.indexing.fields.instances.into_iter()),
     )
     .collect::<Vec<String>>();
+  // Check the fields against the dictionaries before scanning anything
+  let field_paths = indexable_fields
+    .iter()
+    .map(|field| {
+      TagPath::try_from(field)
+        .map_err(|e| format!("Invalid field {} in the configuration: {}", field, e))
+    })
+    .collect::<Result<Vec<TagPath>, String>>()?;
+  // Only the header of the files needs to be read, unless a field to index is
+  // located after the pixel data
+  let stop_at = field_paths
+    .iter()
+    .map(|path| path.components[0].tag.clone())
+    .all(|tag| (tag.group, tag.element) < (PixelData.group, PixelData.element))
+    .then_some(PixelData);
   // Create an index store depending on the options
   let mut index_store: Box<dyn IndexStore> = if let Some(sql_output) = opt.sql_output.clone() {
     let connection = Connection::open_thread_safe(sql_output)?;
@@ -191,6 +230,8 @@ fn main() -> Result<(), Box<dyn Error>> {
   // There sets will be used for a fancy display
   let mut count = 0;
   let mut error_count = 0;
+  // Number of DICOM errors per kind, for the summary
+  let mut error_kinds = BTreeMap::<DicomErrorKind, usize>::new();
   let mut study_set: HashSet<String> = HashSet::new();
   let mut series_set: HashSet<String> = HashSet::new();
   let mut modality_set: HashSet<String> = HashSet::new();
@@ -214,22 +255,25 @@ fn main() -> Result<(), Box<dyn Error>> {
           .to_string_lossy()
           .to_string();
         match Instance::from_filepath(&filepathstr) {
-          Ok(instance) => {
+          Ok(mut instance) => {
+            instance.stop_at = stop_at.clone();
             let index_start = std::time::Instant::now();
             log::info!("indexing {}", filepathstr);
             match instance.get_value(&MediaStorageSOPClassUID) {
               // Ignore DICOMDIR files
               Ok(Some(sop_class_uid))
-                if sop_class_uid.to_string() != MEDIA_STORAGE_DIRECTORY_STORAGE =>
-              {
+                if sop_class_uid.to_string() == MediaStorageDirectoryStorage.uid => {}
+              // Files without file meta information (ACR-NEMA, raw datasets)
+              // have no MediaStorageSOPClassUID but are indexed nonetheless
+              Ok(_) => {
                 let mut data = HashMap::<String, String>::new();
                 // We want the filepath in the index by default
                 data.insert("filepath".to_string(), relative_filepath_str);
-                for field in indexable_fields.iter() {
-                  match instance.get_value(&field.try_into()?) {
+                for (field, path) in indexable_fields.iter().zip(&field_paths) {
+                  match instance.get_value_by_path(path) {
                     Ok(result) => {
                       let value = if let Some(value) = result {
-                        value.to_string()
+                        index_value(&value)
                       } else {
                         "undefined".to_string()
                       };
@@ -239,9 +283,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                     Err(e) => {
                       print!("\r\x1b[2K");
                       io::stdout().flush()?;
-                      eprintln!("{}: {}", filepathstr, e.details);
-                      log::error!("{}: {}", filepathstr, e.details);
+                      eprintln!("{}: {}", filepathstr, e);
+                      log::error!("{}: {}", filepathstr, e);
                       error_count += 1;
+                      *error_kinds.entry(e.kind).or_default() += 1;
                     }
                   }
                 }
@@ -280,15 +325,23 @@ fn main() -> Result<(), Box<dyn Error>> {
                   }
                 }
               }
-              _ => (),
+              Err(e) => {
+                print!("\r\x1b[2K");
+                io::stdout().flush()?;
+                eprintln!("{}: {}", filepathstr, e);
+                log::error!("{}: {}", filepathstr, e);
+                error_count += 1;
+                *error_kinds.entry(e.kind).or_default() += 1;
+              }
             }
           }
           Err(e) => {
             print!("\r\x1b[2K");
             io::stdout().flush()?;
-            eprintln!("{}: {}", filepathstr, e.details);
-            log::error!("{}: {}", filepathstr, e.details);
+            eprintln!("{}: {}", filepathstr, e);
+            log::error!("{}: {}", filepathstr, e);
             error_count += 1;
+            *error_kinds.entry(e.kind).or_default() += 1;
           }
         }
       }
@@ -307,6 +360,16 @@ fn main() -> Result<(), Box<dyn Error>> {
     modality_set,
     error_count
   );
+  if !error_kinds.is_empty() {
+    print!(
+      " ({})",
+      error_kinds
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind))
+        .collect::<Vec<String>>()
+        .join(", ")
+    );
+  }
   if let Some(sql_output) = opt.sql_output {
     println!(" and index written to {}", sql_output);
   } else {
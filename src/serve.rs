@@ -65,9 +65,14 @@ use crate::index_store::IndexStore;
 use index_store::SqlIndexStoreWithMutex;
 use rdicom::config_file::{self, ConfigProvenance};
 use rdicom::dicom_tags;
-use rdicom::error::DicomError;
-use rdicom::instance::{DicomValue, Instance};
-use rdicom::tags::Tag;
+use rdicom::error::{DicomError, DicomErrorKind};
+use rdicom::instance::{self, DicomValue, Instance};
+use rdicom::person_name::PersonName;
+use rdicom::storage::read_transfer_syntax;
+use rdicom::tags::{Tag, TagPath, TagPathComponent};
+use rdicom::transfer_syntax::{self, TransferSyntax};
+use rdicom::uids;
+use rdicom::value_representation::ValueRepresentation;
 
 mod config;
 mod db;
@@ -389,52 +394,204 @@ fn get_indexed_fields(connection: &Connection) -> Result<Vec<String>, Box<dyn Er
   Ok(result)
 }
 
-fn map_to_entry(tag_map: &HashMap<String, String>) -> String {
+fn person_name_to_json(name: &PersonName) -> String {
+  let groups = [
+    ("Alphabetic", &name.alphabetic),
+    ("Ideographic", &name.ideographic),
+    ("Phonetic", &name.phonetic),
+  ];
   format!(
     "{{ {} }}",
-    tag_map
+    groups
       .iter()
-      .map(|(key, value)| {
-        // Try to convert the column name to a tag
-        let tag_result: Result<Tag, DicomError> = key.try_into();
-        match tag_result {
-          Ok(tag) => {
-            match tag.vr {
-              "OB" | "OD" | "OF" | "OL" | "OV" | "OW" => {
-                format!(
-                  // Create a BulkdataURI
-                  // "00080030": "/bulkdata/{StudyInstanceUID}/{SeriesInstanceUID}/{SOPInstanceUID}/{tag}",
-                  "\"{:04X}{:04X}\": \"/bulkdata/{}\"",
-                  tag.group, tag.element, value,
-                )
-              }
-              _ => {
-                format!(
-                  // We have a Dicom that we will format according to the DicomWeb standard
-                  // "00080030": {
-                  //   "vr": "TM",
-                  //   "Value": ["131600.0000"]
-                  // },
-                  "\"{:04X}{:04X}\": {{ \"vr\": \"{}\", \"Value\": [ \"{}\" ] }}",
-                  // TODO: The replace here is an ugly workaround which is probably going to cause more
-                  // problem than it will solve.
-                  tag.group,
-                  tag.element,
-                  tag.vr,
-                  value.replace("\\", ","),
-                )
-              }
-            }
-          }
-          // Otherwise, just dump the key in the object
-          _ => format!("\"{key}\": \"{value}\""),
-        }
+      .filter_map(|(key, group)| {
+        group
+          .as_ref()
+          .map(|components| format!("\"{}\": \"{}\"", key, components))
       })
       .collect::<Vec<String>>()
-      .join(",")
+      .join(", ")
   )
 }
 
+// The JSON of the value of an attribute whose tag is `tag`
+fn value_to_json(tag: &Tag, value: &str) -> Result<String, DicomError> {
+  Ok(match tag.vr {
+    ValueRepresentation::OB
+    | ValueRepresentation::OD
+    | ValueRepresentation::OF
+    | ValueRepresentation::OL
+    | ValueRepresentation::OV
+    | ValueRepresentation::OW => {
+      format!(
+        // Create a BulkdataURI
+        // "00080030": "/bulkdata/{StudyInstanceUID}/{SeriesInstanceUID}/{SOPInstanceUID}/{tag}",
+        "\"/bulkdata/{}\"",
+        value,
+      )
+    }
+    ValueRepresentation::PN => {
+      format!(
+        // Person names are objects with a string per component group
+        // "00100010": {
+        //   "vr": "PN",
+        //   "Value": [{ "Alphabetic": "Yamada^Tarou", "Ideographic": "山田^太郎" }]
+        // },
+        "{{ \"vr\": \"{}\", \"Value\": [ {} ] }}",
+        tag.vr,
+        value
+          .split('\\')
+          .map(|name| person_name_to_json(&PersonName::parse(name)))
+          .collect::<Vec<String>>()
+          .join(", "),
+      )
+    }
+    ValueRepresentation::DS | ValueRepresentation::IS => {
+      format!(
+        // Decimal and integer strings are numbers, as in dcm2json
+        // "00280030": {
+        //   "vr": "DS",
+        //   "Value": [0.5, 0.5]
+        // },
+        "{{ \"vr\": \"{}\", \"Value\": [ {} ] }}",
+        tag.vr,
+        value
+          .split('\\')
+          .filter(|v| !v.trim().is_empty())
+          .map(|v| number_to_json(tag.vr, v))
+          .collect::<Result<Vec<String>, DicomError>>()?
+          .join(", "),
+      )
+    }
+    _ => {
+      format!(
+        // We have a Dicom that we will format according to the DicomWeb standard
+        // "00080030": {
+        //   "vr": "TM",
+        //   "Value": ["131600.0000"]
+        // },
+        "{{ \"vr\": \"{}\", \"Value\": [ \"{}\" ] }}",
+        // TODO: The replace here is an ugly workaround which is probably going to cause more
+        // problem than it will solve.
+        tag.vr,
+        value.replace("\\", ","),
+      )
+    }
+  })
+}
+
+// The JSON number of a DS or IS value. JSON has no infinity, which a DS with a
+// large exponent would overflow to.
+fn number_to_json(vr: ValueRepresentation, value: &str) -> Result<String, DicomError> {
+  if vr == ValueRepresentation::IS {
+    return instance::parse_integer_string(value).map(|number| number.to_string());
+  }
+  match instance::parse_decimal_string(value)? {
+    number if number.is_finite() => Ok(number.to_string()),
+    _ => Err(DicomError::with_kind(
+      DicomErrorKind::InvalidValue,
+      &format!("Invalid DS value: {:?}", value),
+    )),
+  }
+}
+
+/**
+ * A dataset (or an item) in the DICOM JSON model, its attributes being keyed by
+ * tag (e.g. 00080030) as in PS3.18 F.2. The values of the nested attributes are
+ * in the items of their sequence.
+ */
+#[derive(Default)]
+struct JsonDataset(BTreeMap<String, JsonAttribute>);
+
+enum JsonAttribute {
+  // The JSON of the value
+  Value(String),
+  Sequence(Vec<JsonDataset>),
+}
+
+impl JsonDataset {
+  // Adds the value of the attribute pointed by the path, creating the sequences
+  // and items leading to it.
+  fn insert(&mut self, path: &[TagPathComponent], value: String) {
+    let Some((first, rest)) = path.split_first() else {
+      return;
+    };
+    let key = format!("{:04X}{:04X}", first.tag.group, first.tag.element);
+    if rest.is_empty() {
+      self.0.insert(key, JsonAttribute::Value(value));
+      return;
+    }
+    let sequence = self
+      .0
+      .entry(key)
+      .or_insert_with(|| JsonAttribute::Sequence(vec![]));
+    if let JsonAttribute::Value(_) = sequence {
+      *sequence = JsonAttribute::Sequence(vec![]);
+    }
+    if let JsonAttribute::Sequence(items) = sequence {
+      // Without index, the value comes from any of the items. It is given in
+      // the first one.
+      let item = first.item.unwrap_or(0);
+      if items.len() <= item {
+        items.resize_with(item + 1, JsonDataset::default);
+      }
+      items[item].insert(rest, value);
+    }
+  }
+
+  fn to_json(&self) -> String {
+    format!(
+      "{{ {} }}",
+      self
+        .0
+        .iter()
+        .map(|(key, attribute)| match attribute {
+          JsonAttribute::Value(value) => format!("\"{}\": {}", key, value),
+          JsonAttribute::Sequence(items) => format!(
+            "\"{}\": {{ \"vr\": \"SQ\", \"Value\": [ {} ] }}",
+            key,
+            items
+              .iter()
+              .map(JsonDataset::to_json)
+              .collect::<Vec<String>>()
+              .join(", ")
+          ),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+    )
+  }
+}
+
+fn map_to_entry(tag_map: &HashMap<String, String>) -> Result<String, DicomError> {
+  let mut dataset = JsonDataset::default();
+  for (key, value) in tag_map {
+    // Try to convert the column name to a tag path
+    let path_result: Result<TagPath, DicomError> = key.try_into();
+    match path_result {
+      Ok(path) => {
+        // The values of the index come from the files: a malformed one is a
+        // broken file rather than a bad request
+        let json = value_to_json(path.tag(), value).map_err(|error| {
+          DicomError {
+            kind: DicomErrorKind::InvalidFormat,
+            ..error
+          }
+          .in_path(key)
+        })?;
+        dataset.insert(&path.components, json)
+      }
+      // Otherwise, just dump the key in the object
+      _ => {
+        dataset
+          .0
+          .insert(key.clone(), JsonAttribute::Value(format!("\"{value}\"")));
+      }
+    }
+  }
+  Ok(dataset.to_json())
+}
+
 // Create an SQL where clause based on the search_term and query parameters.
 fn create_where_clause(
   params: &QidoQueryParameters,
@@ -461,12 +618,27 @@ fn create_where_clause(
           "{}{}{}{}",
           field.name,
           if fuzzymatching { " LIKE '%" } else { "='" },
-          value,
+          // The values come from the request
+          value.replace('\'', "''"),
           if fuzzymatching { "%'" } else { "'" },
         )
     })
 }
 
+// Extract the attribute matching of a QIDO query (e.g. ?Modality=CT). The UIDs
+// of the registry can be given by their keyword (e.g. SOPClassUID=CTImageStorage).
+fn get_attribute_matching(query: &HashMap<String, String>) -> HashMap<Tag, String> {
+  query
+    .iter()
+    // The other query parameters (limit, offset, ...) are not attributes
+    .filter_map(|(key, value)| Some((Tag::try_from(key).ok()?, value)))
+    .map(|(tag, value)| match uids::get_by_keyword(value) {
+      Some(uid) if tag.vr == ValueRepresentation::UI => (tag, uid.uid.to_string()),
+      _ => (tag, value.to_string()),
+    })
+    .collect()
+}
+
 fn create_limit_clause(params: &QidoQueryParameters) -> String {
   let limit = params.limit.unwrap_or(u32::MAX as usize);
   let offset = params.offset.unwrap_or(0);
@@ -519,7 +691,10 @@ impl InstanceFactory for FSInstanceFactory {
       Ok(Box::new(BufWriter::new(f)))
     } else {
       error!("{:?} file already exists, cannot overwrite file", path);
-      Err(DicomError::new("File already exists"))
+      Err(DicomError::with_kind(
+        DicomErrorKind::Io,
+        "File already exists",
+      ))
     }
   }
 }
@@ -591,8 +766,7 @@ fn get_entries(
           let instance = Instance::from_reader(reader)?;
           // Go through those missing fields from the index and enrich the data from the index
           for field in &fields_to_fetch {
-            if let Some(field_value) = instance.get_value(&field.try_into()?)? {
-              // TODO: Manage nested fields
+            if let Some(field_value) = instance.get_value_by_path(&field.try_into()?)? {
               item.insert(field.to_string(), field_value.to_string());
             }
           }
@@ -614,6 +788,7 @@ struct SearchTerms {
 async fn get_studies(
   axum::extract::State(state): axum::extract::State<Arc<AppState>>,
   params: axum::extract::Query<QidoQueryParameters>,
+  axum::extract::Query(attributes): axum::extract::Query<HashMap<String, String>>,
   Path(SearchTerms {
     instance_uid,
     study_uid,
@@ -621,7 +796,7 @@ async fn get_studies(
   }): Path<SearchTerms>,
   headers: HeaderMap,
 ) -> impl IntoResponse {
-  let mut search_terms = HashMap::<Tag, String>::new();
+  let mut search_terms = get_attribute_matching(&attributes);
   if let Some(instance_uid) = instance_uid {
     search_terms.insert(dicom_tags::SOPInstanceUID, instance_uid);
   }
@@ -646,15 +821,20 @@ async fn get_studies(
         .iter()
         .any(|e| e == "application/json" || e == "application/json+dicom")
       {
-        response_headers.insert(
-          "content-type",
-          "application/dicom+json; charset=utf-8".parse().unwrap(),
-        );
         // 🤮 TODO: need to replace generate_json_response
-        (
-          response_headers,
-          generate_json_response(&result).into_response(),
-        )
+        match generate_json_response(&result) {
+          Ok(json) => {
+            response_headers.insert(
+              "content-type",
+              "application/dicom+json; charset=utf-8".parse().unwrap(),
+            );
+            (response_headers, json.into_response())
+          }
+          Err(e) => {
+            tracing::error!("{}", e);
+            (response_headers, error_status(&e).into_response())
+          }
+        }
       } else {
         (
           response_headers,
@@ -663,10 +843,10 @@ async fn get_studies(
       }
     }
     Ok(_) => (response_headers, StatusCode::NOT_FOUND.into_response()),
-    Err(_) => (
-      response_headers,
-      StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    ),
+    Err(e) => {
+      tracing::error!("{}", e);
+      (response_headers, error_status(e.as_ref()).into_response())
+    }
   }
 }
 
@@ -674,6 +854,7 @@ async fn get_studies(
 async fn get_series(
   axum::extract::State(state): axum::extract::State<Arc<AppState>>,
   params: axum::extract::Query<QidoQueryParameters>,
+  axum::extract::Query(attributes): axum::extract::Query<HashMap<String, String>>,
   Path(SearchTerms {
     instance_uid,
     study_uid,
@@ -681,7 +862,7 @@ async fn get_series(
   }): Path<SearchTerms>,
   headers: HeaderMap,
 ) -> impl IntoResponse {
-  let mut search_terms = HashMap::<Tag, String>::new();
+  let mut search_terms = get_attribute_matching(&attributes);
   if let Some(instance_uid) = instance_uid {
     search_terms.insert(dicom_tags::SOPInstanceUID, instance_uid);
   }
@@ -706,14 +887,19 @@ async fn get_series(
         .iter()
         .any(|e| e == "application/json" || e == "application/json+dicom")
       {
-        response_headers.insert(
-          "content-type",
-          "application/dicom+json; charset=utf-8".parse().unwrap(),
-        );
-        (
-          response_headers,
-          generate_json_response(&result).into_response(),
-        )
+        match generate_json_response(&result) {
+          Ok(json) => {
+            response_headers.insert(
+              "content-type",
+              "application/dicom+json; charset=utf-8".parse().unwrap(),
+            );
+            (response_headers, json.into_response())
+          }
+          Err(e) => {
+            tracing::error!("{}", e);
+            (response_headers, error_status(&e).into_response())
+          }
+        }
       } else {
         (
           response_headers,
@@ -722,10 +908,10 @@ async fn get_series(
       }
     }
     Ok(_) => (response_headers, StatusCode::NOT_FOUND.into_response()),
-    Err(_) => (
-      response_headers,
-      StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    ),
+    Err(e) => {
+      tracing::error!("{}", e);
+      (response_headers, error_status(e.as_ref()).into_response())
+    }
   }
 }
 
@@ -733,6 +919,7 @@ async fn get_series(
 async fn get_instances(
   axum::extract::State(state): axum::extract::State<Arc<AppState>>,
   params: axum::extract::Query<QidoQueryParameters>,
+  axum::extract::Query(attributes): axum::extract::Query<HashMap<String, String>>,
   Path(SearchTerms {
     instance_uid,
     study_uid,
@@ -740,7 +927,7 @@ async fn get_instances(
   }): Path<SearchTerms>,
   headers: HeaderMap,
 ) -> impl IntoResponse {
-  let mut search_terms = HashMap::<Tag, String>::new();
+  let mut search_terms = get_attribute_matching(&attributes);
   if let Some(instance_uid) = instance_uid {
     search_terms.insert(dicom_tags::SOPInstanceUID, instance_uid);
   }
@@ -765,14 +952,19 @@ async fn get_instances(
         .iter()
         .any(|e| e == "application/json" || e == "application/json+dicom")
       {
-        response_headers.insert(
-          "content-type",
-          "application/dicom+json; charset=utf-8".parse().unwrap(),
-        );
-        (
-          response_headers,
-          generate_json_response(&result).into_response(),
-        )
+        match generate_json_response(&result) {
+          Ok(json) => {
+            response_headers.insert(
+              "content-type",
+              "application/dicom+json; charset=utf-8".parse().unwrap(),
+            );
+            (response_headers, json.into_response())
+          }
+          Err(e) => {
+            tracing::error!("{}", e);
+            (response_headers, error_status(&e).into_response())
+          }
+        }
       } else {
         (
           response_headers,
@@ -781,10 +973,87 @@ async fn get_instances(
       }
     }
     Ok(_) => (response_headers, StatusCode::NOT_FOUND.into_response()),
-    Err(_) => (
+    Err(e) => {
+      tracing::error!("{}", e);
+      (response_headers, error_status(e.as_ref()).into_response())
+    }
+  }
+}
+
+#[derive(Deserialize)]
+struct InstancePath {
+  study_uid: String,
+  series_uid: String,
+  instance_uid: String,
+}
+
+/**
+ * WADO-RS retrieve instance. The file is returned as it is stored, in a single
+ * application/dicom part as multipart responses are not supported yet.
+ */
+#[axum_macros::debug_handler]
+async fn get_instance(
+  axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+  Path(InstancePath {
+    study_uid,
+    series_uid,
+    instance_uid,
+  }): Path<InstancePath>,
+  headers: HeaderMap,
+) -> impl IntoResponse {
+  let mut response_headers = HeaderMap::new();
+  let accepts = get_accept_formats(headers)
+    .iter()
+    .map(|accept_format| parse_accept_header(accept_format))
+    .collect::<Vec<AcceptHeader>>();
+  let Ok(accept) = get_accept_format(&accepts, &["application/dicom", "*/*"]) else {
+    return (
       response_headers,
-      StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    ),
+      StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response(),
+    );
+  };
+  let search_terms = HashMap::from([
+    (dicom_tags::StudyInstanceUID, study_uid),
+    (dicom_tags::SeriesInstanceUID, series_uid),
+    (dicom_tags::SOPInstanceUID, instance_uid),
+  ]);
+  let params = QidoQueryParameters {
+    limit: None,
+    offset: None,
+    fuzzymatching: None,
+    includefield: None,
+  };
+  let entries = get_entries(
+    &state.connection.lock().unwrap(),
+    &state.instance_factory,
+    &params,
+    &search_terms,
+    "filepath",
+  );
+  match entries.as_ref().map(|entries| entries.first()) {
+    Ok(Some(entry)) => match get_dicom_file(state.instance_factory.as_ref(), entry, accept) {
+      Ok((transfer_syntax, content)) => {
+        response_headers.insert(
+          "content-type",
+          format!(
+            "application/dicom; transfer-syntax={}",
+            transfer_syntax.uid.uid
+          )
+          .parse()
+          .unwrap(),
+        );
+        (response_headers, content.into_response())
+      }
+      Err(e) => {
+        tracing::error!("{}", e);
+        (response_headers, error_status(e.as_ref()).into_response())
+      }
+    },
+    Ok(None) => (response_headers, StatusCode::NOT_FOUND.into_response()),
+    Err(e) => {
+      tracing::error!("{}", e);
+      (response_headers, error_status(e.as_ref()).into_response())
+    }
   }
 }
 
@@ -930,15 +1199,72 @@ fn get_bulk_tag<T: InstanceFactory>(
   }
 }
 
-fn generate_json_response(data: &[HashMap<String, String>]) -> String {
-  format!(
+/**
+ * Returns the transfer syntax and the content of the file of an instance, if
+ * its transfer syntax is the one requested by the accept header (e.g.
+ * application/dicom; transfer-syntax=1.2.840.10008.1.2.1). Explicit VR Little
+ * Endian is the default and * accepts any transfer syntax, as instances are
+ * served as they are stored, without transcoding.
+ */
+fn get_dicom_file(
+  instance_factory: &(dyn InstanceFactory + Send + Sync),
+  entry: &HashMap<String, String>,
+  accept: &AcceptHeader,
+) -> Result<(TransferSyntax, Vec<u8>), Box<dyn Error>> {
+  let filepath = entry
+    .get("filepath")
+    .ok_or("Missing filepath in the index")?;
+  let mut content = vec![];
+  instance_factory
+    .get_reader(filepath)?
+    .read_to_end(&mut content)?;
+  // Only the file meta information is parsed, a deflated dataset is not inflated
+  let transfer_syntax = read_transfer_syntax(&mut content.as_slice())?;
+  let requested = accept
+    .parameters
+    .get("transfer-syntax")
+    .map(String::as_str)
+    .unwrap_or(transfer_syntax::ExplicitVRLittleEndian.uid.uid);
+  if requested != "*" && TransferSyntax::try_from(requested)? != transfer_syntax {
+    return Err(Box::new(DicomError::with_kind(
+      DicomErrorKind::UnsupportedTransferSyntax,
+      &format!(
+        "{} is stored in {}, transcoding to {} is not supported",
+        filepath, transfer_syntax, requested
+      ),
+    )));
+  }
+  Ok((transfer_syntax, content))
+}
+
+// The HTTP status code of the response to a request which failed with `error`
+fn error_status(error: &(dyn Error + 'static)) -> StatusCode {
+  match error.downcast_ref::<DicomError>().map(|error| error.kind) {
+    Some(DicomErrorKind::UnknownTag) | Some(DicomErrorKind::InvalidValue) => {
+      StatusCode::BAD_REQUEST
+    }
+    Some(DicomErrorKind::NotFound) => StatusCode::NOT_FOUND,
+    Some(DicomErrorKind::UnsupportedTransferSyntax) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+    // The DICOM file is broken
+    Some(DicomErrorKind::NotDicom)
+    | Some(DicomErrorKind::Truncated)
+    | Some(DicomErrorKind::InvalidFormat)
+    | Some(DicomErrorKind::InvalidEncoding) => StatusCode::UNPROCESSABLE_ENTITY,
+    Some(DicomErrorKind::Io) | Some(DicomErrorKind::Other) | None => {
+      StatusCode::INTERNAL_SERVER_ERROR
+    }
+  }
+}
+
+fn generate_json_response(data: &[HashMap<String, String>]) -> Result<String, DicomError> {
+  Ok(format!(
     "[{}]",
     data
       .iter()
       .map(map_to_entry)
-      .collect::<Vec<String>>()
+      .collect::<Result<Vec<String>, DicomError>>()?
       .join(",")
-  )
+  ))
 }
 
 fn dicom_attribute_json_to_string(attribute: &DicomAttributeJson) -> String {
@@ -1009,7 +1335,7 @@ async fn post_studies(
       .unwrap(),
   )
   .map_err(|e| {
-    tracing::error!(e.details);
+    tracing::error!("{}", e);
     (
       StatusCode::BAD_REQUEST,
       Json("Could not perform STORE").into_response(),
@@ -1036,7 +1362,7 @@ async fn post_studies(
       e
     );
     (
-      StatusCode::INTERNAL_SERVER_ERROR,
+      error_status(&e),
       Json("Could not perform STORE").into_response(),
     )
   })?;
@@ -1120,7 +1446,7 @@ fn check_db(
     // We will create the requested table with the appropriate fields
     let table = indexable_fields
       .iter()
-      .map(|s| s.to_string() + " TEXT NON NULL")
+      .map(|s| format!("\"{}\" TEXT NON NULL", s))
       .collect::<Vec<String>>()
       .join(",");
 
@@ -1159,6 +1485,23 @@ fn get_accept_format<'a>(
   )))
 }
 
+// Splits an entry of the accept header in its format and its parameters, e.g.
+// application/dicom; transfer-syntax="1.2.840.10008.1.2.1"
+fn parse_accept_header(accept: &str) -> AcceptHeader {
+  let mut fields = accept.split(';');
+  let format = fields.next().unwrap_or_default().trim().to_lowercase();
+  let parameters = fields
+    .filter_map(|parameter| parameter.split_once('='))
+    .map(|(name, value)| {
+      (
+        name.trim().to_lowercase(),
+        value.trim().trim_matches('"').to_string(),
+      )
+    })
+    .collect::<HashMap<String, String>>();
+  AcceptHeader { format, parameters }
+}
+
 fn get_accept_formats(headers: HeaderMap) -> Vec<String> {
   // The following 3 lines could be within a function
   let accept_types = headers
@@ -1305,6 +1648,7 @@ fn get_config(opt: &Opt) -> Result<config::Config, Box<dyn Error>> {
   }
 
   let config: config::Config = serde_yaml::from_str(&config_access.content)?;
+  config.load_dictionaries()?;
 
   Ok(config)
 }
@@ -1406,23 +1750,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
       get(get_instances),
     )
     .route(
-      "/studies/{study_uid}/series/{series_uid}/instances/{instances_uid}",
-      get(get_instances),
+      "/studies/{study_uid}/series/{series_uid}/instances/{instance_uid}",
+      get(get_instance),
     )
     .route(
-      "/studies/{study_uid}/series/{series_uid}/instances/{instances_uid}/frames/{frame_uid}",
+      "/studies/{study_uid}/series/{series_uid}/instances/{instance_uid}/frames/{frame_uid}",
       get(not_implemented),
     )
     .route(
-      "/studies/{study_uid}/series/{series_uid}/instances/{instances_uid}/rendered",
+      "/studies/{study_uid}/series/{series_uid}/instances/{instance_uid}/rendered",
       get(not_implemented),
     )
     .route(
-      "/studies/{study_uid}/series/{series_uid}/instances/{instances_uid}/thumbnail",
+      "/studies/{study_uid}/series/{series_uid}/instances/{instance_uid}/thumbnail",
       get(not_implemented),
     )
     .route(
-      "/studies/{study_uid}/series/{series_uid}/instances/{instances_uid}/{tag_id}",
+      "/studies/{study_uid}/series/{series_uid}/instances/{instance_uid}/{tag_id}",
       get(not_implemented),
     )
     // POST
@@ -0,0 +1,791 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Read, Seek, SeekFrom};
+
+use alloc::borrow::Cow;
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::boxed::Box;
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+#[cfg(not(target_arch = "wasm32"))]
+use core::cell::RefCell;
+use core::fmt;
+
+use crate::error::{DicomError, DicomErrorKind};
+use crate::instance::ByteOrder;
+use crate::misc::MAX_NESTING_DEPTH;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::misc::{guess_transfer_syntax, DICOM_PROBE_SIZE};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tags::Tag;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transfer_syntax::TransferSyntax;
+use crate::value_representation::ValueRepresentation;
+
+/**
+ * The bytes an Instance is parsed from.
+ * Depending on how the instance was created, the bytes are either held in
+ * memory, borrowed from the caller, memory mapped or read on demand from a
+ * reader.
+ */
+pub enum Storage<'a> {
+  Owned(Vec<u8>),
+  Borrowed(&'a [u8]),
+  #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+  Mapped(memmap2::Mmap),
+  #[cfg(not(target_arch = "wasm32"))]
+  Lazy(LazyBuffer),
+}
+
+impl<'a> Storage<'a> {
+  /**
+   * Returns the size of the underlying file.
+   */
+  pub fn len(&self) -> usize {
+    match self {
+      Storage::Owned(buffer) => buffer.len(),
+      Storage::Borrowed(buffer) => buffer.len(),
+      #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+      Storage::Mapped(mmap) => mmap.len(),
+      #[cfg(not(target_arch = "wasm32"))]
+      Storage::Lazy(lazy) => lazy.length,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /**
+   * Returns `length` bytes starting at `offset`.
+   * Fails if the range is out of the file bounds or could not be read.
+   */
+  pub fn bytes(&self, offset: usize, length: usize) -> Result<&[u8], DicomError> {
+    let end = self.check_range(offset, length)?;
+    match self {
+      Storage::Owned(buffer) => Ok(&buffer[offset..end]),
+      Storage::Borrowed(buffer) => Ok(&buffer[offset..end]),
+      #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+      Storage::Mapped(mmap) => Ok(&mmap[offset..end]),
+      #[cfg(not(target_arch = "wasm32"))]
+      Storage::Lazy(lazy) => lazy.bytes(offset, length),
+    }
+  }
+
+  /**
+   * Returns `length` bytes starting at `offset`, copied when read on demand.
+   * Unlike `bytes`, nothing stays borrowed from a lazy buffer, which can evict
+   * the part of the file it read them from. Meant for the small values read
+   * while parsing, e.g. Private Creators.
+   */
+  pub fn read_bytes(&self, offset: usize, length: usize) -> Result<Cow<'_, [u8]>, DicomError> {
+    match self {
+      #[cfg(not(target_arch = "wasm32"))]
+      Storage::Lazy(lazy) => {
+        self.check_range(offset, length)?;
+        let mut buffer = vec![0; length];
+        lazy.copy(offset, &mut buffer)?;
+        Ok(Cow::Owned(buffer))
+      }
+      _ => Ok(Cow::Borrowed(self.bytes(offset, length)?)),
+    }
+  }
+
+  /**
+   * Returns a copy of the `N` bytes starting at `offset`. Unlike `bytes`, nothing
+   * stays borrowed from the storage, which lets a lazy buffer evict the part of
+   * the file it read them from.
+   */
+  pub fn read_array<const N: usize>(&self, offset: usize) -> Result<[u8; N], DicomError> {
+    match self {
+      #[cfg(not(target_arch = "wasm32"))]
+      Storage::Lazy(lazy) => {
+        self.check_range(offset, N)?;
+        lazy.read_array(offset)
+      }
+      _ => Ok(self.bytes(offset, N)?.try_into()?),
+    }
+  }
+
+  // Returns the end of the range, if within the file bounds
+  fn check_range(&self, offset: usize, length: usize) -> Result<usize, DicomError> {
+    match offset.checked_add(length) {
+      Some(end) if end <= self.len() => Ok(end),
+      _ => Err(
+        DicomError::with_kind(
+          DicomErrorKind::Truncated,
+          &format!(
+            "Trying to read out of file bound (length: {}, file size: {})",
+            length,
+            self.len()
+          ),
+        )
+        .at(offset),
+      ),
+    }
+  }
+
+  pub fn read_u16(&self, offset: usize, byte_order: ByteOrder) -> Result<u16, DicomError> {
+    let bytes = self.read_array(offset)?;
+    Ok(match byte_order {
+      ByteOrder::LittleEndian => u16::from_le_bytes(bytes),
+      ByteOrder::BigEndian => u16::from_be_bytes(bytes),
+    })
+  }
+
+  pub fn read_u32(&self, offset: usize, byte_order: ByteOrder) -> Result<u32, DicomError> {
+    let bytes = self.read_array(offset)?;
+    Ok(match byte_order {
+      ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
+      ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+    })
+  }
+}
+
+impl<'a> fmt::Debug for Storage<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let kind = match self {
+      Storage::Owned(_) => "Owned",
+      Storage::Borrowed(_) => "Borrowed",
+      #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+      Storage::Mapped(_) => "Mapped",
+      #[cfg(not(target_arch = "wasm32"))]
+      Storage::Lazy(_) => "Lazy",
+    };
+    write!(f, "Storage::{}({} bytes)", kind, self.len())
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub trait ReadSeek: Read + Seek {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Read + Seek> ReadSeek for T {}
+
+// Amount of bytes read at once from the reader. Attribute headers are small
+// and close to each other, reading them one by one would be too slow. Chunks
+// start on a multiple of CHUNK_SIZE.
+#[cfg(not(target_arch = "wasm32"))]
+const CHUNK_SIZE: usize = 4 * 1024;
+
+// Maximum amount of bytes kept for the reads to come, beyond which the least
+// recently used chunks are evicted. The chunks values were borrowed from are
+// not counted, as they can not be evicted.
+#[cfg(not(target_arch = "wasm32"))]
+const CACHE_SIZE: usize = 256 * CHUNK_SIZE;
+
+// A part of the file, one or more CHUNK_SIZE long (or up to the end of file)
+#[cfg(not(target_arch = "wasm32"))]
+struct Chunk {
+  data: Box<[u8]>,
+  // A slice of the chunk was handed out by `bytes`, it must not be dropped
+  borrowed: bool,
+  // Value of Cache::clock the last time the chunk was read from
+  last_used: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Cache {
+  // Chunks read so far by offset of their start. They never overlap.
+  chunks: BTreeMap<usize, Chunk>,
+  // Borrowed chunks merged into larger ones, kept until the buffer is dropped
+  merged: Vec<Box<[u8]>>,
+  // Size of the chunks which are not borrowed
+  size: usize,
+  clock: u64,
+}
+
+/**
+ * Reads the bytes of a file on demand.
+ * Only the parts of the file which are actually accessed are read, so that
+ * walking over the attributes does not load the bulk data (e.g. PixelData)
+ * they contain.
+ *
+ * The slices returned by `bytes` borrow the buffer while it reads more of the
+ * file through a shared reference, hence the RefCells: a LazyBuffer (and the
+ * Instance reading from it) can not be shared between threads.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub struct LazyBuffer {
+  reader: RefCell<Box<dyn ReadSeek>>,
+  length: usize,
+  cache: RefCell<Cache>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LazyBuffer {
+  pub fn new<T: Read + Seek + 'static>(mut reader: T) -> Result<Self, DicomError> {
+    let length = reader.seek(SeekFrom::End(0))? as usize;
+    Ok(LazyBuffer {
+      reader: RefCell::new(Box::new(reader)),
+      length,
+      cache: RefCell::new(Cache::default()),
+    })
+  }
+
+  // The range is expected to have been checked against the file size.
+  fn bytes(&self, offset: usize, length: usize) -> Result<&[u8], DicomError> {
+    let mut cache = self.cache.borrow_mut();
+    let start = self.load(&mut cache, offset, offset + length)?;
+    let Cache { chunks, size, .. } = &mut *cache;
+    let chunk = chunks
+      .get_mut(&start)
+      .ok_or_else(|| DicomError::new("Chunk not cached"))?;
+    if !chunk.borrowed {
+      chunk.borrowed = true;
+      *size -= chunk.data.len();
+    }
+    let ptr = chunk.data[offset - start..].as_ptr();
+    // Safety: the slice outlives the borrow of the cache but not the data it
+    // points to. The data of a chunk is boxed, so it does not move when the map
+    // is modified, and a borrowed chunk is never dropped before self: it is
+    // neither evicted nor dropped when merged (see `load`).
+    Ok(unsafe { core::slice::from_raw_parts(ptr, length) })
+  }
+
+  // The range is expected to have been checked against the file size.
+  fn read_array<const N: usize>(&self, offset: usize) -> Result<[u8; N], DicomError> {
+    let mut array = [0; N];
+    self.copy(offset, &mut array)?;
+    Ok(array)
+  }
+
+  // Copies the bytes starting at `offset` to `buffer`, without pinning the
+  // chunk they are read from. The range is expected to have been checked
+  // against the file size.
+  fn copy(&self, offset: usize, buffer: &mut [u8]) -> Result<(), DicomError> {
+    let mut cache = self.cache.borrow_mut();
+    let start = self.load(&mut cache, offset, offset + buffer.len())?;
+    let chunk = cache
+      .chunks
+      .get(&start)
+      .ok_or_else(|| DicomError::new("Chunk not cached"))?;
+    buffer.copy_from_slice(&chunk.data[offset - start..offset - start + buffer.len()]);
+    Ok(())
+  }
+
+  // Caches the chunks containing the range [offset, end) if not already done
+  // and returns the start of the chunk holding the range. Reading a range
+  // spanning several chunks merges them in a single one, only reading what is
+  // not cached yet.
+  fn load(&self, cache: &mut Cache, offset: usize, end: usize) -> Result<usize, DicomError> {
+    cache.clock += 1;
+    let clock = cache.clock;
+    if let Some((&start, chunk)) = cache.chunks.range_mut(..=offset).next_back() {
+      if start + chunk.data.len() >= end {
+        chunk.last_used = clock;
+        return Ok(start);
+      }
+    }
+    let mut start = offset / CHUNK_SIZE * CHUNK_SIZE;
+    let mut stop = (end.div_ceil(CHUNK_SIZE) * CHUNK_SIZE).min(self.length);
+    // Chunks are sorted and do not overlap
+    let overlapping = cache
+      .chunks
+      .range(..stop)
+      .rev()
+      .take_while(|(&chunk_start, chunk)| chunk_start + chunk.data.len() > start)
+      .map(|(&chunk_start, chunk)| (chunk_start, chunk_start + chunk.data.len()))
+      .collect::<Vec<(usize, usize)>>();
+    for &(chunk_start, chunk_end) in &overlapping {
+      start = start.min(chunk_start);
+      stop = stop.max(chunk_end);
+    }
+    let mut data = vec![0u8; stop - start].into_boxed_slice();
+    let mut position = start;
+    for &(chunk_start, chunk_end) in overlapping.iter().rev() {
+      self.read_at(position, &mut data[position - start..chunk_start - start])?;
+      let chunk = cache
+        .chunks
+        .remove(&chunk_start)
+        .ok_or_else(|| DicomError::new("Chunk not cached"))?;
+      data[chunk_start - start..chunk_end - start].copy_from_slice(&chunk.data);
+      if chunk.borrowed {
+        cache.merged.push(chunk.data);
+      } else {
+        cache.size -= chunk.data.len();
+      }
+      position = chunk_end;
+    }
+    self.read_at(position, &mut data[position - start..])?;
+    cache.size += data.len();
+    cache.chunks.insert(
+      start,
+      Chunk {
+        data,
+        borrowed: false,
+        last_used: clock,
+      },
+    );
+    cache.evict(start);
+    Ok(start)
+  }
+
+  fn read_at(&self, offset: usize, buffer: &mut [u8]) -> Result<(), DicomError> {
+    if !buffer.is_empty() {
+      let mut reader = self.reader.borrow_mut();
+      reader.seek(SeekFrom::Start(offset as u64))?;
+      reader.read_exact(buffer)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Cache {
+  // Drops the least recently used chunks which are not borrowed, except the
+  // one starting at `keep`, until the cache fits in CACHE_SIZE.
+  fn evict(&mut self, keep: usize) {
+    while self.size > CACHE_SIZE {
+      let Some(start) = self
+        .chunks
+        .iter()
+        .filter(|(&start, chunk)| start != keep && !chunk.borrowed)
+        .min_by_key(|(_, chunk)| chunk.last_used)
+        .map(|(&start, _)| start)
+      else {
+        return;
+      };
+      if let Some(chunk) = self.chunks.remove(&start) {
+        self.size -= chunk.data.len();
+      }
+    }
+  }
+}
+
+/**
+ * Reads a DICOM file from a reader up to the top level attribute `stop_at`
+ * (excluded) and returns the bytes read.
+ * The attributes are parsed while being read so that nothing is consumed from
+ * the reader past the header of the `stop_at` attribute. Deflated datasets
+ * can not be parsed on the fly and are read entirely.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_until<R: Read>(reader: &mut R, stop_at: &Tag) -> Result<Vec<u8>, DicomError> {
+  let mut stream = Stream {
+    reader,
+    buffer: vec![],
+  };
+  let (mut offset, transfer_syntax) = read_file_meta(&mut stream)?;
+  if transfer_syntax.deflated {
+    stream.reader.read_to_end(&mut stream.buffer)?;
+    return Ok(stream.buffer);
+  }
+  let TransferSyntax {
+    implicit,
+    byte_order,
+    ..
+  } = transfer_syntax;
+  while stream.fill(offset + 4)? {
+    let group = get_u16(&mut stream, offset, byte_order)?;
+    let element = get_u16(&mut stream, offset + 2, byte_order)?;
+    if (group, element) >= (stop_at.group, stop_at.element) {
+      break;
+    }
+    (_, _, offset) = skip_attribute(&mut stream, offset, implicit, byte_order)?;
+  }
+  // Bytes read ahead (e.g. the stop_at attribute tag) are not part of the dataset
+  stream.buffer.truncate(offset);
+  Ok(stream.buffer)
+}
+
+/**
+ * Returns the transfer syntax of a DICOM file, reading nothing more than its
+ * file meta information (or the beginning of the dataset to guess it when
+ * there is none).
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_transfer_syntax<R: Read>(reader: &mut R) -> Result<TransferSyntax, DicomError> {
+  let mut stream = Stream {
+    reader,
+    buffer: vec![],
+  };
+  Ok(read_file_meta(&mut stream)?.1)
+}
+
+// Reads the file meta information, if any, and returns the offset of the
+// dataset along with its transfer syntax.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file_meta<R: Read>(stream: &mut Stream<R>) -> Result<(usize, TransferSyntax), DicomError> {
+  let mut offset = 0;
+  let mut transfer_syntax = None;
+  if stream.fill(128 + 4)? && &stream.buffer[128..128 + 4] == b"DICM" {
+    offset = 128 + 4;
+    // The file meta information group is always explicit little endian
+    while stream.fill(offset + 2)? && get_u16(stream, offset, ByteOrder::LittleEndian)? == 0x0002 {
+      let (_, element, next_offset) =
+        skip_attribute(stream, offset, false, ByteOrder::LittleEndian)?;
+      if element == 0x0010 {
+        // TransferSyntaxUID value starts after the 8 bytes of header
+        let value = &stream.buffer[offset + 8..next_offset];
+        transfer_syntax = Some(TransferSyntax::try_from(core::str::from_utf8(value)?)?);
+      }
+      offset = next_offset;
+    }
+  }
+  let transfer_syntax = match transfer_syntax {
+    Some(transfer_syntax) => transfer_syntax,
+    None => {
+      stream.fill(offset + DICOM_PROBE_SIZE)?;
+      guess_transfer_syntax(&stream.buffer[offset..]).ok_or_else(|| {
+        DicomError::with_kind(
+          DicomErrorKind::NotDicom,
+          "Could not determine the transfer syntax of the dataset",
+        )
+      })?
+    }
+  };
+  Ok((offset, transfer_syntax))
+}
+
+// Keeps the bytes read from a reader while parsing them.
+#[cfg(not(target_arch = "wasm32"))]
+struct Stream<'a, R: Read> {
+  reader: &'a mut R,
+  buffer: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, R: Read> Stream<'a, R> {
+  // Reads from the reader until the buffer is `length` long. Returns false if
+  // the end of the reader is reached before.
+  fn fill(&mut self, length: usize) -> Result<bool, DicomError> {
+    if self.buffer.len() < length {
+      let missing = (length - self.buffer.len()) as u64;
+      (&mut *self.reader)
+        .take(missing)
+        .read_to_end(&mut self.buffer)?;
+    }
+    Ok(self.buffer.len() >= length)
+  }
+}
+
+/**
+ * The bytes `skip_attribute` walks through.
+ */
+pub(crate) trait Source {
+  // Returns `length` bytes starting at `offset`.
+  fn get(&mut self, offset: usize, length: usize) -> Result<Cow<'_, [u8]>, DicomError>;
+  // Checks that the file is at least `length` long, without reading it if
+  // possible.
+  fn skip_to(&mut self, length: usize) -> Result<(), DicomError>;
+}
+
+impl<'a> Source for &Storage<'a> {
+  // The headers of all the attributes go through here, they must not pin the
+  // chunks of a lazy buffer
+  fn get(&mut self, offset: usize, length: usize) -> Result<Cow<'_, [u8]>, DicomError> {
+    self.read_bytes(offset, length)
+  }
+
+  fn skip_to(&mut self, length: usize) -> Result<(), DicomError> {
+    if length <= self.len() {
+      Ok(())
+    } else {
+      Err(
+        DicomError::with_kind(
+          DicomErrorKind::Truncated,
+          &format!("Unexpected end of file (file size: {})", self.len()),
+        )
+        .at(length),
+      )
+    }
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, R: Read> Source for Stream<'a, R> {
+  fn get(&mut self, offset: usize, length: usize) -> Result<Cow<'_, [u8]>, DicomError> {
+    self.skip_to(offset + length)?;
+    Ok(Cow::Borrowed(&self.buffer[offset..offset + length]))
+  }
+
+  // A stream can not be skipped, the bytes have to be read
+  fn skip_to(&mut self, length: usize) -> Result<(), DicomError> {
+    if self.fill(length)? {
+      Ok(())
+    } else {
+      Err(
+        DicomError::with_kind(DicomErrorKind::Truncated, "Unexpected end of file")
+          .at(self.buffer.len()),
+      )
+    }
+  }
+}
+
+fn get_u16<S: Source>(
+  source: &mut S,
+  offset: usize,
+  byte_order: ByteOrder,
+) -> Result<u16, DicomError> {
+  let bytes = source.get(offset, 2)?.as_ref().try_into()?;
+  Ok(match byte_order {
+    ByteOrder::LittleEndian => u16::from_le_bytes(bytes),
+    ByteOrder::BigEndian => u16::from_be_bytes(bytes),
+  })
+}
+
+fn get_u32<S: Source>(
+  source: &mut S,
+  offset: usize,
+  byte_order: ByteOrder,
+) -> Result<u32, DicomError> {
+  let bytes = source.get(offset, 4)?.as_ref().try_into()?;
+  Ok(match byte_order {
+    ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
+    ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+  })
+}
+
+/**
+ * Walks over the attribute at offset, including the items of sequences of
+ * undefined length, and returns its group, element and the offset of the
+ * following attribute.
+ * Only the attribute headers are read, values are skipped. Unlike
+ * `Instance::next_attribute`, the attribute is not interpreted.
+ */
+pub(crate) fn skip_attribute<S: Source>(
+  source: &mut S,
+  offset: usize,
+  implicit: bool,
+  byte_order: ByteOrder,
+) -> Result<(u16, u16, usize), DicomError> {
+  skip_nested_attribute(source, offset, implicit, byte_order, 0)
+}
+
+// `depth` is the number of sequences and items the attribute is nested in
+fn skip_nested_attribute<S: Source>(
+  source: &mut S,
+  offset: usize,
+  implicit: bool,
+  byte_order: ByteOrder,
+  depth: usize,
+) -> Result<(u16, u16, usize), DicomError> {
+  if depth > MAX_NESTING_DEPTH {
+    return Err(
+      DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!(
+          "Sequences nested more than {} levels deep",
+          MAX_NESTING_DEPTH
+        ),
+      )
+      .at(offset),
+    );
+  }
+  let group = get_u16(source, offset, byte_order)?;
+  let element = get_u16(source, offset + 2, byte_order)?;
+  let (length, mut offset) = if group == 0xFFFE || implicit {
+    (get_u32(source, offset + 4, byte_order)?, offset + 8)
+  } else {
+    let vr = ValueRepresentation::try_from(source.get(offset + 4, 2)?.as_ref())?;
+    if vr.has_long_length() {
+      (get_u32(source, offset + 8, byte_order)?, offset + 12)
+    } else {
+      (get_u16(source, offset + 6, byte_order)? as u32, offset + 8)
+    }
+  };
+  if length == 0xFFFFFFFF {
+    // Items (or fragments) until the delimitation item
+    let delimiter = if group == 0xFFFE { 0xE00D } else { 0xE0DD };
+    loop {
+      let (subgroup, subelement, suboffset) =
+        skip_nested_attribute(source, offset, implicit, byte_order, depth + 1)?;
+      offset = suboffset;
+      if subgroup == 0xFFFE && subelement == delimiter {
+        break;
+      }
+    }
+  } else {
+    offset = offset.checked_add(length as usize).ok_or_else(|| {
+      DicomError::with_kind(
+        DicomErrorKind::Truncated,
+        &format!("Attribute length {} goes past the end of the file", length),
+      )
+      .at(offset)
+    })?;
+    source.skip_to(offset)?;
+  }
+  Ok((group, element, offset))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+  use std::io::Cursor;
+  use std::rc::Rc;
+
+  // Counts the bytes read from the file
+  struct CountingReader {
+    cursor: Cursor<Vec<u8>>,
+    read: Rc<Cell<usize>>,
+  }
+
+  impl Read for CountingReader {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+      let count = self.cursor.read(buffer)?;
+      self.read.set(self.read.get() + count);
+      Ok(count)
+    }
+  }
+
+  impl Seek for CountingReader {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+      self.cursor.seek(position)
+    }
+  }
+
+  fn lazy(length: usize) -> (Vec<u8>, Storage<'static>, Rc<Cell<usize>>) {
+    let content = (0..length).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+    let read = Rc::new(Cell::new(0));
+    let reader = CountingReader {
+      cursor: Cursor::new(content.clone()),
+      read: read.clone(),
+    };
+    let storage = Storage::Lazy(LazyBuffer::new(reader).unwrap());
+    (content, storage, read)
+  }
+
+  fn cache<'a>(storage: &'a Storage) -> core::cell::Ref<'a, Cache> {
+    match storage {
+      Storage::Lazy(lazy) => lazy.cache.borrow(),
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn chunks_are_aligned_and_reused() {
+    let (content, storage, read) = lazy(3 * CHUNK_SIZE + 100);
+    assert_eq!(storage.bytes(10, 4).unwrap(), &content[10..14]);
+    assert_eq!(read.get(), CHUNK_SIZE);
+    assert_eq!(
+      storage.read_u32(100, ByteOrder::LittleEndian).unwrap(),
+      0x67666564
+    );
+    assert_eq!(read.get(), CHUNK_SIZE);
+    // The second chunk is read and merged with the first one
+    let end = CHUNK_SIZE + 6;
+    assert_eq!(
+      storage.bytes(CHUNK_SIZE - 6, 12).unwrap(),
+      &content[CHUNK_SIZE - 6..end]
+    );
+    assert_eq!(read.get(), 2 * CHUNK_SIZE);
+    // The last chunk stops at the end of the file
+    let length = content.len() - 3 * CHUNK_SIZE;
+    assert_eq!(
+      storage.bytes(3 * CHUNK_SIZE, length).unwrap(),
+      &content[3 * CHUNK_SIZE..]
+    );
+    assert_eq!(read.get(), 2 * CHUNK_SIZE + 100);
+    let starts = cache(&storage)
+      .chunks
+      .keys()
+      .copied()
+      .collect::<Vec<usize>>();
+    assert_eq!(starts, [0, 3 * CHUNK_SIZE]);
+    assert!(storage.bytes(content.len() - 1, 2).is_err());
+  }
+
+  #[test]
+  fn cache_is_capped() {
+    let length = CACHE_SIZE + 16 * CHUNK_SIZE;
+    let (content, storage, read) = lazy(length);
+    let borrowed = storage.bytes(5, 3).unwrap();
+    for offset in (0..length).step_by(CHUNK_SIZE) {
+      storage.read_array::<2>(offset).unwrap();
+    }
+    assert_eq!(read.get(), length);
+    assert_eq!(cache(&storage).size, CACHE_SIZE);
+    // The borrowed chunk is not evicted, the least recently used ones are
+    assert_eq!(borrowed, &content[5..8]);
+    assert!(cache(&storage).chunks.contains_key(&0));
+    assert!(!cache(&storage).chunks.contains_key(&CHUNK_SIZE));
+    storage.read_array::<2>(CHUNK_SIZE).unwrap();
+    assert_eq!(read.get(), length + CHUNK_SIZE);
+    assert_eq!(cache(&storage).size, CACHE_SIZE);
+  }
+
+  // Explicit VR little endian attribute
+  fn attribute(group: u16, element: u16, vr: ValueRepresentation, value: &[u8]) -> Vec<u8> {
+    let mut attribute = [group.to_le_bytes(), element.to_le_bytes()].concat();
+    attribute.extend_from_slice(vr.to_string().as_bytes());
+    if vr.has_long_length() {
+      attribute.extend_from_slice(&[0, 0]);
+      attribute.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    } else {
+      attribute.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    }
+    attribute.extend_from_slice(value);
+    attribute
+  }
+
+  #[test]
+  fn walking_a_large_file_stays_in_the_cache() {
+    // Private attributes, which need their Private Creator to be read, in a
+    // dataset twice as large as the cache
+    let mut dataset = attribute(0x0008, 0x0005, ValueRepresentation::CS, b"ISO_IR 100");
+    for group in (0x0009..0x0019).step_by(2) {
+      dataset.extend(attribute(group, 0x0010, ValueRepresentation::LO, b"ACME"));
+      for element in 0x1000..0x1040 {
+        dataset.extend(attribute(
+          group,
+          element,
+          ValueRepresentation::UN,
+          &[0; CHUNK_SIZE],
+        ));
+      }
+    }
+    dataset.extend(attribute(0x0028, 0x0010, ValueRepresentation::US, &[8, 0]));
+    assert!(dataset.len() > 2 * CACHE_SIZE);
+    let instance = crate::instance::Instance::from_reader(Cursor::new(dataset)).unwrap();
+    assert_eq!(instance.walk().count(), 8 * 65 + 2);
+    // Indexes the whole file
+    assert!(instance
+      .get_value(&crate::dicom_tags::PatientName)
+      .unwrap()
+      .is_none());
+    let cache = cache(&instance.buffer);
+    assert!(cache.chunks.values().all(|chunk| !chunk.borrowed));
+    assert!(cache.merged.is_empty());
+    assert!(cache.size <= CACHE_SIZE);
+  }
+
+  #[test]
+  fn transfer_syntax_from_file_meta() {
+    let file = include_bytes!("../tests/data/deflated.dcm");
+    let mut reader = &file[..];
+    assert_eq!(
+      read_transfer_syntax(&mut reader).unwrap(),
+      crate::transfer_syntax::DeflatedExplicitVRLittleEndian
+    );
+    // The deflated dataset is left in the reader
+    assert!(reader.len() > file.len() / 2);
+    let mut reader = &include_bytes!("../tests/data/acrnema_implicit.dcm")[..];
+    assert_eq!(
+      read_transfer_syntax(&mut reader).unwrap(),
+      crate::transfer_syntax::ImplicitVRLittleEndian
+    );
+  }
+}
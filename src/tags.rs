@@ -23,18 +23,45 @@
 
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
 use core::hash;
 
+use crate::error::{DicomError, DicomErrorKind};
+use crate::value_multiplicity::ValueMultiplicity;
+use crate::value_representation::ValueRepresentation;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Tag {
   pub group: u16,
   pub element: u16,
   pub name: &'static str,
-  pub vr: &'static str,
-  pub vm: core::ops::Range<u16>,
+  pub vr: ValueRepresentation,
+  pub vm: ValueMultiplicity,
+  // Retired from the standard, kept to read older instances
+  pub retired: bool,
   pub description: &'static str,
 }
 
+impl Tag {
+  /**
+   * A tag absent from the dictionaries, e.g. a private attribute whose creator
+   * is unknown.
+   */
+  pub fn unknown(group: u16, element: u16) -> Tag {
+    Tag {
+      group,
+      element,
+      name: "Unknown Tag & Data",
+      vr: ValueRepresentation::UN,
+      vm: ValueMultiplicity::at_least(1),
+      retired: false,
+      description: "Unknown Tag & Data",
+    }
+  }
+}
+
 impl hash::Hash for Tag {
   fn hash<H: hash::Hasher>(&self, state: &mut H) {
     self.group.hash(state);
@@ -47,3 +74,191 @@ impl ToString for Tag {
     format!("{:04x}{:04x}", self.group, self.element)
   }
 }
+
+/**
+ * Path to an attribute nested in sequences.
+ *
+ * A path is a list of tags separated by dots, each tag being expressed either by
+ * its keyword or by its group and element in hexadecimal. Sequences can be
+ * followed by the index of an item between brackets:
+ *   0040A730[2].0040A168[0].00080100
+ *   ContentSequence[2].ConceptCodeSequence[0].CodeValue
+ * Without an index, all the items of the sequence are searched.
+ * A path ending with an index points to an item, as given by Instance::walk:
+ *   ContentSequence[2]
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct TagPath {
+  pub components: Vec<TagPathComponent>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct TagPathComponent {
+  pub tag: Tag,
+  // Index of the item in the sequence. Only meaningful on sequences.
+  pub item: Option<usize>,
+}
+
+impl TagPath {
+  /**
+   * The tag of the attribute the path points to.
+   */
+  pub fn tag(&self) -> &Tag {
+    // A path is never empty
+    &self.components[self.components.len() - 1].tag
+  }
+
+  /**
+   * Parses a tag in a path. Unlike Tag::try_from, this accepts tags absent from
+   * the dictionary (e.g. private tags) when expressed in hexadecimal.
+   */
+  fn parse_tag(tag: &str) -> Result<Tag, DicomError> {
+    if let Ok(tag) = Tag::try_from(tag) {
+      return Ok(tag);
+    }
+    if tag.len() == 8 && tag.chars().all(|c| c.is_ascii_hexdigit()) {
+      let value = u32::from_str_radix(tag, 16)?;
+      return Ok(Tag::unknown((value >> 16) as u16, (value & 0xFFFF) as u16));
+    }
+    Err(DicomError::with_kind(
+      DicomErrorKind::UnknownTag,
+      &format!("Unknown tag {}", tag),
+    ))
+  }
+}
+
+impl TryFrom<&str> for TagPath {
+  type Error = DicomError;
+
+  fn try_from(path: &str) -> Result<Self, Self::Error> {
+    let mut components = vec![];
+    for component in path.split('.') {
+      let (tag, item) = match component.split_once('[') {
+        Some((tag, index)) => {
+          let index = index.strip_suffix(']').ok_or_else(|| {
+            DicomError::with_kind(
+              DicomErrorKind::UnknownTag,
+              &format!("Missing closing bracket in {} ({})", component, path),
+            )
+          })?;
+          let index = index.parse::<usize>().map_err(|_| {
+            DicomError::with_kind(
+              DicomErrorKind::UnknownTag,
+              &format!("Invalid item index {} in {}", index, path),
+            )
+          })?;
+          (tag, Some(index))
+        }
+        None => (component, None),
+      };
+      if tag.is_empty() {
+        return Err(DicomError::with_kind(
+          DicomErrorKind::UnknownTag,
+          &format!("Empty tag in {}", path),
+        ));
+      }
+      components.push(TagPathComponent {
+        tag: TagPath::parse_tag(tag)?,
+        item,
+      });
+    }
+    Ok(TagPath { components })
+  }
+}
+
+impl TryFrom<&String> for TagPath {
+  type Error = DicomError;
+
+  fn try_from(path: &String) -> Result<Self, Self::Error> {
+    TagPath::try_from(path.as_str())
+  }
+}
+
+impl From<Tag> for TagPath {
+  fn from(tag: Tag) -> Self {
+    TagPath {
+      components: vec![TagPathComponent { tag, item: None }],
+    }
+  }
+}
+
+impl fmt::Display for TagPath {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, component) in self.components.iter().enumerate() {
+      if i > 0 {
+        write!(f, ".")?;
+      }
+      write!(
+        f,
+        "{:04X}{:04X}",
+        component.tag.group, component.tag.element
+      )?;
+      if let Some(item) = component.item {
+        write!(f, "[{}]", item)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn path_with_item_indices() {
+    let path = TagPath::try_from("ContentSequence[2].0040A168[0].CodeValue").unwrap();
+    let components = path
+      .components
+      .iter()
+      .map(|component| (component.tag.group, component.tag.element, component.item))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      components,
+      [
+        (0x0040, 0xA730, Some(2)),
+        (0x0040, 0xA168, Some(0)),
+        (0x0008, 0x0100, None)
+      ]
+    );
+    assert_eq!(path.tag().name, "CodeValue");
+    assert_eq!(path.to_string(), "0040A730[2].0040A168[0].00080100");
+  }
+
+  #[test]
+  fn path_to_an_item() {
+    // As given by Instance::walk for the items
+    let path = TagPath::try_from("0040A730[1]").unwrap();
+    assert_eq!(path.components[0].item, Some(1));
+    assert_eq!(TagPath::try_from(&path.to_string()).unwrap(), path);
+  }
+
+  #[test]
+  fn private_tags_in_hexadecimal() {
+    let path = TagPath::try_from("00091010[0].00091001").unwrap();
+    assert_eq!(path.tag().group, 0x0009);
+    assert_eq!(path.tag().element, 0x1001);
+    assert_eq!(path.to_string(), "00091010[0].00091001");
+  }
+
+  #[test]
+  fn malformed_paths() {
+    for path in [
+      "",
+      ".PatientName",
+      "ContentSequence.",
+      "ContentSequence[",
+      "ContentSequence[1",
+      "ContentSequence[1]x",
+      "ContentSequence[]",
+      "ContentSequence[-1]",
+      "ContentSequence[a].CodeValue",
+      "[0]",
+      "NotATag",
+      "0009100G",
+    ] {
+      let error = TagPath::try_from(path).unwrap_err();
+      assert_eq!(error.kind, DicomErrorKind::UnknownTag, "{}", path);
+    }
+  }
+}
@@ -0,0 +1,288 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/chapter_10.html
+//
+// How the transfer syntaxes of the UID registry encode a dataset: byte order,
+// implicit or explicit VR, deflated dataset and encapsulated pixel data. This
+// is the only place where the parser, the writer and the tools find out what a
+// transfer syntax UID means.
+// The XML and MIME encodings (retired) are not datasets and are left out, so
+// that they are reported as unsupported.
+
+#![allow(non_upper_case_globals)]
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::error::{DicomError, DicomErrorKind};
+use crate::instance::ByteOrder;
+use crate::uids::{self, Uid};
+
+/**
+ * A transfer syntax and the way it encodes a dataset.
+ */
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct TransferSyntax {
+  pub uid: &'static Uid,
+  // Byte order of the dataset. The file meta information is always little endian.
+  pub byte_order: ByteOrder,
+  // The VRs are not written in the dataset and come from the dictionary
+  pub implicit: bool,
+  // The dataset following the file meta information is deflated
+  pub deflated: bool,
+  // The pixel data is encapsulated in fragments (compressed or not)
+  pub encapsulated: bool,
+  // The compression of the pixel data loses information
+  pub lossy: bool,
+  // The pixel data can be read as is or decoded by rdicom
+  pub codec_available: bool,
+}
+
+// Pixel data stored as is (native format)
+const fn native(
+  uid: &'static Uid,
+  byte_order: ByteOrder,
+  implicit: bool,
+  deflated: bool,
+) -> TransferSyntax {
+  TransferSyntax {
+    uid,
+    byte_order,
+    implicit,
+    deflated,
+    encapsulated: false,
+    lossy: false,
+    codec_available: true,
+  }
+}
+
+// Explicit VR little endian with encapsulated pixel data. There is no codec in
+// rdicom, the fragments are only available as they are.
+const fn encapsulated(uid: &'static Uid, lossy: bool) -> TransferSyntax {
+  TransferSyntax {
+    uid,
+    byte_order: ByteOrder::LittleEndian,
+    implicit: false,
+    deflated: false,
+    encapsulated: true,
+    lossy,
+    codec_available: false,
+  }
+}
+
+// JPIP: the pixel data is not in the dataset but retrieved from a JPIP server
+const fn referenced(uid: &'static Uid, deflated: bool) -> TransferSyntax {
+  TransferSyntax {
+    codec_available: false,
+    ..native(uid, ByteOrder::LittleEndian, false, deflated)
+  }
+}
+
+pub const ImplicitVRLittleEndian: TransferSyntax = native(
+  &uids::ImplicitVRLittleEndian,
+  ByteOrder::LittleEndian,
+  true,
+  false,
+);
+pub const ExplicitVRLittleEndian: TransferSyntax = native(
+  &uids::ExplicitVRLittleEndian,
+  ByteOrder::LittleEndian,
+  false,
+  false,
+);
+pub const DeflatedExplicitVRLittleEndian: TransferSyntax = native(
+  &uids::DeflatedExplicitVRLittleEndian,
+  ByteOrder::LittleEndian,
+  false,
+  true,
+);
+pub const ExplicitVRBigEndian: TransferSyntax = native(
+  &uids::ExplicitVRBigEndian,
+  ByteOrder::BigEndian,
+  false,
+  false,
+);
+
+static TRANSFER_SYNTAXES: &[TransferSyntax] = &[
+  ImplicitVRLittleEndian,
+  ExplicitVRLittleEndian,
+  encapsulated(&uids::EncapsulatedUncompressedExplicitVRLittleEndian, false),
+  DeflatedExplicitVRLittleEndian,
+  ExplicitVRBigEndian,
+  encapsulated(&uids::JPEGBaseline8Bit, true),
+  encapsulated(&uids::JPEGExtended12Bit, true),
+  encapsulated(&uids::JPEGExtended35, true),
+  encapsulated(&uids::JPEGSpectralSelectionNonHierarchical68, true),
+  encapsulated(&uids::JPEGSpectralSelectionNonHierarchical79, true),
+  encapsulated(&uids::JPEGFullProgressionNonHierarchical1012, true),
+  encapsulated(&uids::JPEGFullProgressionNonHierarchical1113, true),
+  encapsulated(&uids::JPEGLossless, false),
+  encapsulated(&uids::JPEGLosslessNonHierarchical15, false),
+  encapsulated(&uids::JPEGExtendedHierarchical1618, true),
+  encapsulated(&uids::JPEGExtendedHierarchical1719, true),
+  encapsulated(&uids::JPEGSpectralSelectionHierarchical2022, true),
+  encapsulated(&uids::JPEGSpectralSelectionHierarchical2123, true),
+  encapsulated(&uids::JPEGFullProgressionHierarchical2426, true),
+  encapsulated(&uids::JPEGFullProgressionHierarchical2527, true),
+  encapsulated(&uids::JPEGLosslessHierarchical28, false),
+  encapsulated(&uids::JPEGLosslessHierarchical29, false),
+  encapsulated(&uids::JPEGLosslessSV1, false),
+  encapsulated(&uids::JPEGLSLossless, false),
+  encapsulated(&uids::JPEGLSNearLossless, true),
+  encapsulated(&uids::JPEG2000Lossless, false),
+  // Lossless or lossy, the transfer syntax does not tell
+  encapsulated(&uids::JPEG2000, true),
+  encapsulated(&uids::JPEG2000MCLossless, false),
+  encapsulated(&uids::JPEG2000MC, true),
+  referenced(&uids::JPIPReferenced, false),
+  referenced(&uids::JPIPReferencedDeflate, true),
+  encapsulated(&uids::MPEG2MPML, true),
+  encapsulated(&uids::MPEG2MPMLF, true),
+  encapsulated(&uids::MPEG2MPHL, true),
+  encapsulated(&uids::MPEG2MPHLF, true),
+  encapsulated(&uids::MPEG4HP41, true),
+  encapsulated(&uids::MPEG4HP41F, true),
+  encapsulated(&uids::MPEG4HP41BD, true),
+  encapsulated(&uids::MPEG4HP41BDF, true),
+  encapsulated(&uids::MPEG4HP422D, true),
+  encapsulated(&uids::MPEG4HP422DF, true),
+  encapsulated(&uids::MPEG4HP423D, true),
+  encapsulated(&uids::MPEG4HP423DF, true),
+  encapsulated(&uids::MPEG4HP42STEREO, true),
+  encapsulated(&uids::MPEG4HP42STEREOF, true),
+  encapsulated(&uids::HEVCMP51, true),
+  encapsulated(&uids::HEVCM10P51, true),
+  encapsulated(&uids::JPEGXLLossless, false),
+  // The recompressed JPEG was lossy in the first place
+  encapsulated(&uids::JPEGXLJPEGRecompression, true),
+  encapsulated(&uids::JPEGXL, true),
+  encapsulated(&uids::HTJ2KLossless, false),
+  encapsulated(&uids::HTJ2KLosslessRPCL, false),
+  encapsulated(&uids::HTJ2K, true),
+  referenced(&uids::JPIPHTJ2KReferenced, false),
+  referenced(&uids::JPIPHTJ2KReferencedDeflate, true),
+  encapsulated(&uids::RLELossless, false),
+  encapsulated(
+    &uids::SMPTEST211020UncompressedProgressiveActiveVideo,
+    false,
+  ),
+  encapsulated(&uids::SMPTEST211020UncompressedInterlacedActiveVideo, false),
+  encapsulated(&uids::SMPTEST211030PCMDigitalAudio, false),
+  encapsulated(&uids::DeflatedImageFrameCompression, false),
+  native(
+    &uids::Papyrus3ImplicitVRLittleEndian,
+    ByteOrder::LittleEndian,
+    true,
+    false,
+  ),
+];
+
+/**
+ * Returns the transfer syntax with this UID, if known. The padding of UI
+ * values (a trailing NULL byte) is ignored.
+ */
+pub fn get_by_uid(uid: &str) -> Option<&'static TransferSyntax> {
+  let uid = uid.trim_end_matches(['\0', ' ']);
+  TRANSFER_SYNTAXES
+    .iter()
+    .find(|transfer_syntax| transfer_syntax.uid.uid == uid)
+}
+
+/**
+ * Returns all the transfer syntaxes rdicom knows about.
+ */
+pub fn all() -> &'static [TransferSyntax] {
+  TRANSFER_SYNTAXES
+}
+
+impl TryFrom<&str> for TransferSyntax {
+  type Error = DicomError;
+
+  fn try_from(uid: &str) -> Result<Self, Self::Error> {
+    get_by_uid(uid).copied().ok_or_else(|| {
+      DicomError::with_kind(
+        DicomErrorKind::UnsupportedTransferSyntax,
+        &format!(
+          "Unsupported transfer syntax {}",
+          uid.trim_end_matches(['\0', ' '])
+        ),
+      )
+    })
+  }
+}
+
+// Formats the transfer syntax with its name, e.g. Explicit VR Little Endian
+impl fmt::Display for TransferSyntax {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.uid.name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn uid_padding_is_ignored() {
+    assert_eq!(
+      get_by_uid("1.2.840.10008.1.2.1\0"),
+      Some(&ExplicitVRLittleEndian)
+    );
+    assert_eq!(
+      get_by_uid("1.2.840.10008.1.2 "),
+      Some(&ImplicitVRLittleEndian)
+    );
+    assert_eq!(get_by_uid("1.2.840.10008.1.2.1.9"), None);
+  }
+
+  #[test]
+  fn capabilities() {
+    let jpeg_baseline = TransferSyntax::try_from("1.2.840.10008.1.2.4.50").unwrap();
+    assert!(jpeg_baseline.encapsulated && jpeg_baseline.lossy && !jpeg_baseline.codec_available);
+    assert_eq!(jpeg_baseline.byte_order, ByteOrder::LittleEndian);
+    let rle = TransferSyntax::try_from("1.2.840.10008.1.2.5").unwrap();
+    assert!(rle.encapsulated && !rle.lossy);
+    let jpip = TransferSyntax::try_from("1.2.840.10008.1.2.4.95").unwrap();
+    assert!(jpip.deflated && !jpip.encapsulated && !jpip.codec_available);
+    let big_endian = TransferSyntax::try_from("1.2.840.10008.1.2.2").unwrap();
+    assert!(big_endian.codec_available && !big_endian.implicit);
+    assert_eq!(big_endian.byte_order, ByteOrder::BigEndian);
+    // Every transfer syntax is registered once
+    for transfer_syntax in all() {
+      assert_eq!(get_by_uid(transfer_syntax.uid.uid), Some(transfer_syntax));
+    }
+  }
+
+  #[test]
+  fn unsupported_transfer_syntax() {
+    // RFC 2557 MIME Encapsulation (retired) is not a dataset
+    let error = TransferSyntax::try_from("1.2.840.10008.1.2.6.1\0").unwrap_err();
+    assert_eq!(error.kind, DicomErrorKind::UnsupportedTransferSyntax);
+    assert_eq!(
+      error.details,
+      "Unsupported transfer syntax 1.2.840.10008.1.2.6.1"
+    );
+    assert_eq!(
+      DeflatedExplicitVRLittleEndian.to_string(),
+      "Deflated Explicit VR Little Endian"
+    );
+  }
+}
@@ -0,0 +1,169 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part06/chapter_A.html
+//
+// The registry of the UIDs defined by the standard (PS3.6 Annex A): SOP classes,
+// transfer syntaxes, well-known instances and frames of reference, coding
+// schemes... The constants and the lookup tables are generated by build.rs
+// from uids.csv.
+
+#![allow(non_upper_case_globals)]
+
+use core::fmt;
+
+/**
+ * The kind of object a UID of the registry identifies.
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum UidType {
+  SopClass,
+  MetaSopClass,
+  TransferSyntax,
+  WellKnownSopInstance,
+  WellKnownFrameOfReference,
+  SynchronizationFrameOfReference,
+  CodingScheme,
+  ApplicationContextName,
+  ServiceClass,
+  ApplicationHostingModel,
+  MappingResource,
+  LdapOid,
+}
+
+impl UidType {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      UidType::SopClass => "SOP Class",
+      UidType::MetaSopClass => "Meta SOP Class",
+      UidType::TransferSyntax => "Transfer Syntax",
+      UidType::WellKnownSopInstance => "Well-known SOP Instance",
+      UidType::WellKnownFrameOfReference => "Well-known Frame of Reference",
+      UidType::SynchronizationFrameOfReference => "Synchronization Frame of Reference",
+      UidType::CodingScheme => "Coding Scheme",
+      UidType::ApplicationContextName => "Application Context Name",
+      UidType::ServiceClass => "Service Class",
+      UidType::ApplicationHostingModel => "Application Hosting Model",
+      UidType::MappingResource => "Mapping Resource",
+      UidType::LdapOid => "LDAP OID",
+    }
+  }
+}
+
+impl fmt::Display for UidType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.as_str())
+  }
+}
+
+/**
+ * A UID of the registry, e.g. 1.2.840.10008.5.1.4.1.1.2 (CTImageStorage).
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Uid {
+  pub uid: &'static str,
+  pub keyword: &'static str,
+  pub name: &'static str,
+  pub uid_type: UidType,
+  // Retired from the standard, kept to read older instances
+  pub retired: bool,
+}
+
+include!(concat!(env!("OUT_DIR"), "/uids.rs"));
+
+/**
+ * Returns the UID of the registry with this value, if any. The padding of UI
+ * values (a trailing NULL byte) is ignored.
+ */
+pub fn get_by_uid(uid: &str) -> Option<&'static Uid> {
+  UIDS.get(uid.trim_end_matches(['\0', ' '])).copied()
+}
+
+/**
+ * Returns the UID of the registry with this keyword (e.g. CTImageStorage), if
+ * any. The case is ignored.
+ */
+pub fn get_by_keyword(keyword: &str) -> Option<&'static Uid> {
+  KEYWORDS.get(keyword.to_uppercase().as_str()).copied()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn uids_of_the_registry() {
+    let uid = get_by_uid("1.2.840.10008.5.1.4.1.1.2").unwrap();
+    assert_eq!(uid.keyword, "CTImageStorage");
+    assert_eq!(uid.name, "CT Image Storage");
+    assert_eq!(uid.uid_type, UidType::SopClass);
+    assert!(!uid.retired);
+
+    let uid = get_by_uid("1.2.840.10008.1.2.4.50").unwrap();
+    assert_eq!(uid.keyword, "JPEGBaseline8Bit");
+    assert_eq!(uid.uid_type, UidType::TransferSyntax);
+    assert_eq!(uid.uid_type.as_str(), "Transfer Syntax");
+
+    let uid = get_by_uid("1.2.840.10008.1.4.1.1").unwrap();
+    assert_eq!(uid.keyword, "TalairachBrainAtlas");
+    assert_eq!(uid.uid_type, UidType::WellKnownFrameOfReference);
+    assert_eq!(uid.uid_type.to_string(), "Well-known Frame of Reference");
+
+    let uid = get_by_uid("1.2.840.10008.1.2.2").unwrap();
+    assert_eq!(uid.keyword, "ExplicitVRBigEndian");
+    assert!(uid.retired);
+
+    // UI values are padded to an even length with a NULL byte
+    assert_eq!(
+      get_by_uid("1.2.840.10008.1.2.4.50\0").map(|uid| uid.keyword),
+      Some("JPEGBaseline8Bit")
+    );
+  }
+
+  #[test]
+  fn unknown_uid() {
+    assert_eq!(get_by_uid("1.2.840.10008.99.99"), None);
+    assert_eq!(get_by_uid(""), None);
+    assert_eq!(get_by_keyword("NotAKeyword"), None);
+  }
+
+  #[test]
+  fn uids_by_keyword() {
+    assert_eq!(
+      get_by_keyword("CTImageStorage").map(|uid| uid.uid),
+      Some("1.2.840.10008.5.1.4.1.1.2")
+    );
+    assert_eq!(
+      get_by_keyword("ctimagestorage").map(|uid| uid.uid),
+      Some("1.2.840.10008.5.1.4.1.1.2")
+    );
+
+    // The SOP class of the DICOMDIR files, as matched by scan
+    assert_eq!(MediaStorageDirectoryStorage.uid, "1.2.840.10008.1.3.10");
+    assert_eq!(
+      get_by_uid(MediaStorageDirectoryStorage.uid),
+      Some(&MediaStorageDirectoryStorage)
+    );
+    assert_eq!(
+      get_by_keyword("MediaStorageDirectoryStorage").map(|uid| uid.uid_type),
+      Some(UidType::SopClass)
+    );
+  }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_6.4.html
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::error::{DicomError, DicomErrorKind};
+
+/**
+ * Value Multiplicity (VM) of an attribute, i.e. the number of values it holds,
+ * e.g. 1, 1-3, 1-n or 2-2n.
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct ValueMultiplicity {
+  pub min: u16,
+  // None when there is no upper bound (n)
+  pub max: Option<u16>,
+  // The number of values is a multiple of step, e.g. 2 for 2-2n
+  pub step: u16,
+}
+
+impl ValueMultiplicity {
+  /**
+   * Exactly `count` values.
+   */
+  pub const fn exactly(count: u16) -> ValueMultiplicity {
+    ValueMultiplicity {
+      min: count,
+      max: Some(count),
+      step: 1,
+    }
+  }
+
+  /**
+   * At least `min` values, e.g. 1-n.
+   */
+  pub const fn at_least(min: u16) -> ValueMultiplicity {
+    ValueMultiplicity {
+      min,
+      max: None,
+      step: 1,
+    }
+  }
+
+  /**
+   * Whether an attribute with `count` values complies with the multiplicity.
+   */
+  pub fn contains(&self, count: usize) -> bool {
+    count >= self.min as usize
+      && self.max.is_none_or(|max| count <= max as usize)
+      && count.is_multiple_of(self.step as usize)
+  }
+}
+
+// Formats the multiplicity as in PS3.6, e.g. 1-n or 2-2n
+impl fmt::Display for ValueMultiplicity {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.max {
+      Some(max) if max == self.min => write!(f, "{}", self.min),
+      Some(max) => write!(f, "{}-{}", self.min, max),
+      None if self.step == 1 => write!(f, "{}-n", self.min),
+      None => write!(f, "{}-{}n", self.min, self.step),
+    }
+  }
+}
+
+impl TryFrom<&str> for ValueMultiplicity {
+  type Error = DicomError;
+
+  /**
+   * Parses a multiplicity as written in PS3.6. Alternatives such as "1-n or 1"
+   * are reduced to the first one.
+   */
+  fn try_from(vm: &str) -> Result<Self, Self::Error> {
+    let invalid = || {
+      DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!("Invalid value multiplicity: {}", vm),
+      )
+    };
+    let first = vm.split(" or ").next().unwrap_or_default().trim();
+    let (min, max) = match first.split_once('-') {
+      Some((min, max)) => (min, Some(max)),
+      None => (first, None),
+    };
+    let min = min.parse::<u16>().map_err(|_| invalid())?;
+    match max {
+      None => Ok(ValueMultiplicity::exactly(min)),
+      Some(max) => match max.strip_suffix('n') {
+        Some("") => Ok(ValueMultiplicity::at_least(min)),
+        // e.g. 2-2n, an even number of values
+        Some(step) => match step.parse::<u16>() {
+          Ok(step) if step > 0 => Ok(ValueMultiplicity {
+            min,
+            max: None,
+            step,
+          }),
+          _ => Err(invalid()),
+        },
+        None => Ok(ValueMultiplicity {
+          min,
+          max: Some(max.parse::<u16>().map_err(|_| invalid())?),
+          step: 1,
+        }),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(vm: &str) -> ValueMultiplicity {
+    ValueMultiplicity::try_from(vm).unwrap()
+  }
+
+  #[test]
+  fn multiplicities_of_ps3_6() {
+    assert_eq!(parse("1"), ValueMultiplicity::exactly(1));
+    assert_eq!(parse("1-n"), ValueMultiplicity::at_least(1));
+    assert_eq!(
+      parse("1-32"),
+      ValueMultiplicity {
+        min: 1,
+        max: Some(32),
+        step: 1
+      }
+    );
+    assert_eq!(
+      parse("3-3n"),
+      ValueMultiplicity {
+        min: 3,
+        max: None,
+        step: 3
+      }
+    );
+    assert_eq!(parse("1-n or 1"), ValueMultiplicity::at_least(1));
+    for vm in ["1", "6", "1-3", "6-n", "2-2n"] {
+      assert_eq!(parse(vm).to_string(), vm);
+    }
+    for invalid in ["", "n", "1-", "-3", "2-0n", "2-xn", "1 to 3"] {
+      assert!(ValueMultiplicity::try_from(invalid).is_err(), "{}", invalid);
+    }
+  }
+
+  #[test]
+  fn number_of_values() {
+    assert!(parse("1").contains(1));
+    assert!(!parse("1").contains(2));
+    assert!(!parse("1-3").contains(0));
+    assert!(parse("1-3").contains(3));
+    assert!(!parse("1-3").contains(4));
+    assert!(parse("1-n").contains(1000));
+    assert!(!parse("2-2n").contains(3));
+    assert!(parse("2-2n").contains(4));
+    assert!(!parse("3-3n").contains(0));
+  }
+}
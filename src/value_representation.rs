@@ -0,0 +1,271 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_6.2.html
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::error::{DicomError, DicomErrorKind};
+
+/**
+ * Value Representation (VR) of an attribute, i.e. the data type of its value.
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "tools", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueRepresentation {
+  AE,
+  AS,
+  AT,
+  CS,
+  DA,
+  DS,
+  DT,
+  FL,
+  FD,
+  IS,
+  LO,
+  LT,
+  OB,
+  OD,
+  OF,
+  OL,
+  OV,
+  OW,
+  PN,
+  SH,
+  SL,
+  SQ,
+  SS,
+  ST,
+  SV,
+  TM,
+  UC,
+  UI,
+  UL,
+  UN,
+  UR,
+  US,
+  UT,
+  UV,
+  // Items and delimitation items have no value representation
+  NA,
+}
+
+impl ValueRepresentation {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ValueRepresentation::AE => "AE",
+      ValueRepresentation::AS => "AS",
+      ValueRepresentation::AT => "AT",
+      ValueRepresentation::CS => "CS",
+      ValueRepresentation::DA => "DA",
+      ValueRepresentation::DS => "DS",
+      ValueRepresentation::DT => "DT",
+      ValueRepresentation::FL => "FL",
+      ValueRepresentation::FD => "FD",
+      ValueRepresentation::IS => "IS",
+      ValueRepresentation::LO => "LO",
+      ValueRepresentation::LT => "LT",
+      ValueRepresentation::OB => "OB",
+      ValueRepresentation::OD => "OD",
+      ValueRepresentation::OF => "OF",
+      ValueRepresentation::OL => "OL",
+      ValueRepresentation::OV => "OV",
+      ValueRepresentation::OW => "OW",
+      ValueRepresentation::PN => "PN",
+      ValueRepresentation::SH => "SH",
+      ValueRepresentation::SL => "SL",
+      ValueRepresentation::SQ => "SQ",
+      ValueRepresentation::SS => "SS",
+      ValueRepresentation::ST => "ST",
+      ValueRepresentation::SV => "SV",
+      ValueRepresentation::TM => "TM",
+      ValueRepresentation::UC => "UC",
+      ValueRepresentation::UI => "UI",
+      ValueRepresentation::UL => "UL",
+      ValueRepresentation::UN => "UN",
+      ValueRepresentation::UR => "UR",
+      ValueRepresentation::US => "US",
+      ValueRepresentation::UT => "UT",
+      ValueRepresentation::UV => "UV",
+      ValueRepresentation::NA => "na",
+    }
+  }
+
+  /**
+   * In explicit VR datasets, these VRs are followed by 2 reserved bytes and
+   * their length is on 4 bytes instead of 2. In implicit VR datasets, all the
+   * lengths are on 4 bytes.
+   * https://dicom.nema.org/medical/dicom/current/output/chtml/part05/chapter_7.html#sect_7.1.2
+   */
+  pub fn has_long_length(&self) -> bool {
+    matches!(
+      self,
+      ValueRepresentation::OB
+        | ValueRepresentation::OD
+        | ValueRepresentation::OF
+        | ValueRepresentation::OL
+        | ValueRepresentation::OV
+        | ValueRepresentation::OW
+        | ValueRepresentation::SQ
+        | ValueRepresentation::SV
+        | ValueRepresentation::UC
+        | ValueRepresentation::UN
+        | ValueRepresentation::UR
+        | ValueRepresentation::UT
+        | ValueRepresentation::UV
+    )
+  }
+
+  /**
+   * Length of the header (tag, VR and length) of an attribute in an explicit
+   * VR dataset.
+   */
+  pub fn explicit_header_length(&self) -> usize {
+    if self.has_long_length() {
+      12
+    } else {
+      8
+    }
+  }
+
+  /**
+   * Whether the value is text, possibly with several values separated by '\'.
+   */
+  pub fn is_string(&self) -> bool {
+    matches!(
+      self,
+      ValueRepresentation::AE
+        | ValueRepresentation::AS
+        | ValueRepresentation::CS
+        | ValueRepresentation::DA
+        | ValueRepresentation::DS
+        | ValueRepresentation::DT
+        | ValueRepresentation::IS
+        | ValueRepresentation::LO
+        | ValueRepresentation::LT
+        | ValueRepresentation::PN
+        | ValueRepresentation::SH
+        | ValueRepresentation::ST
+        | ValueRepresentation::TM
+        | ValueRepresentation::UC
+        | ValueRepresentation::UI
+        | ValueRepresentation::UR
+        | ValueRepresentation::UT
+    )
+  }
+
+  /**
+   * Byte used to pad values to an even length. UI values are padded with a NULL
+   * byte and the other strings with a space.
+   */
+  pub fn padding(&self) -> u8 {
+    match self {
+      ValueRepresentation::UI => b'\0',
+      _ if self.is_string() => b' ',
+      _ => b'\0',
+    }
+  }
+
+  /**
+   * Size in bytes of a single value for binary VRs. None for the VRs with a
+   * variable size (strings, SQ, UN).
+   */
+  pub fn fixed_size(&self) -> Option<usize> {
+    match self {
+      ValueRepresentation::OB => Some(1),
+      ValueRepresentation::OW | ValueRepresentation::SS | ValueRepresentation::US => Some(2),
+      ValueRepresentation::AT
+      | ValueRepresentation::FL
+      | ValueRepresentation::OF
+      | ValueRepresentation::OL
+      | ValueRepresentation::SL
+      | ValueRepresentation::UL => Some(4),
+      ValueRepresentation::FD
+      | ValueRepresentation::OD
+      | ValueRepresentation::OV
+      | ValueRepresentation::SV
+      | ValueRepresentation::UV => Some(8),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for ValueRepresentation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.as_str())
+  }
+}
+
+impl TryFrom<&[u8]> for ValueRepresentation {
+  type Error = DicomError;
+
+  fn try_from(vr: &[u8]) -> Result<Self, Self::Error> {
+    match vr {
+      b"AE" => Ok(ValueRepresentation::AE),
+      b"AS" => Ok(ValueRepresentation::AS),
+      b"AT" => Ok(ValueRepresentation::AT),
+      b"CS" => Ok(ValueRepresentation::CS),
+      b"DA" => Ok(ValueRepresentation::DA),
+      b"DS" => Ok(ValueRepresentation::DS),
+      b"DT" => Ok(ValueRepresentation::DT),
+      b"FL" => Ok(ValueRepresentation::FL),
+      b"FD" => Ok(ValueRepresentation::FD),
+      b"IS" => Ok(ValueRepresentation::IS),
+      b"LO" => Ok(ValueRepresentation::LO),
+      b"LT" => Ok(ValueRepresentation::LT),
+      b"OB" => Ok(ValueRepresentation::OB),
+      b"OD" => Ok(ValueRepresentation::OD),
+      b"OF" => Ok(ValueRepresentation::OF),
+      b"OL" => Ok(ValueRepresentation::OL),
+      b"OV" => Ok(ValueRepresentation::OV),
+      b"OW" => Ok(ValueRepresentation::OW),
+      b"PN" => Ok(ValueRepresentation::PN),
+      b"SH" => Ok(ValueRepresentation::SH),
+      b"SL" => Ok(ValueRepresentation::SL),
+      b"SQ" => Ok(ValueRepresentation::SQ),
+      b"SS" => Ok(ValueRepresentation::SS),
+      b"ST" => Ok(ValueRepresentation::ST),
+      b"SV" => Ok(ValueRepresentation::SV),
+      b"TM" => Ok(ValueRepresentation::TM),
+      b"UC" => Ok(ValueRepresentation::UC),
+      b"UI" => Ok(ValueRepresentation::UI),
+      b"UL" => Ok(ValueRepresentation::UL),
+      b"UN" => Ok(ValueRepresentation::UN),
+      b"UR" => Ok(ValueRepresentation::UR),
+      b"US" => Ok(ValueRepresentation::US),
+      b"UT" => Ok(ValueRepresentation::UT),
+      b"UV" => Ok(ValueRepresentation::UV),
+      _ => Err(DicomError::with_kind(
+        DicomErrorKind::InvalidFormat,
+        &format!("Unknown value representation: {:02x?}", vr),
+      )),
+    }
+  }
+}
+
+impl TryFrom<&str> for ValueRepresentation {
+  type Error = DicomError;
+
+  fn try_from(vr: &str) -> Result<Self, Self::Error> {
+    ValueRepresentation::try_from(vr.as_bytes())
+  }
+}
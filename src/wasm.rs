@@ -22,6 +22,7 @@
 
 extern crate alloc; // We need this in order to use alloc modules
 
+use crate::date_time::Date;
 use crate::instance::DicomValue;
 use crate::instance::Instance;
 
@@ -34,6 +35,8 @@ use crate::allocator;
 #[global_allocator]
 static ALLOCATOR: allocator::WasmAllocator = allocator::WasmAllocator::new();
 
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
 // We need CString for writing null-terminated string in wasm memory.
 use alloc::ffi::CString;
 
@@ -44,18 +47,18 @@ extern "C" {
   fn printError();
 }
 
+// addString is given the length of the string, which does not need to be null
+// terminated
 fn console_log(s: &str) {
   unsafe {
-    let c_str = CString::new(s).unwrap();
-    addString(c_str.as_ptr() as *const u8, s.len());
+    addString(s.as_ptr(), s.len());
     printString();
   }
 }
 
 fn console_error(s: &str) {
   unsafe {
-    let c_str = CString::new(s).unwrap();
-    addString(c_str.as_ptr() as *const u8, s.len());
+    addString(s.as_ptr(), s.len());
     printError();
   }
 }
@@ -78,9 +81,11 @@ fn panic(panic_info: &core::panic::PanicInfo<'_>) -> ! {
 
 /**
  * Creates an instance from a buffer containing a DICOM file.
+ * The instance borrows the buffer which must therefore outlive it.
+ * Returns null if the buffer could not be parsed, the error is logged.
  */
 #[no_mangle]
-pub extern "C" fn instance_from_ptr(ptr: *mut u8, len: usize) -> *const Instance {
+pub extern "C" fn instance_from_ptr(ptr: *mut u8, len: usize) -> *const Instance<'static> {
   let buffer = unsafe { alloc::slice::from_raw_parts(ptr, len) };
   match Instance::from(buffer) {
     Ok(instance) => {
@@ -88,7 +93,8 @@ pub extern "C" fn instance_from_ptr(ptr: *mut u8, len: usize) -> *const Instance
       alloc::boxed::Box::into_raw(p_instance)
     }
     Err(e) => {
-      panic!("error: {e} while creating the instance");
+      console_error(&format!("error: {e} while creating the instance"));
+      core::ptr::null()
     }
   }
 }
@@ -98,7 +104,9 @@ pub extern "C" fn instance_from_ptr(ptr: *mut u8, len: usize) -> *const Instance
  */
 #[no_mangle]
 pub extern "C" fn get_value_from_ptr(instance_ptr: *const Instance, tagid: u32) -> *const u8 {
-  let instance: &Instance = unsafe { instance_ptr.as_ref().unwrap() };
+  let Some(instance) = (unsafe { instance_ptr.as_ref() }) else {
+    return core::ptr::null();
+  };
   let tag = tagid.try_into();
   if let Ok(tag) = tag {
     if let Ok(dicom_value) = instance.get_value(&tag) {
@@ -115,16 +123,63 @@ pub extern "C" fn get_value_from_ptr(instance_ptr: *const Instance, tagid: u32)
   }
 }
 
-fn stream_number<T: Into<f64>>(value: T) -> *const u8 {
-  let fvalue: f64 = value.into();
-  let buffer: *mut u8 = unsafe { ALLOCATOR.alloc_t::<f64>(core::mem::size_of::<f64>()) };
-  let number_of_string_as_bytes = fvalue.to_le_bytes();
+// Stream to memory an array of numbers as f64. Javascript numbers are f64 so
+// 64 bits integers above 2^53 lose precision.
+// The buffer starts with the number of values on 4 bytes, followed by 4 bytes
+// of padding so the values are aligned on 8 bytes.
+fn stream_numbers<I: Iterator<Item = f64>>(values: I) -> *const u8 {
+  let fvalues = values.collect::<alloc::vec::Vec<f64>>();
+  let header_size = core::mem::size_of::<f64>();
+  let buffer_size = fvalues.len() * core::mem::size_of::<f64>();
+  let buffer = unsafe { ALLOCATOR.alloc_t::<f64>(header_size + buffer_size) };
+  let number_of_values: u32 = u32::try_from(fvalues.len()).unwrap();
   unsafe {
     core::ptr::copy_nonoverlapping(
-      number_of_string_as_bytes.as_ptr(),
+      number_of_values.to_le_bytes().as_ptr(),
       buffer,
-      core::mem::size_of::<f64>(),
+      core::mem::size_of::<u32>(),
+    );
+    core::ptr::copy_nonoverlapping(
+      fvalues.as_ptr() as *const u8,
+      buffer.wrapping_add(header_size),
+      buffer_size,
+    );
+  }
+  buffer
+}
+
+// Stream to memory an array of strings.
+// The buffer starts with the number of strings on 4 bytes, followed, for each
+// string, by its size on 4 bytes and its bytes without the terminating null
+// character.
+fn stream_strings(strings: &[String]) -> *const u8 {
+  // Compute the size of all the string put together + a 4 bytes for each string length
+  let buffer_size: usize = strings.iter().fold(0, |acc, value| acc + value.len() + 4);
+
+  // The buffer will contain the number of strings and then, for each string,
+  // the size of the string and a copy of the string without the terminating
+  // null character
+  let buffer = unsafe { ALLOCATOR.alloc_t::<usize>(buffer_size + core::mem::size_of::<u32>()) };
+  let mut ptr = buffer;
+
+  unsafe {
+    // Prefix the buffer with the number of element in the vector
+    let number_of_string: u32 = u32::try_from(strings.len()).unwrap();
+    core::ptr::copy_nonoverlapping(
+      number_of_string.to_le_bytes().as_ptr(),
+      ptr,
+      core::mem::size_of::<u32>(),
     );
+    ptr = ptr.wrapping_add(core::mem::size_of::<u32>());
+    // Then append the pointer to all the string
+    for s in strings {
+      let ssize = u32::try_from(s.len()).unwrap().to_le_bytes();
+      core::ptr::copy_nonoverlapping(ssize.as_ptr(), ptr, core::mem::size_of::<u32>());
+      ptr = ptr.wrapping_add(core::mem::size_of::<u32>());
+      // The strings are prefixed with their size, no terminal null character
+      core::ptr::copy_nonoverlapping(s.as_ptr(), ptr, s.len());
+      ptr = ptr.wrapping_add(s.len());
+    }
   }
   buffer
 }
@@ -136,76 +191,55 @@ fn dicom_value_to_memory(dicom_value: &DicomValue) -> *const u8 {
     DicomValue::AE(strings)
     | DicomValue::AS(strings)
     | DicomValue::CS(strings)
-    | DicomValue::DA(strings)
     | DicomValue::DS(strings)
     | DicomValue::DT(strings)
     | DicomValue::IS(strings)
     | DicomValue::LO(strings)
     | DicomValue::LT(strings)
-    | DicomValue::PN(strings)
     | DicomValue::SH(strings)
     | DicomValue::ST(strings)
     | DicomValue::TM(strings)
-    | DicomValue::UT(strings) => {
-      // Compute the size of all the string put together + a 4 bytes for each string length
-      let buffer_size: usize = strings.iter().fold(0, |acc, value| acc + value.len() + 4);
-
-      // The buffer will contain the number of strings and then, for each string,
-      // the size of the string and a copy of the string without the terminating
-      // null character
-      let buffer = unsafe { ALLOCATOR.alloc_t::<usize>(buffer_size + core::mem::size_of::<u32>()) };
-      let mut ptr = buffer;
-
-      unsafe {
-        // Prefix the buffer with the number of element in the vector
-        let number_of_string: u32 = u32::try_from(strings.len()).unwrap();
-        core::ptr::copy_nonoverlapping(
-          number_of_string.to_le_bytes().as_ptr(),
-          ptr,
-          core::mem::size_of::<u32>(),
-        );
-        ptr = ptr.wrapping_add(core::mem::size_of::<u32>());
-        // Then append the pointer to all the string
-        for s in strings {
-          let c_str = CString::new(s.clone()).unwrap();
-          let ssize = u32::try_from(s.len()).unwrap().to_le_bytes();
-          core::ptr::copy_nonoverlapping(ssize.as_ptr(), ptr, core::mem::size_of::<u32>());
-          ptr = ptr.wrapping_add(core::mem::size_of::<u32>());
-          // Do not copy the terminal null character
-          core::ptr::copy_nonoverlapping(c_str.as_ptr(), ptr as *mut i8, s.len());
-          ptr = ptr.wrapping_add(s.len());
-        }
-      }
-      buffer
-    }
-    DicomValue::UI(value) => {
-      let c_str = CString::new(value.as_str()).unwrap();
-      return c_str.into_raw() as *const u8;
-    }
-    DicomValue::SL(value) => stream_number(*value),
-    DicomValue::SS(value) => stream_number(*value),
-    DicomValue::UL(value) => stream_number(*value),
-    DicomValue::US(value) => stream_number(*value),
-    DicomValue::FD(values) => {
-      let buffer_size = values.len() * core::mem::size_of::<f64>();
-      let buffer = unsafe { ALLOCATOR.alloc_t::<f64>(buffer_size) };
-      unsafe {
-        core::ptr::copy_nonoverlapping(values.as_ptr() as *const u8, buffer, buffer_size);
-      }
-      buffer
+    | DicomValue::UC(strings)
+    | DicomValue::UT(strings) => stream_strings(strings),
+    // Dates are normalized, e.g. ACR-NEMA dates "2023.04.15" become "20230415"
+    DicomValue::DA(strings) => stream_strings(
+      &strings
+        .iter()
+        .map(|value| {
+          Date::try_from(value.as_str())
+            .map(|date| date.to_string())
+            .unwrap_or_else(|_| value.clone())
+        })
+        .collect::<alloc::vec::Vec<String>>(),
+    ),
+    DicomValue::PN(names) => stream_strings(
+      &names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<alloc::vec::Vec<String>>(),
+    ),
+    // A value with a null character (corrupted file) can not be a C string
+    DicomValue::UI(value) | DicomValue::UR(value) => {
+      CString::new(value.as_str()).map_or(core::ptr::null(), |c_str| c_str.into_raw() as *const u8)
     }
-    DicomValue::FL(values) => {
-      let buffer_size = values.len() * core::mem::size_of::<f64>();
-      let buffer = unsafe { ALLOCATOR.alloc_t::<f64>(buffer_size) };
-      let fvalues = values
+    DicomValue::SL(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::SS(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::UL(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::US(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    // Tags are streamed as numbers (e.g. 0x00280010)
+    DicomValue::AT(tags) => stream_numbers(
+      tags
         .iter()
-        .map(|&n| n as f64)
-        .collect::<alloc::vec::Vec<f64>>();
-      unsafe {
-        core::ptr::copy_nonoverlapping(fvalues.as_ptr() as *const u8, buffer, buffer_size);
-      }
-      buffer
+        .map(|tag| (((tag.group as u32) << 16) | tag.element as u32) as f64),
+    ),
+    DicomValue::FD(values) | DicomValue::OD(values) => stream_numbers(values.iter().copied()),
+    DicomValue::FL(values) | DicomValue::OF(values) => {
+      stream_numbers(values.iter().map(|&n| n as f64))
     }
+    DicomValue::OL(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::OV(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::UV(values) => stream_numbers(values.iter().map(|&n| n as f64)),
+    DicomValue::SV(values) => stream_numbers(values.iter().map(|&n| n as f64)),
     DicomValue::UN(values) | DicomValue::OB(values) => {
       // Allocate two u32, one for size of the buffer and one for its index (pointer)
       let buffer = unsafe { ALLOCATOR.alloc_t::<u32>(2) };
@@ -226,8 +260,21 @@ fn dicom_value_to_memory(dicom_value: &DicomValue) -> *const u8 {
       // Allocate two u32, one for size of the buffer and one for its index (pointer)
       let buffer = unsafe { ALLOCATOR.alloc_t::<u32>(2) };
       // OW is 16bits so 2 bytes
-      let buffer_size = u32::try_from(values.len() * 2).unwrap();
-      let ptr = u32::try_from(values.as_ptr() as u64).unwrap();
+      let buffer_size = values.len() * 2;
+      let ptr = match values {
+        // Points directly into the instance buffer
+        Cow::Borrowed(values) => values.as_ptr() as *const u8,
+        // The decoded values are dropped with the DicomValue, copy them
+        Cow::Owned(values) => {
+          let copy = unsafe { ALLOCATOR.alloc_t::<u16>(buffer_size) };
+          unsafe {
+            core::ptr::copy_nonoverlapping(values.as_ptr() as *const u8, copy, buffer_size);
+          }
+          copy
+        }
+      };
+      let buffer_size = u32::try_from(buffer_size).unwrap();
+      let ptr = u32::try_from(ptr as u64).unwrap();
       // TODO: We should check pointer size maybe
       let data: alloc::vec::Vec<u32> = vec![buffer_size, ptr];
       unsafe {
@@ -239,17 +286,9 @@ fn dicom_value_to_memory(dicom_value: &DicomValue) -> *const u8 {
       }
       buffer
     }
-    DicomValue::SeqEnd | DicomValue::SeqItemEnd => core::ptr::null(),
-    DicomValue::AT(_)
-    | DicomValue::SQ(_)
-    | DicomValue::OL(_)
-    | DicomValue::OV(_)
-    | DicomValue::OF(_)
-    | DicomValue::OD(_)
-    | DicomValue::SV(_)
-    | DicomValue::UC(_)
-    | DicomValue::UR(_)
-    | DicomValue::UV(_)
-    | DicomValue::SeqItem(_) => unimplemented!(),
+    // Sequences are not streamed
+    DicomValue::SeqEnd | DicomValue::SeqItemEnd | DicomValue::SQ(_) | DicomValue::SeqItem(_) => {
+      core::ptr::null()
+    }
   }
 }
@@ -0,0 +1,73 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Character set of the text values, declared by the dataset or by its items
+
+use std::fs;
+use std::path::PathBuf;
+
+use rdicom::character_set::CharacterSet;
+use rdicom::dicom_tags::PatientName;
+use rdicom::instance::{DicomValue, Instance};
+use rdicom::tags::TagPath;
+
+fn read(name: &str) -> Vec<u8> {
+  fs::read(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/data")
+      .join(name),
+  )
+  .unwrap()
+}
+
+fn patient_name(instance: &Instance, path: &str) -> String {
+  match instance
+    .get_value_by_path(&TagPath::try_from(path).unwrap())
+    .unwrap()
+  {
+    Some(DicomValue::PN(names)) => names[0].to_string(),
+    value => panic!("unexpected {:?}", value),
+  }
+}
+
+#[test]
+fn dataset_character_set() {
+  let buffer = read("latin1.dcm");
+  let instance = Instance::from(&buffer[..]).unwrap();
+  assert_eq!(
+    instance.character_set().unwrap(),
+    CharacterSet::from_terms(&["ISO_IR 100"])
+  );
+  assert_eq!(patient_name(&instance, "PatientName"), "Müller^Jürgen");
+}
+
+#[test]
+fn item_character_set_does_not_leak() {
+  // Only the item of ReferencedStudySequence declares ISO_IR 100
+  let buffer = read("cs_nested_item.dcm");
+  let instance = Instance::from(&buffer[..]).unwrap();
+  assert_eq!(instance.character_set().unwrap(), CharacterSet::default());
+  assert_eq!(patient_name(&instance, "PatientName"), "Doe^John");
+  assert_eq!(
+    patient_name(&instance, "ReferencedStudySequence[0].PatientName"),
+    "Müller"
+  );
+  assert!(instance.get_value(&PatientName).unwrap().is_some());
+}
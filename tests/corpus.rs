@@ -0,0 +1,170 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Feeds truncated and corrupted versions of the files of tests/data to the
+// parser. Malformed inputs must be rejected with a DicomError, never panic.
+// The fuzz binary (src/fuzz.rs) does the same with more mutations on any corpus.
+
+use std::fs;
+use std::io::Cursor;
+use std::panic;
+use std::path::PathBuf;
+
+use rdicom::dicom_tags::{PatientName, PixelData, SOPInstanceUID, SpecificCharacterSet, StudyDate};
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+
+// Number of random mutations of each file of the corpus
+const MUTATIONS: usize = 300;
+
+fn corpus() -> Vec<(String, Vec<u8>)> {
+  let folder = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+  let mut files = fs::read_dir(folder)
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension().is_some_and(|extension| extension == "dcm"))
+    .map(|path| {
+      let name = path.file_name().unwrap().to_string_lossy().to_string();
+      (name, fs::read(path).unwrap())
+    })
+    .collect::<Vec<_>>();
+  files.sort();
+  files
+}
+
+// xorshift64*, reproducible across runs and platforms
+struct Random(u64);
+
+impl Random {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 >> 12;
+    self.0 ^= self.0 << 25;
+    self.0 ^= self.0 >> 27;
+    self.0.wrapping_mul(0x2545F4914F6CDD1D)
+  }
+
+  fn below(&mut self, bound: usize) -> usize {
+    (self.next() % bound as u64) as usize
+  }
+}
+
+// Values likely to hit the edge cases of the parser when written over a
+// length, a tag or a VR
+const INTERESTING: &[&[u8]] = &[
+  &[0x00, 0x00, 0x00, 0x00],
+  &[0xFF, 0xFF, 0xFF, 0xFF],
+  &[0xFE, 0xFF, 0x00, 0xE0],
+  &[0xFE, 0xFF, 0xDD, 0xE0],
+  b"SQ",
+  b"UN",
+  b"\x1B$B",
+];
+
+fn mutate(original: &[u8], random: &mut Random) -> Vec<u8> {
+  let mut buffer = original.to_vec();
+  for _ in 0..1 + random.below(3) {
+    let offset = random.below(buffer.len());
+    match random.below(3) {
+      0 => buffer[offset] ^= 1 << random.below(8),
+      1 => buffer[offset] = random.next() as u8,
+      _ => {
+        let value = INTERESTING[random.below(INTERESTING.len())];
+        let end = (offset + value.len()).min(buffer.len());
+        buffer[offset..end].copy_from_slice(&value[..end - offset]);
+      }
+    }
+  }
+  buffer
+}
+
+// Goes through everything the parser offers on an input, errors are expected
+fn parse(buffer: &[u8]) {
+  let Ok(instance) = Instance::from(buffer) else {
+    return;
+  };
+  let _ = instance.character_set();
+  for tag in [
+    &SpecificCharacterSet,
+    &StudyDate,
+    &PatientName,
+    &SOPInstanceUID,
+    &PixelData,
+  ] {
+    let _ = instance.get_value(tag);
+    let _ = instance.get_values(tag);
+  }
+  for step in instance.walk() {
+    let Ok(step) = step else {
+      break;
+    };
+    if let WalkEvent::Attribute(attribute) = step.event {
+      let _ = instance.get_value_by_path(&step.path);
+      if let Ok(value) = DicomValue::from_dicom_attribute(&attribute, &instance) {
+        let _ = value.to_string();
+        let _ = value.to_dates();
+        let _ = value.to_times();
+        let _ = value.to_date_times();
+        let _ = value.to_ages();
+        let _ = value.to_f64s();
+        let _ = value.to_i64s();
+      }
+    }
+  }
+}
+
+#[test]
+fn corpus_parses() {
+  for (name, buffer) in corpus() {
+    let instance = Instance::from(&buffer[..]);
+    // truncated.dcm is only broken past its header
+    assert!(instance.is_ok(), "{}: {:?}", name, instance.err());
+    // Read on demand, the attributes are the same
+    let lazy = Instance::from_reader(Cursor::new(buffer.clone()));
+    assert!(lazy.is_ok(), "{}: {:?}", name, lazy.err());
+    let (instance, lazy) = (instance.unwrap(), lazy.unwrap());
+    let attributes = |instance: &Instance| {
+      instance
+        .walk()
+        .map(|step| format!("{:?}", step.map(|step| step.event)))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(attributes(&instance), attributes(&lazy), "{}", name);
+  }
+}
+
+#[test]
+fn malformed_inputs_do_not_panic() {
+  // The panics are collected below, no need for their backtrace
+  panic::set_hook(Box::new(|_| {}));
+  let mut random = Random(1);
+  let mut panics = vec![];
+  for (name, original) in corpus() {
+    let truncated = (0..original.len()).map(|length| original[..length].to_vec());
+    let mutated = (0..MUTATIONS)
+      .map(|_| mutate(&original, &mut random))
+      .collect::<Vec<_>>();
+    for buffer in truncated.chain(mutated) {
+      if panic::catch_unwind(|| parse(&buffer)).is_err() {
+        panics.push(format!("{} ({} bytes)", name, buffer.len()));
+      }
+    }
+  }
+  let _ = panic::take_hook();
+  assert!(panics.is_empty(), "the parser panicked on {:?}", panics);
+}
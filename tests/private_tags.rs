@@ -0,0 +1,145 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Private attributes are resolved with the Private Creator reserving their block
+
+use std::fs;
+use std::path::PathBuf;
+
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+use rdicom::tags::TagPath;
+use rdicom::value_representation::ValueRepresentation;
+
+fn read(name: &str) -> Vec<u8> {
+  fs::read(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/data")
+      .join(name),
+  )
+  .unwrap()
+}
+
+// The name and VR of the private attributes, by path
+fn private_tags(instance: &Instance) -> Vec<(String, &'static str, ValueRepresentation)> {
+  instance
+    .walk()
+    .filter_map(|step| match step.unwrap() {
+      step if step.path.tag().group % 2 == 1 => match step.event {
+        WalkEvent::Attribute(attribute) => {
+          Some((step.path.to_string(), attribute.tag.name, attribute.vr))
+        }
+        _ => None,
+      },
+      _ => None,
+    })
+    .collect()
+}
+
+fn value(instance: &Instance, path: &str) -> Option<String> {
+  instance
+    .get_value_by_path(&TagPath::try_from(path).unwrap())
+    .unwrap()
+    .map(|value| value.to_string())
+}
+
+#[test]
+fn creators_in_explicit_and_implicit_vr() {
+  for name in ["priv_explicit.dcm", "priv_implicit.dcm"] {
+    let buffer = read(name);
+    let instance = Instance::from(&buffer[..]).unwrap();
+    let tags = private_tags(&instance);
+    // The VRs come from the private dictionary in implicit VR
+    for expected in [
+      ("0019100C", "BValue", ValueRepresentation::IS),
+      (
+        "0019100E",
+        "DiffusionGradientDirection",
+        ValueRepresentation::FD,
+      ),
+      ("00291008", "CSAImageHeaderType", ValueRepresentation::CS),
+      ("00291010", "CSAImageHeaderInfo", ValueRepresentation::OB),
+      ("20011003", "DiffusionBFactor", ValueRepresentation::FL),
+    ] {
+      assert!(
+        tags.contains(&(expected.0.to_string(), expected.1, expected.2)),
+        "{}: {:?}",
+        name,
+        expected
+      );
+    }
+    assert_eq!(
+      value(&instance, "0019100E").as_deref(),
+      Some("0.5\\-0.5\\0.707"),
+      "{}",
+      name
+    );
+    assert!(matches!(
+      instance.get_value_by_path(&TagPath::try_from("20011003").unwrap()),
+      Ok(Some(DicomValue::FL(values))) if values[..] == [1000.0]
+    ));
+  }
+}
+
+#[test]
+fn creators_of_an_item() {
+  for name in ["priv_explicit.dcm", "priv_implicit.dcm"] {
+    let buffer = read(name);
+    let instance = Instance::from(&buffer[..]).unwrap();
+    let tags = private_tags(&instance);
+    // Block 10 is reserved by SIEMENS CSA HEADER in the dataset but by SIEMENS
+    // MEDCOM HEADER in the item
+    let (_, tag_name, _) = tags
+      .iter()
+      .find(|(path, _, _)| path == "00291140[0].00291041")
+      .unwrap();
+    assert_eq!(*tag_name, "ApplicationHeaderType", "{}", name);
+    assert_eq!(
+      value(&instance, "00291140[0].00291041").as_deref(),
+      Some("INNER")
+    );
+    // The creators of the dataset do not apply to the item
+    let (_, tag_name, _) = tags
+      .iter()
+      .find(|(path, _, _)| path == "00291140[0].00291142")
+      .unwrap();
+    assert_eq!(*tag_name, "Unknown Tag & Data", "{}", name);
+  }
+}
+
+#[test]
+fn unknown_creator() {
+  let buffer = read("priv_implicit.dcm");
+  let instance = Instance::from(&buffer[..]).unwrap();
+  let tags = private_tags(&instance);
+  assert!(tags.contains(&(
+    "00431039".to_string(),
+    "Unknown Tag & Data",
+    ValueRepresentation::UN
+  )));
+  // The VR written in the file is kept in explicit VR
+  let buffer = read("priv_explicit.dcm");
+  let instance = Instance::from(&buffer[..]).unwrap();
+  let tags = private_tags(&instance);
+  assert!(tags.contains(&(
+    "00431039".to_string(),
+    "Unknown Tag & Data",
+    ValueRepresentation::IS
+  )));
+}
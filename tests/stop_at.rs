@@ -0,0 +1,64 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Instances only read up to a top level attribute (e.g. PixelData)
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use rdicom::dicom_tags::{IconImageSequence, PixelData, Rows};
+use rdicom::instance::{DicomValue, Instance};
+use rdicom::tags::TagPath;
+
+fn header(name: &str) -> Instance<'static> {
+  let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/data")
+    .join(name);
+  Instance::from_buf_reader_until(BufReader::new(File::open(path).unwrap()), PixelData).unwrap()
+}
+
+#[test]
+fn top_level_attributes_past_stop_are_absent() {
+  let instance = header("explicit_le.dcm");
+  assert!(matches!(instance.get_value(&PixelData), Ok(None)));
+  assert!(matches!(
+    instance.get_value(&Rows),
+    Ok(Some(DicomValue::US(_)))
+  ));
+}
+
+#[test]
+fn nested_attributes_past_stop_are_present() {
+  // The thumbnail of IconImageSequence comes before the PixelData of the image
+  let instance = header("icon_image.dcm");
+  match instance.get_value(&PixelData) {
+    Ok(Some(DicomValue::OB(value))) => assert_eq!(value, [1, 2, 3, 4]),
+    value => panic!("unexpected {:?}", value),
+  }
+  let path = TagPath::try_from("IconImageSequence[0].PixelData").unwrap();
+  assert!(matches!(
+    instance.get_value_by_path(&path),
+    Ok(Some(DicomValue::OB(_)))
+  ));
+  let values = instance.get_values(&PixelData).unwrap();
+  assert_eq!(values.len(), 1);
+  assert_eq!(values[0].0.components[0].tag, IconImageSequence);
+}
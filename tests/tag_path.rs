@@ -0,0 +1,87 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Tag paths given by the walk of an instance, read back to get the values
+
+use std::fs;
+use std::path::PathBuf;
+
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+use rdicom::tags::TagPath;
+
+#[test]
+fn walked_paths_can_be_parsed() {
+  let buffer =
+    fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/nested.dcm")).unwrap();
+  let instance = Instance::from(&buffer[..]).unwrap();
+  let mut paths = vec![];
+  for step in instance.walk() {
+    let step = step.unwrap();
+    let path = TagPath::try_from(&step.path.to_string()).unwrap();
+    assert_eq!(path, step.path);
+    if let WalkEvent::Attribute(_) = step.event {
+      let value = instance.get_value_by_path(&path).unwrap().unwrap();
+      paths.push(format!("{} {}", path, value.to_string()));
+    }
+  }
+  // After the 5 attributes of the file meta information
+  assert_eq!(
+    paths[5..],
+    [
+      "00080060 SR",
+      "0040A730[0].0040A010 HAS",
+      "0040A730[0].0040A168[0].00080100 N1",
+      "0040A730[0].0040A168[0].00080104 Nested",
+      "0040A730[1].0040A010 CON",
+      "0040A730[1].0040A168[0].00080100 N1",
+      "0040A730[1].0040A168[0].00080104 Nested",
+      "0040A730[1].0040A168[0].00080105 X",
+    ]
+  );
+}
+
+#[test]
+fn paths_to_items() {
+  let buffer =
+    fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/nested.dcm")).unwrap();
+  let instance = Instance::from(&buffer[..]).unwrap();
+  let value = |path: &str| {
+    instance
+      .get_value_by_path(&TagPath::try_from(path).unwrap())
+      .unwrap()
+  };
+  assert_eq!(
+    value("0040A730[1].0040A168[0]"),
+    Some(DicomValue::SeqItem(vec![
+      DicomValue::SH(vec!["N1".to_string()]),
+      DicomValue::LO(vec!["Nested".to_string()]),
+      DicomValue::CS(vec!["X".to_string()]),
+    ]))
+  );
+  assert!(matches!(value("0040A730[0]"), Some(DicomValue::SeqItem(values)) if values.len() == 2));
+  assert_eq!(value("0040A730[2]"), Some(DicomValue::SeqItem(vec![])));
+  assert!(matches!(value("0040A730"), Some(DicomValue::SQ(items)) if items.len() == 3));
+  // ContentSequence only has 3 items, ConceptCodeSequence 1
+  assert_eq!(value("0040A730[3]"), None);
+  assert_eq!(value("0040A730[7]"), None);
+  assert_eq!(value("0040A730[0].0040A168[1]"), None);
+  // Not a sequence
+  assert_eq!(value("00080060[0]"), None);
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// The sample files of tests/data hold the same dataset in each transfer syntax
+// and must all read the same values.
+
+use std::fs;
+use std::path::PathBuf;
+
+use rdicom::dicom_tags::{
+  Columns, InstanceNumber, Item, PatientName, PixelData, PixelSpacing, Rows,
+};
+use rdicom::instance::{DicomValue, Instance, WalkEvent};
+use rdicom::tags::TagPath;
+use rdicom::transfer_syntax::{self, TransferSyntax};
+
+fn read(name: &str) -> Vec<u8> {
+  fs::read(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/data")
+      .join(name),
+  )
+  .unwrap()
+}
+
+fn check(name: &str, transfer_syntax: TransferSyntax) {
+  let buffer = read(name);
+  let instance = Instance::from(&buffer[..]).unwrap();
+  assert_eq!(instance.transfer_syntax, transfer_syntax, "{}", name);
+  match instance.get_value(&PatientName).unwrap() {
+    Some(DicomValue::PN(names)) => assert_eq!(names[0].to_string(), "Doe^John", "{}", name),
+    value => panic!("{}: unexpected {:?}", name, value),
+  }
+  assert_eq!(instance.get_i64(&Rows).unwrap(), Some(8), "{}", name);
+  assert_eq!(instance.get_i64(&Columns).unwrap(), Some(8), "{}", name);
+  assert_eq!(
+    instance.get_i64(&InstanceNumber).unwrap(),
+    Some(7),
+    "{}",
+    name
+  );
+  assert_eq!(
+    instance.get_f64s(&PixelSpacing).unwrap(),
+    Some(vec![0.5, 0.25]),
+    "{}",
+    name
+  );
+  match instance.get_value(&PixelData).unwrap() {
+    Some(DicomValue::OW(pixels)) => {
+      assert_eq!(pixels.to_vec(), (0..64).collect::<Vec<u16>>(), "{}", name)
+    }
+    value => panic!("{}: unexpected {:?}", name, value),
+  }
+  // The second item has an undefined length
+  for (path, code) in [
+    ("ProcedureCodeSequence[0].CodeValue", "C1"),
+    ("ProcedureCodeSequence[1].CodeValue", "C2"),
+  ] {
+    assert_eq!(
+      instance
+        .get_value_by_path(&TagPath::try_from(path).unwrap())
+        .unwrap(),
+      Some(DicomValue::SH(vec![code.to_string()])),
+      "{}: {}",
+      name,
+      path
+    );
+  }
+}
+
+#[test]
+fn implicit_vr_little_endian() {
+  check("implicit_le.dcm", transfer_syntax::ImplicitVRLittleEndian);
+}
+
+#[test]
+fn explicit_vr_little_endian() {
+  check("explicit_le.dcm", transfer_syntax::ExplicitVRLittleEndian);
+}
+
+#[test]
+fn explicit_vr_big_endian() {
+  check("explicit_be.dcm", transfer_syntax::ExplicitVRBigEndian);
+}
+
+#[test]
+fn deflated_explicit_vr_little_endian() {
+  check(
+    "deflated.dcm",
+    transfer_syntax::DeflatedExplicitVRLittleEndian,
+  );
+}
+
+#[test]
+fn datasets_without_preamble() {
+  // The transfer syntax is guessed from the first attributes
+  check("raw_explicit.dcm", transfer_syntax::ExplicitVRLittleEndian);
+  check(
+    "acrnema_implicit.dcm",
+    transfer_syntax::ImplicitVRLittleEndian,
+  );
+}
+
+#[test]
+fn encapsulated_pixel_data() {
+  let buffer = read("encapsulated.dcm");
+  let instance = Instance::from(&buffer[..]).unwrap();
+  assert_eq!(
+    instance.transfer_syntax,
+    TransferSyntax::try_from("1.2.840.10008.1.2.4.50").unwrap()
+  );
+  assert!(instance.transfer_syntax.encapsulated && instance.transfer_syntax.lossy);
+  assert_eq!(instance.get_i64(&Rows).unwrap(), Some(8));
+  // The basic offset table then the fragments of the JPEG stream
+  let pixel_data = instance
+    .iter()
+    .map(|attribute| attribute.unwrap())
+    .find(|attribute| attribute.tag == PixelData)
+    .unwrap();
+  let fragments = pixel_data
+    .subattributes
+    .iter()
+    .filter(|fragment| fragment.tag == Item)
+    .map(|fragment| fragment.length)
+    .collect::<Vec<_>>();
+  assert_eq!(fragments, [0, 204, 6]);
+  let last = &pixel_data.subattributes[2];
+  assert_eq!(
+    instance
+      .buffer
+      .bytes(last.data_offset, last.data_length)
+      .unwrap(),
+    b"\xFF\xD8yy\xFF\xD9"
+  );
+}
+
+#[test]
+fn big_endian_values_match_little_endian() {
+  // Every binary VR, written in both byte orders
+  let values = |name: &str| {
+    let buffer = read(name);
+    let instance = Instance::from(&buffer[..]).unwrap();
+    instance
+      .walk()
+      .filter_map(|step| match step.unwrap().event {
+        WalkEvent::Attribute(attribute) if attribute.group != 0x0002 => Some(format!(
+          "{:?}",
+          DicomValue::from_dicom_attribute(&attribute, &instance).unwrap()
+        )),
+        _ => None,
+      })
+      .collect::<Vec<_>>()
+  };
+  let little_endian = values("vrs_le.dcm");
+  assert_eq!(little_endian.len(), 16);
+  assert_eq!(values("vrs_be.dcm"), little_endian);
+}
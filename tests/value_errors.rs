@@ -0,0 +1,66 @@
+// Copyright (c) 2023 Jean-Daniel Michaud
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Errors decoding a value tell the path of the attribute, with item indices
+
+use std::fs;
+use std::path::PathBuf;
+
+use rdicom::dicom_tags::{ReferencedSOPInstanceUID, SliceThickness};
+use rdicom::error::DicomError;
+use rdicom::instance::Instance;
+use rdicom::tags::TagPath;
+
+// Both values are in the second item of ReferencedStudySequence
+const UID_PATH: &str = "00081110[1].00081155";
+const THICKNESS_PATH: &str = "00081110[1].00180050";
+
+fn path<T>(result: Result<T, DicomError>) -> Option<String> {
+  result.err().and_then(|error| error.path)
+}
+
+#[test]
+fn nested_value_errors_have_their_path() {
+  let buffer =
+    fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/bad_nested_value.dcm"))
+      .unwrap();
+  let instance = Instance::from(&buffer[..]).unwrap();
+  assert_eq!(
+    path(instance.get_value(&ReferencedSOPInstanceUID)).as_deref(),
+    Some(UID_PATH)
+  );
+  assert_eq!(
+    path(instance.get_values(&ReferencedSOPInstanceUID)).as_deref(),
+    Some(UID_PATH)
+  );
+  let by_path = TagPath::try_from("ReferencedStudySequence[1].ReferencedSOPInstanceUID").unwrap();
+  assert_eq!(
+    path(instance.get_value_by_path(&by_path)).as_deref(),
+    Some(UID_PATH)
+  );
+  assert_eq!(
+    path(instance.get_f64s(&SliceThickness)).as_deref(),
+    Some(THICKNESS_PATH)
+  );
+  assert_eq!(
+    path(instance.get_f64(&SliceThickness)).as_deref(),
+    Some(THICKNESS_PATH)
+  );
+}
@@ -161,7 +161,8 @@ function fromCString(rdicom, offset) {
 }
 
 function fromF64(rdicom, offset) {
-  const memory = new Float64Array(rdicom.env.memory.buffer, offset)
+  // The number of values is on 4 bytes, followed by 4 bytes of padding
+  const memory = new Float64Array(rdicom.env.memory.buffer, offset + 8)
   return memory[0];
 }
 
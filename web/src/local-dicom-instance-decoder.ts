@@ -75,7 +75,13 @@ export class LocalDicomInstanceDecoder {
       }
       case 'Array<number>':
       case 'Array<number | undefined>': {
-        return this.fromCStringArray(addr).map(s => parseFloat(s)) as any;
+        switch (vr) {
+          case 'DS':
+          case 'IS':
+            return this.fromCStringArray(addr).map(s => parseFloat(s)) as any;
+          default:
+            return Array.from(this.fromF64Array(addr)) as any;
+        }
       }
       case 'Array<string>':
       case 'Array<string | undefined>': {
@@ -85,12 +91,10 @@ export class LocalDicomInstanceDecoder {
         const data = this.fromCStringArray(addr);
         const date = data[0];
         if (date === undefined) return undefined;
-        const canondatestr = (date.includes('.'))
-          ? date.split('.').join('') // ACR-NEMA Standard 300 date format
-          : date;
-        const year = Number(canondatestr.substr(0, 4));
-        const month = Number(canondatestr.substr(4, 2));
-        const day = Number(canondatestr.substr(6, 2));
+        // Dates are normalized to YYYYMMDD by rdicom
+        const year = Number(date.substr(0, 4));
+        const month = Number(date.substr(4, 2));
+        const day = Number(date.substr(6, 2));
         // Yes, Date constructores requests the monthIndex...
         return new Date(year, month - 1, day) as any;
       }
@@ -134,6 +138,9 @@ export class LocalDicomInstanceDecoder {
     // Set the content of the DICOM file to the wasm memory at index ptr
     memory.set(new Uint8Array(buffer), ptr);
     const handle = instance_from_ptr(ptr, buffer.byteLength);
+    if (handle === 0) {
+      throw new Error('LocalDicomInstanceDecoder: could not parse the DICOM file (see the rdicom error)');
+    }
     return handle;
   }
 
@@ -161,8 +168,13 @@ export class LocalDicomInstanceDecoder {
   }
 
   fromF64(offset: number): number {
-    const float64 = new Float64Array(this.memory.buffer, offset)
-    return float64[0];
+    return this.fromF64Array(offset)[0];
+  }
+
+  fromF64Array(offset: number): Float64Array {
+    // The number of values is on 4 bytes, followed by 4 bytes of padding
+    const numberOfValues = new Uint32Array(this.memory.buffer, offset)[0];
+    return new Float64Array(this.memory.buffer, offset + 8, numberOfValues);
   }
 
   fromArrayBuffer(offset: number): Uint8Array {
@@ -286,15 +298,4 @@ export class LocalDicomInstanceDecoder {
 
     return this;
   }
-
-  private toDate(datestr: string | undefined): Date | undefined {
-    if (datestr === undefined) return undefined;
-    const canondatestr = (datestr.includes('.'))
-      ? datestr.split('.').join('') // ACR-NEMA Standard 300 date format
-      : datestr;
-    const year = Number(canondatestr.substr(0, 4));
-    const month = Number(canondatestr.substr(4, 2));
-    const day = Number(canondatestr.substr(6, 2));
-    return new Date(year, month, day);
-  }
 }